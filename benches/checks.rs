@@ -56,6 +56,37 @@ fn bench_checks(c: &mut Criterion) {
     temp.close().unwrap();
 }
 
+fn bench_read(c: &mut Criterion) {
+    let dict = typos_cli::dict::BuiltIn::new(Default::default());
+    let tokenizer = typos::tokens::Tokenizer::new();
+
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let mut group = c.benchmark_group("read_file");
+    for (name, sample) in data::DATA {
+        let sample_path = temp.child(name);
+        sample_path.write_str(sample).unwrap();
+
+        let len = sample.len();
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("read", name), &len, |b, _| {
+            b.iter(|| {
+                let buffer = typos_cli::file::FileBuffer::read(sample_path.path()).unwrap();
+                typos::check_bytes(&buffer, &tokenizer, &dict).count()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("mmap", name), &len, |b, _| {
+            b.iter(|| {
+                let buffer = typos_cli::file::FileBuffer::map(sample_path.path()).unwrap();
+                typos::check_bytes(&buffer, &tokenizer, &dict).count()
+            });
+        });
+    }
+    group.finish();
+
+    temp.close().unwrap();
+}
+
 #[derive(Debug, Default)]
 pub struct PrintSilent;
 
@@ -65,5 +96,5 @@ impl typos_cli::report::Report for PrintSilent {
     }
 }
 
-criterion_group!(benches, bench_checks,);
+criterion_group!(benches, bench_checks, bench_read);
 criterion_main!(benches);
@@ -0,0 +1,77 @@
+#ifndef TYPOS_H
+#define TYPOS_H
+
+/* This file is code-genned by cbindgen, see `tests/header.rs` */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The built-in corrections.
+typedef struct TyposDictionary TyposDictionary;
+
+// Splits a buffer into identifiers and words.
+typedef struct TyposTokenizer TyposTokenizer;
+
+// The typos found by `typos_check`.
+typedef struct TyposTypoList TyposTypoList;
+
+// A typo found in a buffer.
+//
+// Owned by its `TyposTypoList`.
+typedef struct {
+  // Offset of `typo` within the checked buffer.
+  size_t byte_offset;
+  const char *typo;
+  // Suggested replacements, empty for words that are never valid.
+  const char *const *corrections;
+  size_t corrections_len;
+} TyposTypo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a tokenizer with the default settings.
+//
+// Free with `typos_tokenizer_free`.
+TyposTokenizer *typos_tokenizer_new(void);
+
+void typos_tokenizer_free(TyposTokenizer *tokenizer);
+
+// Create a dictionary of the built-in corrections.
+//
+// `locale` is one of `en`, `en-us`, `en-gb`, `en-ca`, `en-au`, or `NULL` for `en`.  Returns
+// `NULL` for an unknown `locale`.
+//
+// Free with `typos_dictionary_free`.
+TyposDictionary *typos_dictionary_new(const char *locale);
+
+void typos_dictionary_free(TyposDictionary *dictionary);
+
+// Find the typos in `buffer`.
+//
+// `buffer` may be `NULL` when `len` is `0`.  Returns `NULL` if a handle is `NULL`.
+//
+// Free with `typos_typo_list_free`.
+TyposTypoList *typos_check(const TyposTokenizer *tokenizer,
+                           const TyposDictionary *dictionary,
+                           const uint8_t *buffer,
+                           size_t len);
+
+size_t typos_typo_list_len(const TyposTypoList *list);
+
+// The typo at `index`, or `NULL` if out of bounds.
+//
+// Valid until the list is freed.
+const TyposTypo *typos_typo_list_get(const TyposTypoList *list, size_t index);
+
+void typos_typo_list_free(TyposTypoList *list);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* TYPOS_H */
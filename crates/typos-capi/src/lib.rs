@@ -0,0 +1,244 @@
+//! C API for finding typos.
+//!
+//! See `include/typos.h` for the generated header.
+//!
+//! All handles are created and freed by this library.  Strings are NUL-terminated UTF-8.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// Splits a buffer into identifiers and words.
+pub struct TyposTokenizer {
+    inner: typos::tokens::Tokenizer,
+}
+
+/// The built-in corrections.
+pub struct TyposDictionary {
+    inner: typos_cli::dict::BuiltIn,
+}
+
+/// A typo found in a buffer.
+///
+/// Owned by its `TyposTypoList`.
+#[repr(C)]
+pub struct TyposTypo {
+    /// Offset of `typo` within the checked buffer.
+    pub byte_offset: usize,
+    pub typo: *const c_char,
+    /// Suggested replacements, empty for words that are never valid.
+    pub corrections: *const *const c_char,
+    pub corrections_len: usize,
+}
+
+/// The typos found by `typos_check`.
+pub struct TyposTypoList {
+    typos: Vec<TyposTypo>,
+    // Keep the data `typos` points to alive.
+    _strings: Vec<CString>,
+    _corrections: Vec<Vec<*const c_char>>,
+}
+
+impl TyposTypoList {
+    fn new(typos: impl Iterator<Item = typos::Typo<'static>>) -> Self {
+        let mut list = Self {
+            typos: Vec::new(),
+            _strings: Vec::new(),
+            _corrections: Vec::new(),
+        };
+        for typo in typos {
+            let corrections = match typo.corrections {
+                typos::Status::Valid => continue,
+                typos::Status::Invalid => Vec::new(),
+                typos::Status::Corrections(corrections) => corrections,
+            };
+            let corrections: Vec<_> = corrections
+                .iter()
+                .map(|c| list.intern(c.as_ref()))
+                .collect();
+            let typo = TyposTypo {
+                byte_offset: typo.byte_offset,
+                typo: list.intern(typo.typo.as_ref()),
+                corrections: corrections.as_ptr(),
+                corrections_len: corrections.len(),
+            };
+            list.typos.push(typo);
+            // Moving the `Vec` doesn't move its heap buffer.
+            list._corrections.push(corrections);
+        }
+        list
+    }
+
+    fn intern(&mut self, s: &str) -> *const c_char {
+        let s = CString::new(s).expect("tokens never contain NUL");
+        let ptr = s.as_ptr();
+        self._strings.push(s);
+        ptr
+    }
+}
+
+/// Create a tokenizer with the default settings.
+///
+/// Free with `typos_tokenizer_free`.
+#[no_mangle]
+pub extern "C" fn typos_tokenizer_new() -> *mut TyposTokenizer {
+    let inner = typos::tokens::TokenizerBuilder::new().build();
+    Box::into_raw(Box::new(TyposTokenizer { inner }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typos_tokenizer_free(tokenizer: *mut TyposTokenizer) {
+    if !tokenizer.is_null() {
+        drop(Box::from_raw(tokenizer));
+    }
+}
+
+/// Create a dictionary of the built-in corrections.
+///
+/// `locale` is one of `en`, `en-us`, `en-gb`, `en-ca`, `en-au`, or `NULL` for `en`.  Returns
+/// `NULL` for an unknown `locale`.
+///
+/// Free with `typos_dictionary_free`.
+#[no_mangle]
+pub unsafe extern "C" fn typos_dictionary_new(locale: *const c_char) -> *mut TyposDictionary {
+    let locale = if locale.is_null() {
+        typos_cli::config::Locale::default()
+    } else {
+        match CStr::from_ptr(locale)
+            .to_str()
+            .ok()
+            .and_then(|l| l.parse().ok())
+        {
+            Some(locale) => locale,
+            None => return std::ptr::null_mut(),
+        }
+    };
+    let inner = typos_cli::dict::BuiltIn::new(locale);
+    Box::into_raw(Box::new(TyposDictionary { inner }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typos_dictionary_free(dictionary: *mut TyposDictionary) {
+    if !dictionary.is_null() {
+        drop(Box::from_raw(dictionary));
+    }
+}
+
+/// Find the typos in `buffer`.
+///
+/// `buffer` may be `NULL` when `len` is `0`.  Returns `NULL` if a handle is `NULL`.
+///
+/// Free with `typos_typo_list_free`.
+#[no_mangle]
+pub unsafe extern "C" fn typos_check(
+    tokenizer: *const TyposTokenizer,
+    dictionary: *const TyposDictionary,
+    buffer: *const u8,
+    len: usize,
+) -> *mut TyposTypoList {
+    let (tokenizer, dictionary) = match (tokenizer.as_ref(), dictionary.as_ref()) {
+        (Some(tokenizer), Some(dictionary)) => (tokenizer, dictionary),
+        _ => return std::ptr::null_mut(),
+    };
+    let buffer = if len == 0 {
+        &[]
+    } else if buffer.is_null() {
+        return std::ptr::null_mut();
+    } else {
+        std::slice::from_raw_parts(buffer, len)
+    };
+
+    // Unwinding into C is undefined behavior
+    let list = std::panic::catch_unwind(|| {
+        TyposTypoList::new(
+            typos::check_bytes(buffer, &tokenizer.inner, &dictionary.inner)
+                .map(|typo| typo.into_owned()),
+        )
+    });
+    match list {
+        Ok(list) => Box::into_raw(Box::new(list)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typos_typo_list_len(list: *const TyposTypoList) -> usize {
+    list.as_ref().map(|l| l.typos.len()).unwrap_or(0)
+}
+
+/// The typo at `index`, or `NULL` if out of bounds.
+///
+/// Valid until the list is freed.
+#[no_mangle]
+pub unsafe extern "C" fn typos_typo_list_get(
+    list: *const TyposTypoList,
+    index: usize,
+) -> *const TyposTypo {
+    list.as_ref()
+        .and_then(|l| l.typos.get(index))
+        .map(|t| t as *const _)
+        .unwrap_or(std::ptr::null())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn typos_typo_list_free(list: *mut TyposTypoList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe fn to_str<'s>(s: *const c_char) -> &'s str {
+        CStr::from_ptr(s).to_str().unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        unsafe {
+            let tokenizer = typos_tokenizer_new();
+            let dictionary = typos_dictionary_new(std::ptr::null());
+            let buffer = b"Hello\nApropriate world";
+            let list = typos_check(tokenizer, dictionary, buffer.as_ptr(), buffer.len());
+
+            assert_eq!(typos_typo_list_len(list), 1);
+            let typo = &*typos_typo_list_get(list, 0);
+            assert_eq!(typo.byte_offset, 6);
+            assert_eq!(to_str(typo.typo), "Apropriate");
+            assert_eq!(typo.corrections_len, 1);
+            assert_eq!(to_str(*typo.corrections), "Appropriate");
+            assert!(typos_typo_list_get(list, 1).is_null());
+
+            typos_typo_list_free(list);
+            typos_dictionary_free(dictionary);
+            typos_tokenizer_free(tokenizer);
+        }
+    }
+
+    #[test]
+    fn test_check_empty() {
+        unsafe {
+            let tokenizer = typos_tokenizer_new();
+            let dictionary = typos_dictionary_new(std::ptr::null());
+            let list = typos_check(tokenizer, dictionary, std::ptr::null(), 0);
+
+            assert_eq!(typos_typo_list_len(list), 0);
+
+            typos_typo_list_free(list);
+            typos_dictionary_free(dictionary);
+            typos_tokenizer_free(tokenizer);
+        }
+    }
+
+    #[test]
+    fn test_dictionary_unknown_locale() {
+        unsafe {
+            let locale = CString::new("en-xx").unwrap();
+            assert!(typos_dictionary_new(locale.as_ptr()).is_null());
+        }
+    }
+}
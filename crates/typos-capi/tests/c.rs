@@ -0,0 +1,34 @@
+#![cfg(unix)]
+
+use assert_fs::prelude::*;
+
+/// Build and run `tests/check.c` against the `cdylib` cargo built alongside this test.
+#[test]
+fn c_program() {
+    let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Cargo builds the library into `target/<profile>/deps`, next to this test
+    let exe = std::env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap();
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let program = temp.child("check");
+    let cc = std::env::var_os("CC").unwrap_or_else(|| "cc".into());
+    let status = std::process::Command::new(cc)
+        .arg(crate_dir.join("tests/check.c"))
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-ltypos_capi")
+        .arg("-o")
+        .arg(program.path())
+        .status()
+        .expect("a C compiler is required, set `CC` to pick one");
+    assert!(status.success(), "failed to compile `tests/check.c`");
+
+    let status = std::process::Command::new(program.path()).status().unwrap();
+    assert!(status.success(), "`tests/check.c` failed");
+
+    temp.close().unwrap();
+}
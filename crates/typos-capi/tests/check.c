@@ -0,0 +1,46 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "typos.h"
+
+#define CHECK(cond)                                                    \
+  do {                                                                 \
+    if (!(cond)) {                                                     \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+              #cond);                                                  \
+      return 1;                                                        \
+    }                                                                  \
+  } while (0)
+
+int main(void) {
+  TyposTokenizer *tokenizer = typos_tokenizer_new();
+  TyposDictionary *dictionary = typos_dictionary_new("en-us");
+  CHECK(tokenizer != NULL);
+  CHECK(dictionary != NULL);
+  CHECK(typos_dictionary_new("en-xx") == NULL);
+
+  const char *buffer = "Hello\nApropriate colour";
+  TyposTypoList *list = typos_check(tokenizer, dictionary,
+                                    (const uint8_t *)buffer, strlen(buffer));
+  CHECK(list != NULL);
+  CHECK(typos_typo_list_len(list) == 2);
+
+  const TyposTypo *typo = typos_typo_list_get(list, 0);
+  CHECK(typo->byte_offset == 6);
+  CHECK(strcmp(typo->typo, "Apropriate") == 0);
+  CHECK(typo->corrections_len == 1);
+  CHECK(strcmp(typo->corrections[0], "Appropriate") == 0);
+
+  typo = typos_typo_list_get(list, 1);
+  CHECK(typo->byte_offset == 17);
+  CHECK(strcmp(typo->typo, "colour") == 0);
+  CHECK(typo->corrections_len == 1);
+  CHECK(strcmp(typo->corrections[0], "color") == 0);
+
+  CHECK(typos_typo_list_get(list, 2) == NULL);
+
+  typos_typo_list_free(list);
+  typos_dictionary_free(dictionary);
+  typos_tokenizer_free(tokenizer);
+  return 0;
+}
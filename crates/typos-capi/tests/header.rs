@@ -0,0 +1,23 @@
+#[test]
+fn header_is_current() {
+    let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut actual = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut actual);
+    let actual = String::from_utf8(actual).unwrap();
+
+    let header_path = crate_dir.join("include/typos.h");
+    if std::env::var_os("TYPOS_CAPI_OVERWRITE").is_some() {
+        std::fs::write(&header_path, &actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(&header_path).unwrap_or_default();
+    assert!(
+        expected == actual,
+        "`include/typos.h` is out of date, re-run with `TYPOS_CAPI_OVERWRITE=1`"
+    );
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::Severity;
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields, default)]
 #[serde(rename_all = "kebab-case")]
@@ -27,7 +29,10 @@ impl Config {
 
     pub fn from_file(path: &std::path::Path) -> Result<Self, anyhow::Error> {
         let s = std::fs::read_to_string(path)?;
-        Self::from_toml(&s)
+        let mut config = Self::from_toml(&s)?;
+        let dir = path.parent().unwrap_or_else(|| std::path::Path::new(""));
+        config.resolve_paths(&std::env::current_dir()?.join(dir));
+        Ok(config)
     }
 
     pub fn from_toml(data: &str) -> Result<Self, anyhow::Error> {
@@ -55,6 +60,15 @@ impl Config {
         }
         self.overrides.update(&source.overrides);
     }
+
+    /// Make paths in the config relative to `dir`, the directory of the file declaring them.
+    pub fn resolve_paths(&mut self, dir: &std::path::Path) {
+        self.default.resolve_paths(dir);
+        for engine in self.type_.values_mut() {
+            engine.engine.resolve_paths(dir);
+        }
+        self.overrides.resolve_paths(dir);
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -147,7 +161,9 @@ impl Walk {
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-#[serde(deny_unknown_fields, default)]
+// `deny_unknown_fields` would reject everything in the flattened `engine`, which denies unknown
+// fields itself
+#[serde(default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TypeEngineConfig {
     pub extend_glob: Vec<kstring::KString>,
@@ -172,6 +188,24 @@ pub struct EngineConfig {
     pub check_filename: Option<bool>,
     /// Verifying spelling in files.
     pub check_file: Option<bool>,
+    /// Write fixes to the target of a symlink, rather than refusing.
+    pub fix_symlinks: Option<bool>,
+    /// Keep the modification time of fixed files, rather than updating it.
+    pub preserve_mtime: Option<bool>,
+    /// Decode text that isn't UTF-8 as Shift-JIS, Windows-1252, or Latin-1.
+    pub legacy_encodings: Option<bool>,
+    /// Check the content of compressed files and the members of archives.
+    pub archives: Option<bool>,
+    /// Check the text of PDF, DOCX, ODT, and EPUB documents.
+    pub documents: Option<bool>,
+    /// Only check the prose of files written in this markup language.
+    pub markup: Option<Markup>,
+    /// Check the outputs of Jupyter notebook cells, not just their source.
+    pub notebook_outputs: Option<bool>,
+    /// Flag words repeated with only whitespace between them, like `the the`.
+    pub repeated_words: Option<bool>,
+    /// How severe each kind of finding is.
+    pub severity: Option<SeverityConfig>,
     #[serde(flatten)]
     pub tokenizer: Option<TokenizerConfig>,
     #[serde(flatten)]
@@ -185,6 +219,15 @@ impl EngineConfig {
             binary: Some(empty.binary()),
             check_filename: Some(empty.check_filename()),
             check_file: Some(empty.check_file()),
+            fix_symlinks: Some(empty.fix_symlinks()),
+            preserve_mtime: Some(empty.preserve_mtime()),
+            legacy_encodings: Some(empty.legacy_encodings()),
+            archives: Some(empty.archives()),
+            documents: Some(empty.documents()),
+            markup: Some(empty.markup()),
+            notebook_outputs: Some(empty.notebook_outputs()),
+            repeated_words: Some(empty.repeated_words()),
+            severity: Some(empty.severity.unwrap_or_else(SeverityConfig::from_defaults)),
             tokenizer: Some(
                 empty
                     .tokenizer
@@ -194,6 +237,12 @@ impl EngineConfig {
         }
     }
 
+    pub fn resolve_paths(&mut self, dir: &std::path::Path) {
+        if let Some(tokenizer) = self.tokenizer.as_mut() {
+            tokenizer.resolve_paths(dir);
+        }
+    }
+
     pub fn update(&mut self, source: &EngineConfig) {
         if let Some(source) = source.binary {
             self.binary = Some(source);
@@ -204,6 +253,38 @@ impl EngineConfig {
         if let Some(source) = source.check_file {
             self.check_file = Some(source);
         }
+        if let Some(source) = source.fix_symlinks {
+            self.fix_symlinks = Some(source);
+        }
+        if let Some(source) = source.preserve_mtime {
+            self.preserve_mtime = Some(source);
+        }
+        if let Some(source) = source.legacy_encodings {
+            self.legacy_encodings = Some(source);
+        }
+        if let Some(source) = source.archives {
+            self.archives = Some(source);
+        }
+        if let Some(source) = source.documents {
+            self.documents = Some(source);
+        }
+        if let Some(source) = source.markup {
+            self.markup = Some(source);
+        }
+        if let Some(source) = source.notebook_outputs {
+            self.notebook_outputs = Some(source);
+        }
+        if let Some(source) = source.repeated_words {
+            self.repeated_words = Some(source);
+        }
+        if let Some(source) = source.severity.as_ref() {
+            let mut severity = None;
+            std::mem::swap(&mut severity, &mut self.severity);
+            let mut severity = severity.unwrap_or_default();
+            severity.update(source);
+            let mut severity = Some(severity);
+            std::mem::swap(&mut severity, &mut self.severity);
+        }
         if let Some(source) = source.tokenizer.as_ref() {
             let mut tokenizer = None;
             std::mem::swap(&mut tokenizer, &mut self.tokenizer);
@@ -233,6 +314,105 @@ impl EngineConfig {
     pub fn check_file(&self) -> bool {
         self.check_file.unwrap_or(true)
     }
+
+    pub fn fix_symlinks(&self) -> bool {
+        self.fix_symlinks.unwrap_or(true)
+    }
+
+    pub fn preserve_mtime(&self) -> bool {
+        self.preserve_mtime.unwrap_or(false)
+    }
+
+    pub fn legacy_encodings(&self) -> bool {
+        self.legacy_encodings.unwrap_or(false)
+    }
+
+    pub fn archives(&self) -> bool {
+        self.archives.unwrap_or(false)
+    }
+
+    pub fn documents(&self) -> bool {
+        self.documents.unwrap_or(false)
+    }
+
+    pub fn markup(&self) -> Markup {
+        self.markup.unwrap_or_default()
+    }
+
+    pub fn notebook_outputs(&self) -> bool {
+        self.notebook_outputs.unwrap_or(false)
+    }
+
+    pub fn repeated_words(&self) -> bool {
+        self.repeated_words.unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields, default)]
+#[serde(rename_all = "kebab-case")]
+pub struct SeverityConfig {
+    /// Misspellings from the built-in dictionary, including phrases.
+    pub typo: Option<Severity>,
+    /// Words spelled for a different `locale`.
+    pub locale: Option<Severity>,
+    /// Findings from `extend-identifiers`, `extend-words`, and `extend-phrases`.
+    pub custom: Option<Severity>,
+    /// Typos in file names, whatever their source.
+    pub filename: Option<Severity>,
+    /// Words found by `repeated-words`.
+    pub repeated_words: Option<Severity>,
+}
+
+impl SeverityConfig {
+    pub fn from_defaults() -> Self {
+        let empty = Self::default();
+        Self {
+            typo: Some(empty.typo()),
+            locale: Some(empty.locale()),
+            custom: Some(empty.custom()),
+            filename: Some(empty.filename()),
+            repeated_words: Some(empty.repeated_words()),
+        }
+    }
+
+    pub fn update(&mut self, source: &SeverityConfig) {
+        if let Some(source) = source.typo {
+            self.typo = Some(source);
+        }
+        if let Some(source) = source.locale {
+            self.locale = Some(source);
+        }
+        if let Some(source) = source.custom {
+            self.custom = Some(source);
+        }
+        if let Some(source) = source.filename {
+            self.filename = Some(source);
+        }
+        if let Some(source) = source.repeated_words {
+            self.repeated_words = Some(source);
+        }
+    }
+
+    pub fn typo(&self) -> Severity {
+        self.typo.unwrap_or_default()
+    }
+
+    pub fn locale(&self) -> Severity {
+        self.locale.unwrap_or_default()
+    }
+
+    pub fn custom(&self) -> Severity {
+        self.custom.unwrap_or_default()
+    }
+
+    pub fn filename(&self) -> Severity {
+        self.filename.unwrap_or_default()
+    }
+
+    pub fn repeated_words(&self) -> Severity {
+        self.repeated_words.unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -245,6 +425,16 @@ pub struct TokenizerConfig {
     pub ignore_hex: Option<bool>,
     /// Allow identifiers to start with digits, in addition to letters.
     pub identifier_leading_digits: Option<bool>,
+    /// Do not treat escape sequences, format specifiers, or character references as part of identifiers.
+    pub escapes: Option<bool>,
+    /// Allow `-` within identifiers, like `serde-json`.
+    pub identifier_dashes: Option<bool>,
+    /// Allow `.` within identifiers, like `os.path`.
+    pub identifier_dots: Option<bool>,
+    /// Word-frequency list for splitting run-together lowercase words, like `copysign`.
+    pub compound_word_list: Option<std::path::PathBuf>,
+    /// How sure, in percent, a split of a run-together word must be before it is made.
+    pub compound_word_confidence: Option<u8>,
 }
 
 impl TokenizerConfig {
@@ -254,6 +444,17 @@ impl TokenizerConfig {
             unicode: Some(empty.unicode()),
             ignore_hex: Some(empty.ignore_hex()),
             identifier_leading_digits: Some(empty.identifier_leading_digits()),
+            escapes: Some(empty.escapes()),
+            identifier_dashes: Some(empty.identifier_dashes()),
+            identifier_dots: Some(empty.identifier_dots()),
+            compound_word_list: empty.compound_word_list.clone(),
+            compound_word_confidence: Some(empty.compound_word_confidence()),
+        }
+    }
+
+    pub fn resolve_paths(&mut self, dir: &std::path::Path) {
+        if let Some(path) = self.compound_word_list.as_mut() {
+            *path = dir.join(&path);
         }
     }
 
@@ -267,6 +468,21 @@ impl TokenizerConfig {
         if let Some(source) = source.identifier_leading_digits {
             self.identifier_leading_digits = Some(source);
         }
+        if let Some(source) = source.escapes {
+            self.escapes = Some(source);
+        }
+        if let Some(source) = source.identifier_dashes {
+            self.identifier_dashes = Some(source);
+        }
+        if let Some(source) = source.identifier_dots {
+            self.identifier_dots = Some(source);
+        }
+        if let Some(source) = source.compound_word_list.as_ref() {
+            self.compound_word_list = Some(source.clone());
+        }
+        if let Some(source) = source.compound_word_confidence {
+            self.compound_word_confidence = Some(source);
+        }
     }
 
     pub fn unicode(&self) -> bool {
@@ -280,6 +496,26 @@ impl TokenizerConfig {
     pub fn identifier_leading_digits(&self) -> bool {
         self.identifier_leading_digits.unwrap_or(false)
     }
+
+    pub fn escapes(&self) -> bool {
+        self.escapes.unwrap_or(false)
+    }
+
+    pub fn identifier_dashes(&self) -> bool {
+        self.identifier_dashes.unwrap_or(false)
+    }
+
+    pub fn identifier_dots(&self) -> bool {
+        self.identifier_dots.unwrap_or(false)
+    }
+
+    pub fn compound_word_list(&self) -> Option<&std::path::Path> {
+        self.compound_word_list.as_deref()
+    }
+
+    pub fn compound_word_confidence(&self) -> u8 {
+        self.compound_word_confidence.unwrap_or(75).min(100)
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -287,8 +523,14 @@ impl TokenizerConfig {
 #[serde(rename_all = "kebab-case")]
 pub struct DictConfig {
     pub locale: Option<Locale>,
+    /// Apply `locale` corrections to identifiers, like `color` in `set_color` or `style.color`,
+    /// and not just to prose and the comments of source code.
+    pub locale_identifiers: Option<bool>,
+    /// Correct built-in phrases, like `can not`.
+    pub phrases: Option<bool>,
     pub extend_identifiers: HashMap<kstring::KString, kstring::KString>,
     pub extend_words: HashMap<kstring::KString, kstring::KString>,
+    pub extend_phrases: HashMap<kstring::KString, kstring::KString>,
 }
 
 impl DictConfig {
@@ -296,8 +538,11 @@ impl DictConfig {
         let empty = Self::default();
         Self {
             locale: Some(empty.locale()),
+            locale_identifiers: Some(empty.locale_identifiers()),
+            phrases: Some(empty.phrases()),
             extend_identifiers: Default::default(),
             extend_words: Default::default(),
+            extend_phrases: Default::default(),
         }
     }
 
@@ -305,6 +550,12 @@ impl DictConfig {
         if let Some(source) = source.locale {
             self.locale = Some(source);
         }
+        if let Some(source) = source.locale_identifiers {
+            self.locale_identifiers = Some(source);
+        }
+        if let Some(source) = source.phrases {
+            self.phrases = Some(source);
+        }
         self.extend_identifiers.extend(
             source
                 .extend_identifiers
@@ -317,12 +568,26 @@ impl DictConfig {
                 .iter()
                 .map(|(key, value)| (key.clone(), value.clone())),
         );
+        self.extend_phrases.extend(
+            source
+                .extend_phrases
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
     }
 
     pub fn locale(&self) -> Locale {
         self.locale.unwrap_or_default()
     }
 
+    pub fn locale_identifiers(&self) -> bool {
+        self.locale_identifiers.unwrap_or(true)
+    }
+
+    pub fn phrases(&self) -> bool {
+        self.phrases.unwrap_or(false)
+    }
+
     pub fn extend_identifiers(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
         Box::new(
             self.extend_identifiers
@@ -338,6 +603,14 @@ impl DictConfig {
                 .map(|(k, v)| (k.as_str(), v.as_str())),
         )
     }
+
+    pub fn extend_phrases(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        Box::new(
+            self.extend_phrases
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str())),
+        )
+    }
 }
 
 fn find_project_file(dir: &std::path::Path, names: &[&str]) -> Option<std::path::PathBuf> {
@@ -351,6 +624,23 @@ fn find_project_file(dir: &std::path::Path, names: &[&str]) -> Option<std::path:
     None
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Markup {
+    /// Check all of the text
+    Plain,
+    Markdown,
+    Html,
+    /// reStructuredText
+    Rst,
+}
+
+impl Default for Markup {
+    fn default() -> Self {
+        Markup::Plain
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Locale {
@@ -478,4 +768,33 @@ mod test {
         let expected: Vec<kstring::KString> = vec!["*.foo".into(), "*.bar".into()];
         assert_eq!(actual.extend_glob, expected);
     }
+
+    #[test]
+    fn test_type_engine_from_toml() {
+        let config = Config::from_toml(
+            r#"
+[type.md]
+extend-glob = ["*.mdx"]
+markup = "markdown"
+check-filename = false
+"#,
+        )
+        .unwrap();
+
+        let md = &config.type_["md"];
+        assert_eq!(md.extend_glob, vec![kstring::KString::from("*.mdx")]);
+        assert_eq!(md.engine.markup, Some(Markup::Markdown));
+        assert_eq!(md.engine.check_filename, Some(false));
+    }
+
+    #[test]
+    fn test_type_engine_rejects_unknown() {
+        let config = Config::from_toml(
+            r#"
+[type.md]
+bogus = true
+"#,
+        );
+        assert!(config.is_err());
+    }
 }
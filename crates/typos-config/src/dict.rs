@@ -0,0 +1,769 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use unicase::UniCase;
+
+use typos::Status;
+
+#[derive(Default)]
+pub struct BuiltIn {
+    locale: Option<varcon_core::Category>,
+    locale_identifiers: bool,
+    phrases: bool,
+}
+
+impl BuiltIn {
+    pub const fn new(locale: crate::config::Locale) -> Self {
+        Self {
+            locale: locale.category(),
+            locale_identifiers: true,
+            phrases: false,
+        }
+    }
+
+    /// Apply `locale` corrections to words within identifiers, like `color` in `set_color`.
+    pub fn locale_identifiers(&mut self, yes: bool) -> &mut Self {
+        self.locale_identifiers = yes;
+        self
+    }
+
+    /// Correct phrases, like `can not`.
+    ///
+    /// Phrases that are always valid, like `had had`, apply either way.
+    pub fn phrases(&mut self, yes: bool) -> &mut Self {
+        self.phrases = yes;
+        self
+    }
+
+    pub fn correct_ident<'s, 'w>(
+        &'s self,
+        _ident: typos::tokens::Identifier<'w>,
+    ) -> Option<Status<'s>> {
+        None
+    }
+
+    pub fn correct_word<'s, 'w>(
+        &'s self,
+        word_token: typos::tokens::Word<'w>,
+    ) -> Option<Status<'s>> {
+        self.correct_word_with_locale(word_token, self.locale)
+    }
+
+    pub fn correct_ident_word<'s, 'w>(
+        &'s self,
+        word_token: typos::tokens::Word<'w>,
+    ) -> Option<Status<'s>> {
+        let locale = if self.locale_identifiers {
+            self.locale
+        } else {
+            None
+        };
+        self.correct_word_with_locale(word_token, locale)
+    }
+
+    pub fn correct_phrase<'s, 'w>(
+        &'s self,
+        idents: &[typos::tokens::Identifier<'w>],
+    ) -> Option<(usize, Status<'s>)> {
+        let (len, mut corrections) = BUILTIN_PHRASES.correct(idents)?;
+        if !self.phrases && !corrections.is_valid() {
+            return None;
+        }
+        let typo = itertools::join(idents[..len].iter().map(|i| i.token()), " ");
+        corrections
+            .corrections_mut()
+            .for_each(|s| case_correct(s, &typo));
+        Some((len, corrections))
+    }
+
+    pub fn source(&self, typo: &str) -> typos::Source {
+        // Misspellings chained into a locale's spelling are still misspellings
+        if self.correct_with_dict(typo).is_none()
+            && self.correct_with_vars(typo, self.locale).is_some()
+        {
+            typos::Source::Locale
+        } else {
+            typos::Source::Dictionary
+        }
+    }
+
+    fn correct_word_with_locale(
+        &self,
+        word_token: typos::tokens::Word<'_>,
+        locale: Option<varcon_core::Category>,
+    ) -> Option<Status<'static>> {
+        let word = word_token.token();
+        let mut corrections = if let Some(corrections) = self.correct_with_dict(word) {
+            if corrections.is_empty() {
+                Status::Invalid
+            } else {
+                self.chain_with_vars(corrections, locale)
+            }
+        } else {
+            self.correct_with_vars(word, locale)?
+        };
+        corrections
+            .corrections_mut()
+            .for_each(|s| case_correct(s, word));
+        Some(corrections)
+    }
+}
+
+static BUILTIN_PHRASES: once_cell::sync::Lazy<typos::Phrases<'static>> =
+    once_cell::sync::Lazy::new(|| typos::Phrases::from_csv(include_str!("phrases.csv")));
+
+#[cfg(feature = "dict")]
+impl BuiltIn {
+    // Not using `Status` to avoid the allocations
+    fn correct_with_dict(&self, word: &str) -> Option<&'static [&'static str]> {
+        if typos_dict::WORD_RANGE.contains(&word.len()) {
+            map_lookup(&typos_dict::WORD_DICTIONARY, word)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "dict"))]
+impl BuiltIn {
+    fn correct_with_dict(&self, _word: &str) -> Option<&'static [&'static str]> {
+        None
+    }
+}
+
+#[cfg(feature = "vars")]
+impl BuiltIn {
+    fn chain_with_vars(
+        &self,
+        corrections: &'static [&'static str],
+        locale: Option<varcon_core::Category>,
+    ) -> Status<'static> {
+        if self.is_vars_enabled(locale) {
+            let mut chained: Vec<_> = corrections
+                .iter()
+                .flat_map(|c| match self.correct_with_vars(c, locale) {
+                    Some(Status::Valid) | None => vec![Cow::Borrowed(*c)],
+                    Some(Status::Corrections(vars)) => vars,
+                    Some(Status::Invalid) => {
+                        unreachable!("correct_with_vars should always have valid suggestions")
+                    }
+                })
+                .collect();
+            if chained.len() != 1 {
+                chained.sort_unstable();
+                chained.dedup();
+            }
+            debug_assert!(!chained.is_empty());
+            Status::Corrections(chained)
+        } else {
+            Status::Corrections(corrections.iter().map(|c| Cow::Borrowed(*c)).collect())
+        }
+    }
+
+    fn correct_with_vars(
+        &self,
+        word: &str,
+        locale: Option<varcon_core::Category>,
+    ) -> Option<Status<'static>> {
+        if self.is_vars_enabled(locale) && typos_vars::WORD_RANGE.contains(&word.len()) {
+            map_lookup(&typos_vars::VARS_DICTIONARY, word)
+                .map(|variants| self.select_variant(variants, locale))
+        } else {
+            None
+        }
+    }
+
+    fn is_vars_enabled(&self, locale: Option<varcon_core::Category>) -> bool {
+        #![allow(clippy::assertions_on_constants)]
+        debug_assert!(typos_vars::NO_INVALID);
+        locale.is_some()
+    }
+
+    fn select_variant(
+        &self,
+        vars: &'static [(u8, &'static typos_vars::VariantsMap)],
+        locale: Option<varcon_core::Category>,
+    ) -> Status<'static> {
+        let var = vars[0];
+        let var_categories = unsafe {
+            // Code-genned from a checked category-set, so known to be safe
+            typos_vars::CategorySet::from_bits_unchecked(var.0)
+        };
+        if let Some(locale) = locale {
+            if var_categories.contains(locale) {
+                // Already valid for the current locale.
+                Status::Valid
+            } else {
+                Status::Corrections(
+                    typos_vars::corrections(locale, *var.1)
+                        .iter()
+                        .copied()
+                        .map(Cow::Borrowed)
+                        .collect(),
+                )
+            }
+        } else {
+            // All locales are valid
+            if var_categories.is_empty() {
+                // But the word is never valid.
+                let mut unique: Vec<_> = var
+                    .1
+                    .iter()
+                    .flat_map(|v| v.iter())
+                    .copied()
+                    .map(Cow::Borrowed)
+                    .collect();
+                unique.sort_unstable();
+                unique.dedup();
+                Status::Corrections(unique)
+            } else {
+                Status::Valid
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "vars"))]
+impl BuiltIn {
+    fn chain_with_vars(
+        &self,
+        corrections: &'static [&'static str],
+        _locale: Option<varcon_core::Category>,
+    ) -> Status<'static> {
+        Status::Corrections(corrections.iter().map(|c| Cow::Borrowed(*c)).collect())
+    }
+
+    fn correct_with_vars(
+        &self,
+        _word: &str,
+        _locale: Option<varcon_core::Category>,
+    ) -> Option<Status<'static>> {
+        None
+    }
+}
+
+impl typos::Dictionary for BuiltIn {
+    fn correct_ident<'s, 'w>(&'s self, ident: typos::tokens::Identifier<'w>) -> Option<Status<'s>> {
+        BuiltIn::correct_ident(self, ident)
+    }
+
+    fn correct_word<'s, 'w>(&'s self, word: typos::tokens::Word<'w>) -> Option<Status<'s>> {
+        BuiltIn::correct_word(self, word)
+    }
+
+    fn correct_ident_word<'s, 'w>(&'s self, word: typos::tokens::Word<'w>) -> Option<Status<'s>> {
+        BuiltIn::correct_ident_word(self, word)
+    }
+
+    fn correct_phrase<'s, 'w>(
+        &'s self,
+        idents: &[typos::tokens::Identifier<'w>],
+    ) -> Option<(usize, Status<'s>)> {
+        BuiltIn::correct_phrase(self, idents)
+    }
+
+    fn source(&self, typo: &str) -> typos::Source {
+        BuiltIn::source(self, typo)
+    }
+}
+
+fn map_lookup<V: Clone>(map: &'static phf::Map<UniCase<&'static str>, V>, key: &str) -> Option<V> {
+    // This transmute should be safe as `get` will not store the reference with
+    // the expanded lifetime. This is due to `Borrow` being overly strict and
+    // can't have an impl for `&'static str` to `Borrow<&'a str>`.
+    //
+    //
+    // See https://github.com/rust-lang/rust/issues/28853#issuecomment-158735548
+    unsafe {
+        let key = ::std::mem::transmute::<_, &'static str>(key);
+        map.get(&UniCase::new(key)).cloned()
+    }
+}
+
+/// Give `correction` the casing of `typo`, like `Teh` -> `The` or `McDonlad` -> `McDonald`.
+fn case_correct(correction: &mut Cow<'_, str>, typo: &str) {
+    let cased = match CasePattern::of(typo) {
+        CasePattern::Lower => return,
+        CasePattern::Title => {
+            let mut chars = correction.chars();
+            match chars.next() {
+                Some(first) if !first.is_uppercase() => {
+                    first.to_uppercase().chain(chars).collect::<String>()
+                }
+                _ => return,
+            }
+        }
+        CasePattern::Upper => correction.to_uppercase(),
+        CasePattern::Mixed => transfer_case(correction, typo),
+    };
+    if cased != *correction {
+        *correction = cased.into();
+    }
+}
+
+/// How the cased characters of a term are cased.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum CasePattern {
+    /// All lowercase, or nothing to case
+    Lower,
+    /// Only the first cased character is uppercase
+    Title,
+    /// At least two cased characters, all uppercase
+    Upper,
+    /// Anything else, like `McDonald` or `iPhone`
+    Mixed,
+}
+
+impl CasePattern {
+    fn of(term: &str) -> Self {
+        let mut cased = term
+            .chars()
+            .filter(|c| c.is_lowercase() || c.is_uppercase());
+        let first = match cased.next() {
+            Some(first) => first,
+            None => return CasePattern::Lower,
+        };
+        let (mut upper, mut lower) = (0, 0);
+        for c in cased {
+            if c.is_uppercase() {
+                upper += 1;
+            } else {
+                lower += 1;
+            }
+        }
+        match (first.is_uppercase(), upper, lower) {
+            (false, 0, _) => CasePattern::Lower,
+            (true, 0, _) => CasePattern::Title,
+            (true, _, 0) => CasePattern::Upper,
+            _ => CasePattern::Mixed,
+        }
+    }
+}
+
+/// Copy the case of each character in `typo` to the matching character in `correction`.
+///
+/// Characters line up by position when the lengths are the same.  Otherwise, only the common
+/// prefix and suffix line up, leaving what was inserted or removed between them as-is.
+fn transfer_case(correction: &str, typo: &str) -> String {
+    let typo: Vec<_> = typo.chars().collect();
+    let correction: Vec<_> = correction.chars().collect();
+    let (prefix, suffix) = if typo.len() == correction.len() {
+        (typo.len(), 0)
+    } else {
+        let same = |t: &char, c: &char| t.to_lowercase().eq(c.to_lowercase());
+        let prefix = typo
+            .iter()
+            .zip(correction.iter())
+            .take_while(|(t, c)| same(t, c))
+            .count();
+        let suffix = typo
+            .iter()
+            .rev()
+            .zip(correction.iter().rev())
+            .take(typo.len().min(correction.len()) - prefix)
+            .take_while(|(t, c)| same(t, c))
+            .count();
+        (prefix, suffix)
+    };
+    let mut cases: Vec<Option<char>> = vec![None; correction.len()];
+    for (case, t) in cases[..prefix].iter_mut().zip(&typo[..prefix]) {
+        *case = Some(*t);
+    }
+    let (case_suffix, typo_suffix) = (correction.len() - suffix, typo.len() - suffix);
+    for (case, t) in cases[case_suffix..].iter_mut().zip(&typo[typo_suffix..]) {
+        *case = Some(*t);
+    }
+
+    let mut cased = String::with_capacity(correction.len());
+    for (c, case) in correction.iter().zip(cases.iter()) {
+        match case {
+            Some(case) if case.is_uppercase() => cased.extend(c.to_uppercase()),
+            Some(case) if case.is_lowercase() => cased.extend(c.to_lowercase()),
+            _ => cased.push(*c),
+        }
+    }
+    cased
+}
+
+pub struct Override<'i, 'w, D> {
+    identifiers: HashMap<&'i str, Status<'i>, ahash::RandomState>,
+    words: HashMap<unicase::UniCase<&'w str>, Status<'w>, ahash::RandomState>,
+    phrases: typos::Phrases<'w>,
+    inner: D,
+}
+
+impl<'i, 'w, D: typos::Dictionary> Override<'i, 'w, D> {
+    pub fn new(inner: D) -> Self {
+        Self {
+            identifiers: Default::default(),
+            words: Default::default(),
+            phrases: Default::default(),
+            inner,
+        }
+    }
+
+    pub fn identifiers<I: Iterator<Item = (&'i str, &'i str)>>(&mut self, identifiers: I) {
+        self.identifiers = Self::interpret(identifiers).collect();
+    }
+
+    pub fn words<I: Iterator<Item = (&'w str, &'w str)>>(&mut self, words: I) {
+        self.words = Self::interpret(words)
+            .map(|(k, v)| (UniCase::new(k), v))
+            .collect();
+    }
+
+    pub fn phrases<I: Iterator<Item = (&'w str, &'w str)>>(&mut self, phrases: I) {
+        let mut custom = typos::Phrases::new();
+        for (phrase, status) in Self::interpret(phrases) {
+            custom.insert(phrase, status);
+        }
+        self.phrases = custom;
+    }
+
+    fn interpret<'z, I: Iterator<Item = (&'z str, &'z str)>>(
+        cases: I,
+    ) -> impl Iterator<Item = (&'z str, Status<'z>)> {
+        cases.map(|(typo, correction)| {
+            let correction = if typo == correction {
+                Status::Valid
+            } else if correction.is_empty() {
+                Status::Invalid
+            } else {
+                Status::Corrections(vec![Cow::Borrowed(correction)])
+            };
+            (typo, correction)
+        })
+    }
+}
+
+impl<'i, 'w, D: typos::Dictionary> typos::Dictionary for Override<'i, 'w, D> {
+    fn correct_ident<'s, 't>(&'s self, ident: typos::tokens::Identifier<'t>) -> Option<Status<'s>> {
+        // Skip hashing if we can
+        if !self.identifiers.is_empty() {
+            self.identifiers
+                .get(ident.token())
+                .map(|c| c.borrow())
+                .or_else(|| self.inner.correct_ident(ident))
+        } else {
+            None
+        }
+    }
+
+    fn correct_word<'s, 't>(&'s self, word: typos::tokens::Word<'t>) -> Option<Status<'s>> {
+        self.custom_word(word)
+            .or_else(|| self.inner.correct_word(word))
+    }
+
+    fn correct_ident_word<'s, 't>(&'s self, word: typos::tokens::Word<'t>) -> Option<Status<'s>> {
+        self.custom_word(word)
+            .or_else(|| self.inner.correct_ident_word(word))
+    }
+
+    fn correct_phrase<'s, 't>(
+        &'s self,
+        idents: &[typos::tokens::Identifier<'t>],
+    ) -> Option<(usize, Status<'s>)> {
+        if let Some(found) = self.phrases.correct(idents) {
+            return Some(found);
+        }
+        let (len, status) = self.inner.correct_phrase(idents)?;
+        // Custom words and identifiers win over the inner dictionary's phrases
+        let is_custom = idents[..len].iter().any(|ident| {
+            self.identifiers.contains_key(ident.token())
+                || self.words.contains_key(&UniCase::new(ident.token()))
+        });
+        if is_custom {
+            None
+        } else {
+            Some((len, status))
+        }
+    }
+
+    fn source(&self, typo: &str) -> typos::Source {
+        let is_custom = self.identifiers.contains_key(typo)
+            || self.words.contains_key(&UniCase::new(typo))
+            || self.phrases.contains(typo);
+        if is_custom {
+            typos::Source::Custom
+        } else {
+            self.inner.source(typo)
+        }
+    }
+}
+
+impl<'i, 'w, D: typos::Dictionary> Override<'i, 'w, D> {
+    fn custom_word<'s>(&'s self, word: typos::tokens::Word<'_>) -> Option<Status<'s>> {
+        // Skip hashing if we can
+        if !self.words.is_empty() {
+            let w = UniCase::new(word.token());
+            // HACK: couldn't figure out the lifetime issue with replacing `cloned` with `borrow`
+            self.words.get(&w).cloned()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "dict")]
+    #[test]
+    fn test_dict_correct() {
+        let dict = BuiltIn::new(crate::config::Locale::default());
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finallizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalizes".into()]))
+        );
+    }
+
+    #[cfg(feature = "vars")]
+    #[test]
+    fn test_varcon_no_locale() {
+        let dict = BuiltIn::new(crate::config::Locale::En);
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(correction, None);
+    }
+
+    #[cfg(feature = "vars")]
+    #[test]
+    fn test_varcon_same_locale() {
+        let dict = BuiltIn::new(crate::config::Locale::EnUs);
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(correction, Some(Status::Valid));
+    }
+
+    #[cfg(feature = "vars")]
+    #[test]
+    fn test_varcon_different_locale() {
+        let dict = BuiltIn::new(crate::config::Locale::EnGb);
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalises".into()]))
+        );
+    }
+
+    #[cfg(all(feature = "dict", feature = "vars"))]
+    #[test]
+    fn test_dict_to_varcon() {
+        let dict = BuiltIn::new(crate::config::Locale::EnGb);
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finallizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalises".into()]))
+        );
+    }
+
+    #[cfg(feature = "vars")]
+    #[test]
+    fn test_varcon_ident_word() {
+        let dict = BuiltIn::new(crate::config::Locale::EnGb);
+        let correction = dict.correct_ident_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalises".into()]))
+        );
+    }
+
+    #[cfg(feature = "vars")]
+    #[test]
+    fn test_varcon_ident_word_skips_locale() {
+        let mut dict = BuiltIn::new(crate::config::Locale::EnGb);
+        dict.locale_identifiers(false);
+        let correction = dict.correct_ident_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(correction, None);
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "finalizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalises".into()]))
+        );
+    }
+
+    #[cfg(all(feature = "dict", feature = "vars"))]
+    #[test]
+    fn test_dict_ident_word_skips_locale() {
+        let mut dict = BuiltIn::new(crate::config::Locale::EnGb);
+        dict.locale_identifiers(false);
+        let correction = dict.correct_ident_word(typos::tokens::Word::new_unchecked(
+            "finallizes",
+            typos::tokens::Case::Lower,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["finalizes".into()]))
+        );
+    }
+
+    #[test]
+    fn test_builtin_phrase() {
+        let mut dict = BuiltIn::new(crate::config::Locale::default());
+        let idents = [
+            typos::tokens::Identifier::new_unchecked("Can", typos::tokens::Case::None, 0),
+            typos::tokens::Identifier::new_unchecked("not", typos::tokens::Case::None, 4),
+        ];
+        assert_eq!(dict.correct_phrase(&idents), None);
+
+        dict.phrases(true);
+        let correction = dict.correct_phrase(&idents);
+        assert_eq!(
+            correction,
+            Some((2, Status::Corrections(vec!["Cannot".into()])))
+        );
+    }
+
+    #[test]
+    fn test_builtin_valid_phrase() {
+        let dict = BuiltIn::new(crate::config::Locale::default());
+        let idents = [
+            typos::tokens::Identifier::new_unchecked("had", typos::tokens::Case::None, 0),
+            typos::tokens::Identifier::new_unchecked("had", typos::tokens::Case::None, 4),
+        ];
+        assert_eq!(dict.correct_phrase(&idents), Some((2, Status::Valid)));
+    }
+
+    #[test]
+    fn test_override_phrase() {
+        let mut dict = Override::new(BuiltIn::new(crate::config::Locale::default()));
+        dict.phrases([("can not", "can not")].iter().copied());
+        let idents = [
+            typos::tokens::Identifier::new_unchecked("can", typos::tokens::Case::None, 0),
+            typos::tokens::Identifier::new_unchecked("not", typos::tokens::Case::None, 4),
+        ];
+        let correction = typos::Dictionary::correct_phrase(&dict, &idents);
+        assert_eq!(correction, Some((2, Status::Valid)));
+    }
+
+    #[test]
+    fn test_override_phrase_custom_word() {
+        let mut builtin = BuiltIn::new(crate::config::Locale::default());
+        builtin.phrases(true);
+        let mut dict = Override::new(builtin);
+        dict.words([("can", "can")].iter().copied());
+        let idents = [
+            typos::tokens::Identifier::new_unchecked("can", typos::tokens::Case::None, 0),
+            typos::tokens::Identifier::new_unchecked("not", typos::tokens::Case::None, 4),
+        ];
+        let correction = typos::Dictionary::correct_phrase(&dict, &idents);
+        assert_eq!(correction, None);
+    }
+
+    #[cfg(all(feature = "dict", feature = "vars"))]
+    #[test]
+    fn test_source() {
+        let mut dict = Override::new(BuiltIn::new(crate::config::Locale::EnGb));
+        dict.words([("speling", "spelling")].iter().copied());
+        dict.phrases([("log in in", "log in")].iter().copied());
+        let cases = [
+            ("finallizes", typos::Source::Dictionary),
+            ("finalizes", typos::Source::Locale),
+            ("Speling", typos::Source::Custom),
+            ("log  in in", typos::Source::Custom),
+            ("can not", typos::Source::Dictionary),
+        ];
+        for (typo, expected) in cases.iter() {
+            let actual = typos::Dictionary::source(&dict, typo);
+            assert_eq!(actual, *expected, "{}", typo);
+        }
+    }
+
+    #[test]
+    fn test_case_correct() {
+        let cases = [
+            ("foo", "fo", "foo"),
+            ("foo", "11", "foo"),
+            ("foo", "Fo", "Foo"),
+            ("foo", "FO", "FOO"),
+            ("fOo", "fo", "fOo"),
+            ("mcdonald", "McDonlad", "McDonald"),
+            ("iphone", "iPhne", "iPhone"),
+            ("iphone", "iPhonee", "iPhone"),
+            ("ångström", "Ångstöm", "Ångström"),
+            ("straße", "STRASE", "STRASSE"),
+            ("éclair", "Èclair", "Éclair"),
+            ("an", "A", "An"),
+        ];
+        for (correction, typo, expected) in cases.iter() {
+            let mut actual = Cow::Borrowed(*correction);
+            case_correct(&mut actual, typo);
+            assert_eq!(*expected, actual, "{} -> {}", typo, correction);
+
+            let mut actual = Cow::Owned(String::from(*correction));
+            case_correct(&mut actual, typo);
+            assert_eq!(*expected, actual, "{} -> {}", typo, correction);
+        }
+    }
+
+    #[cfg(feature = "dict")]
+    #[test]
+    fn test_dict_correct_mixed_case() {
+        let dict = BuiltIn::new(crate::config::Locale::default());
+        let correction = dict.correct_word(typos::tokens::Word::new_unchecked(
+            "FinalLizes",
+            typos::tokens::Case::None,
+            0,
+        ));
+        assert_eq!(
+            correction,
+            Some(Status::Corrections(vec!["Finalizes".into()]))
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn case_correct_never_panics(correction in "\\PC{0,16}", typo in "\\PC{0,16}") {
+            let mut actual = Cow::Borrowed(correction.as_str());
+            case_correct(&mut actual, &typo);
+        }
+
+        #[test]
+        fn case_correct_keeps_letters(correction in "[a-z]{1,16}", typo in "[a-zA-Z]{1,16}") {
+            let mut actual = Cow::Borrowed(correction.as_str());
+            case_correct(&mut actual, &typo);
+            proptest::prop_assert_eq!(actual.to_ascii_lowercase(), correction);
+        }
+
+        #[test]
+        fn case_correct_reproduces_case(typo in "[a-zA-ZÀ-ÖØ-Þà-öø-þ]{1,16}") {
+            let correction = typo.to_lowercase();
+            let mut actual = Cow::Borrowed(correction.as_str());
+            case_correct(&mut actual, &typo);
+            proptest::prop_assert_eq!(actual, typo);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Configuration and built-in dictionaries shared by `typos-cli` and its bindings.
+//!
+//! This API is unstable.
+
+pub mod config;
+pub mod dict;
+
+mod severity;
+
+pub use severity::*;
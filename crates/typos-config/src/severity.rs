@@ -0,0 +1,75 @@
+/// How much a finding matters, ordered from least to most severe.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Info,
+    Warning,
+    /// Fails the run
+    Error,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Severity::Error
+    }
+}
+
+impl Severity {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// How severe each kind of finding is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Severities {
+    /// Misspellings from the built-in dictionary, including phrases
+    pub typo: Severity,
+    /// Words spelled for a different locale
+    pub locale: Severity,
+    /// Findings from the user's own corrections
+    pub custom: Severity,
+    /// Typos in file names, whatever their source
+    pub filename: Severity,
+    pub repeated_words: Severity,
+}
+
+impl Severities {
+    pub fn from_config(config: &crate::config::SeverityConfig) -> Self {
+        Self {
+            typo: config.typo(),
+            locale: config.locale(),
+            custom: config.custom(),
+            filename: config.filename(),
+            repeated_words: config.repeated_words(),
+        }
+    }
+
+    /// The severity of a typo found with a Dictionary.
+    pub fn of(&self, source: typos::Source) -> Severity {
+        match source {
+            typos::Source::Locale => self.locale,
+            typos::Source::Custom => self.custom,
+            _ => self.typo,
+        }
+    }
+}
+
+impl Default for Severities {
+    fn default() -> Self {
+        Self::from_config(&Default::default())
+    }
+}
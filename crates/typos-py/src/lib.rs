@@ -0,0 +1,324 @@
+//! Python bindings for finding typos.
+//!
+//! Build with `maturin develop` from `crates/typos-py` and test with `pytest`.
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Define rules for tokenizing a buffer.
+#[pyclass(module = "typos", skip_from_py_object)]
+#[derive(Clone, Default)]
+struct TokenizerBuilder {
+    inner: typos::tokens::TokenizerBuilder,
+}
+
+#[pymethods]
+impl TokenizerBuilder {
+    #[new]
+    fn new() -> Self {
+        Default::default()
+    }
+
+    /// Specify that unicode Identifiers are allowed.
+    fn unicode(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.unicode(yes);
+        slf
+    }
+
+    /// Specify that hexadecimal numbers should be ignored.
+    fn ignore_hex(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.ignore_hex(yes);
+        slf
+    }
+
+    /// Specify that leading digits are allowed for Identifiers.
+    fn leading_digits(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.leading_digits(yes);
+        slf
+    }
+
+    /// Specify that escape sequences and format specifiers are not part of Identifiers.
+    fn escapes(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.escapes(yes);
+        slf
+    }
+
+    /// Specify that `-` is allowed within Identifiers.
+    fn dashes(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.dashes(yes);
+        slf
+    }
+
+    /// Specify that `.` is allowed within Identifiers.
+    fn dots(mut slf: PyRefMut<'_, Self>, yes: bool) -> PyRefMut<'_, Self> {
+        slf.inner.dots(yes);
+        slf
+    }
+
+    fn build(&self) -> Tokenizer {
+        Tokenizer {
+            inner: self.inner.build(),
+        }
+    }
+}
+
+/// Extract Identifiers from a buffer.
+#[pyclass(module = "typos", frozen)]
+struct Tokenizer {
+    inner: typos::tokens::Tokenizer,
+}
+
+#[pymethods]
+impl Tokenizer {
+    /// Tokenizer with the default rules, see `TokenizerBuilder` for customizing them.
+    #[new]
+    fn new() -> Self {
+        TokenizerBuilder::new().build()
+    }
+}
+
+#[pyclass(module = "typos", frozen, eq, eq_int, from_py_object)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Locale {
+    #[pyo3(name = "EN")]
+    En,
+    #[pyo3(name = "EN_US")]
+    EnUs,
+    #[pyo3(name = "EN_GB")]
+    EnGb,
+    #[pyo3(name = "EN_CA")]
+    EnCa,
+    #[pyo3(name = "EN_AU")]
+    EnAu,
+}
+
+#[pymethods]
+impl Locale {
+    /// Look up a locale by its config name, like `en-gb`.
+    #[staticmethod]
+    fn parse(value: &str) -> PyResult<Self> {
+        let locale: typos_cli::config::Locale = value.parse().map_err(PyValueError::new_err)?;
+        Ok(Self::from(locale))
+    }
+
+    fn __str__(&self) -> String {
+        typos_cli::config::Locale::from(*self).to_string()
+    }
+}
+
+impl From<typos_cli::config::Locale> for Locale {
+    fn from(locale: typos_cli::config::Locale) -> Self {
+        match locale {
+            typos_cli::config::Locale::En => Locale::En,
+            typos_cli::config::Locale::EnUs => Locale::EnUs,
+            typos_cli::config::Locale::EnGb => Locale::EnGb,
+            typos_cli::config::Locale::EnCa => Locale::EnCa,
+            typos_cli::config::Locale::EnAu => Locale::EnAu,
+        }
+    }
+}
+
+impl From<Locale> for typos_cli::config::Locale {
+    fn from(locale: Locale) -> Self {
+        match locale {
+            Locale::En => typos_cli::config::Locale::En,
+            Locale::EnUs => typos_cli::config::Locale::EnUs,
+            Locale::EnGb => typos_cli::config::Locale::EnGb,
+            Locale::EnCa => typos_cli::config::Locale::EnCa,
+            Locale::EnAu => typos_cli::config::Locale::EnAu,
+        }
+    }
+}
+
+/// The built-in corrections.
+#[pyclass(module = "typos", frozen, skip_from_py_object)]
+#[derive(Clone)]
+struct BuiltIn {
+    #[pyo3(get)]
+    locale: Locale,
+    #[pyo3(get)]
+    locale_identifiers: bool,
+}
+
+impl BuiltIn {
+    fn to_dict(&self) -> typos_cli::dict::BuiltIn {
+        let mut dict = typos_cli::dict::BuiltIn::new(self.locale.into());
+        dict.locale_identifiers(self.locale_identifiers);
+        dict
+    }
+}
+
+#[pymethods]
+impl BuiltIn {
+    #[new]
+    #[pyo3(signature = (locale = Locale::En, locale_identifiers = true))]
+    fn new(locale: Locale, locale_identifiers: bool) -> Self {
+        Self {
+            locale,
+            locale_identifiers,
+        }
+    }
+}
+
+/// Extend a dictionary with project-specific `identifiers` and `words`.
+///
+/// Map a typo to itself to mark it as valid or to `""` to disallow it.
+#[pyclass(module = "typos", frozen)]
+struct Override {
+    inner: BuiltIn,
+    identifiers: HashMap<String, String>,
+    words: HashMap<String, String>,
+}
+
+#[pymethods]
+impl Override {
+    #[new]
+    #[pyo3(signature = (inner, *, identifiers = None, words = None))]
+    fn new(
+        inner: PyRef<'_, BuiltIn>,
+        identifiers: Option<HashMap<String, String>>,
+        words: Option<HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            inner: inner.clone(),
+            identifiers: identifiers.unwrap_or_default(),
+            words: words.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(FromPyObject)]
+enum Dictionary<'py> {
+    BuiltIn(PyRef<'py, BuiltIn>),
+    Override(PyRef<'py, Override>),
+}
+
+/// Whether a word is valid, and how to correct it if not.
+#[pyclass(module = "typos", frozen, eq, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq)]
+struct Status {
+    inner: typos::Status<'static>,
+}
+
+#[pymethods]
+impl Status {
+    fn is_valid(&self) -> bool {
+        self.inner.is_valid()
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.inner.is_invalid()
+    }
+
+    fn is_correction(&self) -> bool {
+        self.inner.is_correction()
+    }
+
+    /// Suggested replacements, empty unless `is_correction()`.
+    #[getter]
+    fn corrections(&self) -> Vec<String> {
+        match &self.inner {
+            typos::Status::Corrections(corrections) => {
+                corrections.iter().map(|c| c.as_ref().to_owned()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            typos::Status::Valid => "Status.Valid".to_owned(),
+            typos::Status::Invalid => "Status.Invalid".to_owned(),
+            typos::Status::Corrections(_) => {
+                format!("Status.Corrections({:?})", self.corrections())
+            }
+        }
+    }
+}
+
+/// A typo found in a buffer.
+#[pyclass(module = "typos", frozen, eq, skip_from_py_object)]
+#[derive(Clone, PartialEq, Eq)]
+struct Typo {
+    /// UTF-8 offset within the buffer
+    #[pyo3(get)]
+    byte_offset: usize,
+    /// Character offset within the buffer, for indexing into `str`
+    #[pyo3(get)]
+    offset: usize,
+    #[pyo3(get)]
+    typo: String,
+    #[pyo3(get)]
+    corrections: Status,
+}
+
+#[pymethods]
+impl Typo {
+    fn __repr__(&self) -> String {
+        format!(
+            "Typo(byte_offset={}, offset={}, typo={:?}, corrections={})",
+            self.byte_offset,
+            self.offset,
+            self.typo,
+            self.corrections.__repr__()
+        )
+    }
+}
+
+/// Find the typos in `buffer`.
+#[pyfunction]
+fn check_str(buffer: &str, tokenizer: &Tokenizer, dictionary: Dictionary<'_>) -> Vec<Typo> {
+    match dictionary {
+        Dictionary::BuiltIn(dict) => collect_typos(buffer, &tokenizer.inner, &dict.to_dict()),
+        Dictionary::Override(dict) => {
+            let mut overridden = typos_cli::dict::Override::new(dict.inner.to_dict());
+            overridden.identifiers(
+                dict.identifiers
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str())),
+            );
+            overridden.words(dict.words.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+            collect_typos(buffer, &tokenizer.inner, &overridden)
+        }
+    }
+}
+
+fn collect_typos(
+    buffer: &str,
+    tokenizer: &typos::tokens::Tokenizer,
+    dictionary: &dyn typos::Dictionary,
+) -> Vec<Typo> {
+    let mut offset = 0;
+    let mut last_offset = 0;
+    typos::check_str(buffer, tokenizer, dictionary)
+        .map(|typo| {
+            offset += buffer[last_offset..typo.byte_offset].chars().count();
+            last_offset = typo.byte_offset;
+            Typo {
+                byte_offset: typo.byte_offset,
+                offset,
+                typo: typo.typo.into_owned(),
+                corrections: Status {
+                    inner: typo.corrections.into_owned(),
+                },
+            }
+        })
+        .collect()
+}
+
+#[pymodule]
+#[pyo3(name = "typos")]
+fn typos_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<TokenizerBuilder>()?;
+    m.add_class::<Tokenizer>()?;
+    m.add_class::<Locale>()?;
+    m.add_class::<BuiltIn>()?;
+    m.add_class::<Override>()?;
+    m.add_class::<Status>()?;
+    m.add_class::<Typo>()?;
+    m.add_function(wrap_pyfunction!(check_str, m)?)?;
+    Ok(())
+}
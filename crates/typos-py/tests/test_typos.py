@@ -0,0 +1,75 @@
+import pytest
+
+import typos
+
+
+def test_check_str():
+    tokenizer = typos.Tokenizer()
+    dictionary = typos.BuiltIn()
+
+    actual = typos.check_str("Hello\n😀 apropriate world", tokenizer, dictionary)
+
+    assert len(actual) == 1
+    typo = actual[0]
+    assert typo.byte_offset == 11
+    assert typo.offset == 8
+    assert typo.typo == "apropriate"
+    assert typo.corrections.is_correction()
+    assert typo.corrections.corrections == ["appropriate"]
+
+
+def test_check_str_empty():
+    assert typos.check_str("", typos.Tokenizer(), typos.BuiltIn()) == []
+
+
+def test_tokenizer_builder():
+    default = typos.TokenizerBuilder().build()
+    leading_digits = typos.TokenizerBuilder().leading_digits(True).build()
+    ascii = typos.TokenizerBuilder().unicode(False).build()
+    escapes = typos.TokenizerBuilder().escapes(True).build()
+    dashes = typos.TokenizerBuilder().dashes(True).build()
+
+    assert typos.check_str("1apropriate", default, typos.BuiltIn()) == []
+    assert len(typos.check_str("1apropriate", leading_digits, typos.BuiltIn())) == 1
+    assert typos.check_str("ñapropriate", default, typos.BuiltIn()) == []
+    assert len(typos.check_str("ñapropriate", ascii, typos.BuiltIn())) == 1
+    assert typos.check_str("\\napropriate", default, typos.BuiltIn()) == []
+    assert len(typos.check_str("\\napropriate", escapes, typos.BuiltIn())) == 1
+    assert len(typos.check_str("serde-apropriate", dashes, typos.BuiltIn())) == 1
+
+
+def test_locale():
+    dictionary = typos.BuiltIn(typos.Locale.EN_GB)
+
+    actual = typos.check_str("color", typos.Tokenizer(), dictionary)
+
+    assert [t.corrections.corrections for t in actual] == [["colour"]]
+
+
+def test_locale_identifiers():
+    dictionary = typos.BuiltIn(typos.Locale.EN_GB, locale_identifiers=False)
+
+    actual = typos.check_str("set_color", typos.Tokenizer(), dictionary)
+
+    assert actual == []
+
+
+def test_locale_parse():
+    assert typos.Locale.parse("en-gb") == typos.Locale.EN_GB
+    assert str(typos.Locale.EN_GB) == "en-gb"
+    with pytest.raises(ValueError):
+        typos.Locale.parse("en-xx")
+
+
+def test_override():
+    dictionary = typos.Override(
+        typos.BuiltIn(),
+        words={"hellp": "help", "apropriate": "apropriate", "foo": ""},
+    )
+
+    actual = typos.check_str("hellp apropriate foo", typos.Tokenizer(), dictionary)
+
+    assert [(t.typo, t.offset) for t in actual] == [("hellp", 0), ("foo", 17)]
+    assert actual[0].corrections.corrections == ["help"]
+    assert actual[1].corrections.is_invalid()
+    assert actual[1].corrections.corrections == []
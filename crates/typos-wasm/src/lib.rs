@@ -0,0 +1,226 @@
+//! WebAssembly bindings for finding typos.
+//!
+//! Build with `wasm-pack build crates/typos-wasm` and test with
+//! `wasm-pack test --node crates/typos-wasm`.
+
+use wasm_bindgen::prelude::*;
+
+/// Find the typos in `text`.
+///
+/// `options` takes the same fields as `[default]` in `typos.toml`, like `locale` or
+/// `extend-words`, and may be `undefined`.  See `check_str` for those that are ignored.
+#[wasm_bindgen]
+pub fn check(text: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = if options.is_undefined() || options.is_null() {
+        typos_config::config::EngineConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)?
+    };
+    let findings = check_str(text, &options);
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(serde::Serialize::serialize(&findings, &serializer)?)
+}
+
+/// A typo found in the text.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[non_exhaustive]
+pub struct Finding {
+    pub line_num: usize,
+    /// UTF-8 offset within the text
+    pub byte_offset: usize,
+    /// UTF-16 offset within the text, for indexing into JavaScript strings
+    pub offset: usize,
+    pub typo: String,
+    /// `None` for disallowed words
+    pub corrections: Option<Vec<String>>,
+    pub severity: typos_config::Severity,
+}
+
+/// Find the typos in `text`, configured like `[default]` in `typos.toml`.
+///
+/// There are no files to read, so `compound-word-list` is ignored, along with the fields for
+/// choosing and decoding files, like `binary`, `markup`, and `archives`.
+pub fn check_str(text: &str, options: &typos_config::config::EngineConfig) -> Vec<Finding> {
+    let tokenizer_config = options
+        .tokenizer
+        .clone()
+        .unwrap_or_else(typos_config::config::TokenizerConfig::from_defaults);
+    let dict_config = options
+        .dict
+        .clone()
+        .unwrap_or_else(typos_config::config::DictConfig::from_defaults);
+    let severities = typos_config::Severities::from_config(
+        &options
+            .severity
+            .clone()
+            .unwrap_or_else(typos_config::config::SeverityConfig::from_defaults),
+    );
+
+    let tokenizer = typos::tokens::TokenizerBuilder::new()
+        .unicode(tokenizer_config.unicode())
+        .ignore_hex(tokenizer_config.ignore_hex())
+        .leading_digits(tokenizer_config.identifier_leading_digits())
+        .escapes(tokenizer_config.escapes())
+        .dashes(tokenizer_config.identifier_dashes())
+        .dots(tokenizer_config.identifier_dots())
+        .build();
+
+    let mut dict = typos_config::dict::BuiltIn::new(dict_config.locale());
+    dict.locale_identifiers(dict_config.locale_identifiers());
+    dict.phrases(dict_config.phrases());
+    let mut dict = typos_config::dict::Override::new(dict);
+    dict.identifiers(dict_config.extend_identifiers());
+    dict.words(dict_config.extend_words());
+    dict.phrases(dict_config.extend_phrases());
+
+    let mut typos: Vec<_> = typos::check_str(text, &tokenizer, &dict)
+        .map(|typo| {
+            let severity = severities.of(typos::Dictionary::source(&dict, &typo.typo));
+            (typo, severity)
+        })
+        .collect();
+    if options.repeated_words() {
+        typos.extend(
+            typos::check_repeated_str(text, &tokenizer, &dict)
+                .map(|typo| (typo, severities.repeated_words)),
+        );
+        // Stable, so dictionary typos win over repeated words at the same offset
+        typos.sort_by_key(|(typo, _)| typo.byte_offset);
+        let mut end = 0;
+        typos.retain(|(typo, _)| {
+            let keep = end <= typo.byte_offset;
+            if keep {
+                end = typo.byte_offset + typo.typo.len();
+            }
+            keep
+        });
+    }
+
+    let mut findings = Vec::new();
+    let mut line_num = 1;
+    let mut offset = 0;
+    let mut last_offset = 0;
+    for (typo, severity) in typos {
+        let skipped = &text[last_offset..typo.byte_offset];
+        line_num += skipped.matches('\n').count();
+        offset += skipped.encode_utf16().count();
+        last_offset = typo.byte_offset;
+
+        let corrections = match typo.corrections {
+            typos::Status::Valid => continue,
+            typos::Status::Invalid => None,
+            typos::Status::Corrections(corrections) => {
+                Some(corrections.iter().map(|c| c.as_ref().to_owned()).collect())
+            }
+        };
+        findings.push(Finding {
+            line_num,
+            byte_offset: typo.byte_offset,
+            offset,
+            typo: typo.typo.into_owned(),
+            corrections,
+            severity,
+        });
+    }
+    findings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_str() {
+        let actual = check_str(
+            "Hello\n😀 apropriate world",
+            &typos_config::config::EngineConfig::default(),
+        );
+        assert_eq!(
+            actual,
+            vec![Finding {
+                line_num: 2,
+                byte_offset: 11,
+                offset: 9,
+                typo: "apropriate".to_owned(),
+                corrections: Some(vec!["appropriate".to_owned()]),
+                severity: typos_config::Severity::Error,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_str_options() {
+        let mut dict = typos_config::config::DictConfig {
+            locale: Some(typos_config::config::Locale::EnGb),
+            ..Default::default()
+        };
+        dict.extend_words.insert("hellp".into(), "help".into());
+        let severity = typos_config::config::SeverityConfig {
+            locale: Some(typos_config::Severity::Warning),
+            ..Default::default()
+        };
+        let options = typos_config::config::EngineConfig {
+            severity: Some(severity),
+            dict: Some(dict),
+            ..Default::default()
+        };
+
+        let actual = check_str("hellp color", &options);
+        assert_eq!(
+            actual,
+            vec![
+                Finding {
+                    line_num: 1,
+                    byte_offset: 0,
+                    offset: 0,
+                    typo: "hellp".to_owned(),
+                    corrections: Some(vec!["help".to_owned()]),
+                    severity: typos_config::Severity::Error,
+                },
+                Finding {
+                    line_num: 1,
+                    byte_offset: 6,
+                    offset: 6,
+                    typo: "color".to_owned(),
+                    corrections: Some(vec!["colour".to_owned()]),
+                    severity: typos_config::Severity::Warning,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_str_repeated_words() {
+        let options = typos_config::config::EngineConfig {
+            repeated_words: Some(true),
+            ..Default::default()
+        };
+
+        let actual = check_str(
+            "See the
+the apropriate docs",
+            &options,
+        );
+        assert_eq!(
+            actual,
+            vec![
+                Finding {
+                    line_num: 2,
+                    byte_offset: 8,
+                    offset: 8,
+                    typo: "the ".to_owned(),
+                    corrections: Some(vec!["".to_owned()]),
+                    severity: typos_config::Severity::Error,
+                },
+                Finding {
+                    line_num: 2,
+                    byte_offset: 12,
+                    offset: 12,
+                    typo: "apropriate".to_owned(),
+                    corrections: Some(vec!["appropriate".to_owned()]),
+                    severity: typos_config::Severity::Error,
+                },
+            ]
+        );
+    }
+}
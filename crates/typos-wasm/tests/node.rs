@@ -0,0 +1,38 @@
+//! Run with `wasm-pack test --node crates/typos-wasm`
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+fn check_json(text: &str, options: &str) -> String {
+    let options = js_sys::JSON::parse(options).unwrap();
+    let findings = typos_wasm::check(text, options).unwrap();
+    js_sys::JSON::stringify(&findings).unwrap().into()
+}
+
+#[wasm_bindgen_test]
+fn check_default() {
+    let findings = typos_wasm::check("apropriate", wasm_bindgen::JsValue::UNDEFINED).unwrap();
+    let actual: String = js_sys::JSON::stringify(&findings).unwrap().into();
+    assert_eq!(
+        actual,
+        r#"[{"line_num":1,"byte_offset":0,"offset":0,"typo":"apropriate","corrections":["appropriate"],"severity":"error"}]"#
+    );
+}
+
+#[wasm_bindgen_test]
+fn check_options() {
+    let actual = check_json(
+        "😀 hellp",
+        r#"{"locale": "en-gb", "extend-words": {"hellp": "help"}}"#,
+    );
+    assert_eq!(
+        actual,
+        r#"[{"line_num":1,"byte_offset":5,"offset":3,"typo":"hellp","corrections":["help"],"severity":"error"}]"#
+    );
+}
+
+#[wasm_bindgen_test]
+fn check_invalid_options() {
+    let options = js_sys::JSON::parse(r#"{"not-a-field": true}"#).unwrap();
+    assert!(typos_wasm::check("", options).is_err());
+}
@@ -1,15 +1,20 @@
 use crate::tokens;
 use crate::Dictionary;
+use itertools::Itertools;
 use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// Most Identifiers a phrase can span.
+pub const MAX_PHRASE_LEN: usize = 4;
 
 pub fn check_str<'b, 's: 'b>(
     buffer: &'b str,
     tokenizer: &'s tokens::Tokenizer,
     dictionary: &'s dyn Dictionary,
 ) -> impl Iterator<Item = Typo<'b>> {
-    tokenizer
-        .parse_str(buffer)
-        .flat_map(move |ident| process_ident(ident, dictionary))
+    let idents = tokenizer.parse_str(buffer);
+    Phrases::new(buffer.as_bytes(), idents, dictionary)
+        .flat_map(move |phrase| process_phrase(phrase, tokenizer, dictionary, Origin::Prose))
 }
 
 pub fn check_bytes<'b, 's: 'b>(
@@ -17,14 +22,206 @@ pub fn check_bytes<'b, 's: 'b>(
     tokenizer: &'s tokens::Tokenizer,
     dictionary: &'s dyn Dictionary,
 ) -> impl Iterator<Item = Typo<'b>> {
-    tokenizer
-        .parse_bytes(buffer)
-        .flat_map(move |ident| process_ident(ident, dictionary))
+    let idents = tokenizer.parse_bytes(buffer);
+    Phrases::new(buffer, idents, dictionary)
+        .flat_map(move |phrase| process_phrase(phrase, tokenizer, dictionary, Origin::Prose))
+}
+
+/// Find the typos in source code, outside of its comments.
+///
+/// Every Word is looked up with `Dictionary::correct_ident_word`, even when it is a whole
+/// Identifier, like `color` in `style.color`.
+pub fn check_code_str<'b, 's: 'b>(
+    buffer: &'b str,
+    tokenizer: &'s tokens::Tokenizer,
+    dictionary: &'s dyn Dictionary,
+) -> impl Iterator<Item = Typo<'b>> {
+    let idents = tokenizer.parse_str(buffer);
+    Phrases::new(buffer.as_bytes(), idents, dictionary)
+        .flat_map(move |phrase| process_phrase(phrase, tokenizer, dictionary, Origin::Code))
+}
+
+/// Find the typos in source code, outside of its comments.
+///
+/// See `check_code_str`.
+pub fn check_code_bytes<'b, 's: 'b>(
+    buffer: &'b [u8],
+    tokenizer: &'s tokens::Tokenizer,
+    dictionary: &'s dyn Dictionary,
+) -> impl Iterator<Item = Typo<'b>> {
+    let idents = tokenizer.parse_bytes(buffer);
+    Phrases::new(buffer, idents, dictionary)
+        .flat_map(move |phrase| process_phrase(phrase, tokenizer, dictionary, Origin::Code))
+}
+
+/// Find words repeated with only whitespace between them, like `the the`.
+///
+/// The fix deletes the repeat with the whitespace before it, or with the spaces after it when it
+/// starts a line, so lines stay intact.  Repeats the dictionary considers a valid phrase, like
+/// `had had`, are skipped.
+pub fn check_repeated_str<'b, 's: 'b>(
+    buffer: &'b str,
+    tokenizer: &'s tokens::Tokenizer,
+    dictionary: &'s dyn Dictionary,
+) -> impl Iterator<Item = Typo<'b>> {
+    let idents = tokenizer.parse_str(buffer);
+    check_repeated(buffer.as_bytes(), idents, dictionary)
+}
+
+/// Find words repeated with only whitespace between them, like `the the`.
+///
+/// See `check_repeated_str`.
+pub fn check_repeated_bytes<'b, 's: 'b>(
+    buffer: &'b [u8],
+    tokenizer: &'s tokens::Tokenizer,
+    dictionary: &'s dyn Dictionary,
+) -> impl Iterator<Item = Typo<'b>> {
+    let idents = tokenizer.parse_bytes(buffer);
+    check_repeated(buffer, idents, dictionary)
+}
+
+fn check_repeated<'b, 's: 'b>(
+    buffer: &'b [u8],
+    idents: impl Iterator<Item = tokens::Identifier<'b>>,
+    dictionary: &'s dyn Dictionary,
+) -> impl Iterator<Item = Typo<'b>> {
+    idents.tuple_windows().filter_map(move |(prev, next)| {
+        let is_repeat = is_joined(buffer, prev, next)
+            && lowercase(prev.token()).eq(lowercase(next.token()))
+            && !matches!(
+                dictionary.correct_phrase(&[prev, next]),
+                Some((2, crate::Status::Valid))
+            );
+        if !is_repeat {
+            return None;
+        }
+
+        let gap_start = prev.offset() + prev.token().len();
+        let end = next.offset() + next.token().len();
+        let (start, end) = if buffer[gap_start..next.offset()].contains(&b'\n') {
+            let spaces = buffer[end..]
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t'))
+                .count();
+            (next.offset(), end + spaces)
+        } else {
+            (gap_start, end)
+        };
+        // An Identifier with ASCII whitespace
+        let typo = std::str::from_utf8(&buffer[start..end]).expect("repeats are always UTF-8");
+        Some(Typo {
+            byte_offset: start,
+            typo: typo.into(),
+            corrections: crate::Status::Corrections(vec![Cow::Borrowed("")]),
+        })
+    })
+}
+
+fn lowercase(token: &str) -> impl Iterator<Item = char> + '_ {
+    token.chars().flat_map(char::to_lowercase)
+}
+
+/// Where the text being checked comes from.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Origin {
+    /// Prose, including comments
+    Prose,
+    /// Source code, where every Word is part of an Identifier
+    Code,
+}
+
+fn process_phrase<'i, 's: 'i>(
+    phrase: Phrase<'i>,
+    tokenizer: &'s tokens::Tokenizer,
+    dictionary: &'s dyn Dictionary,
+    origin: Origin,
+) -> impl Iterator<Item = Typo<'i>> {
+    match phrase {
+        Phrase::Typo(typo) => itertools::Either::Left(Some(typo).into_iter()),
+        Phrase::Ident(ident) => {
+            itertools::Either::Right(process_ident(ident, tokenizer, dictionary, origin))
+        }
+    }
+}
+
+enum Phrase<'b> {
+    /// A phrase the dictionary knows about, skipping the checks of its Identifiers
+    Typo(Typo<'b>),
+    Ident(tokens::Identifier<'b>),
+}
+
+/// Group the Identifiers that make up the dictionary's phrases, like `can not`.
+struct Phrases<'b, 's, I> {
+    buffer: &'b [u8],
+    idents: I,
+    pending: VecDeque<tokens::Identifier<'b>>,
+    dictionary: &'s dyn Dictionary,
+}
+
+impl<'b, 's, I: Iterator<Item = tokens::Identifier<'b>>> Phrases<'b, 's, I> {
+    fn new(buffer: &'b [u8], idents: I, dictionary: &'s dyn Dictionary) -> Self {
+        Self {
+            buffer,
+            idents,
+            pending: VecDeque::with_capacity(MAX_PHRASE_LEN),
+            dictionary,
+        }
+    }
+}
+
+impl<'b, 's: 'b, I: Iterator<Item = tokens::Identifier<'b>>> Iterator for Phrases<'b, 's, I> {
+    type Item = Phrase<'b>;
+
+    fn next(&mut self) -> Option<Phrase<'b>> {
+        loop {
+            while self.pending.len() < MAX_PHRASE_LEN {
+                match self.idents.next() {
+                    Some(ident) => self.pending.push_back(ident),
+                    None => break,
+                }
+            }
+            if self.pending.is_empty() {
+                return None;
+            }
+
+            let pending = self.pending.make_contiguous();
+            let mut len = 1;
+            while len < pending.len() && is_joined(self.buffer, pending[len - 1], pending[len]) {
+                len += 1;
+            }
+            let (count, corrections) = match self.dictionary.correct_phrase(&pending[..len]) {
+                Some((count, corrections)) if 0 < count && count <= len => (count, corrections),
+                _ => return self.pending.pop_front().map(Phrase::Ident),
+            };
+
+            let first = pending[0];
+            let last = pending[count - 1];
+            self.pending.drain(..count);
+            if !corrections.is_valid() {
+                let span = &self.buffer[first.offset()..last.offset() + last.token().len()];
+                // Identifiers joined by ASCII whitespace
+                let span = std::str::from_utf8(span).expect("phrases are always UTF-8");
+                return Some(Phrase::Typo(Typo {
+                    byte_offset: first.offset(),
+                    typo: span.into(),
+                    corrections,
+                }));
+            }
+        }
+    }
+}
+
+/// Whether only whitespace separates two Identifiers.
+fn is_joined(buffer: &[u8], prev: tokens::Identifier<'_>, next: tokens::Identifier<'_>) -> bool {
+    let gap = &buffer[prev.offset() + prev.token().len()..next.offset()];
+    !gap.is_empty() && gap.iter().all(u8::is_ascii_whitespace)
 }
 
 fn process_ident<'i, 's: 'i>(
     ident: tokens::Identifier<'i>,
+    tokenizer: &'s tokens::Tokenizer,
     dictionary: &'s dyn Dictionary,
+    origin: Origin,
 ) -> impl Iterator<Item = Typo<'i>> {
     match dictionary.correct_ident(ident) {
         Some(crate::Status::Valid) => itertools::Either::Left(None.into_iter()),
@@ -39,26 +236,54 @@ fn process_ident<'i, 's: 'i>(
         None => itertools::Either::Right(
             ident
                 .split()
-                .filter_map(move |word| process_word(word, dictionary)),
+                .flat_map(move |word| process_word(ident, word, tokenizer, dictionary, origin)),
         ),
     }
 }
 
 fn process_word<'w, 's: 'w>(
+    ident: tokens::Identifier<'w>,
     word: tokens::Word<'w>,
+    tokenizer: &'s tokens::Tokenizer,
     dictionary: &'s dyn Dictionary,
-) -> Option<Typo<'w>> {
-    match dictionary.correct_word(word) {
-        Some(crate::Status::Valid) => None,
-        Some(corrections) => {
-            let typo = Typo {
-                byte_offset: word.offset(),
-                typo: word.token().into(),
-                corrections,
-            };
-            Some(typo)
-        }
-        None => None,
+    origin: Origin,
+) -> impl Iterator<Item = Typo<'w>> {
+    // Only look for run-together Words when the dictionary has no verdict on the whole Word, so
+    // known misspellings like `occured` aren't lost to `occur` + `ed`.
+    match correct_word(ident, word, dictionary, origin) {
+        Some(status) => itertools::Either::Left(to_typo(word, status).into_iter()),
+        None => itertools::Either::Right(tokenizer.segment(word).filter_map(move |part| {
+            if part == word {
+                return None;
+            }
+            correct_word(ident, part, dictionary, origin).and_then(|status| to_typo(part, status))
+        })),
+    }
+}
+
+fn correct_word<'w, 's>(
+    ident: tokens::Identifier<'w>,
+    word: tokens::Word<'w>,
+    dictionary: &'s dyn Dictionary,
+    origin: Origin,
+) -> Option<crate::Status<'s>> {
+    // Even in prose, a Word that is only part of an Identifier, like `color` in `set_color`, is
+    // a reference to code
+    if origin == Origin::Code || word.token().len() != ident.token().len() {
+        dictionary.correct_ident_word(word)
+    } else {
+        dictionary.correct_word(word)
+    }
+}
+
+fn to_typo<'w>(word: tokens::Word<'w>, status: crate::Status<'w>) -> Option<Typo<'w>> {
+    match status {
+        crate::Status::Valid => None,
+        corrections => Some(Typo {
+            byte_offset: word.offset(),
+            typo: word.token().into(),
+            corrections,
+        }),
     }
 }
 
@@ -97,3 +322,148 @@ impl<'m> Default for Typo<'m> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Status;
+
+    struct PhraseDict(crate::Phrases<'static>);
+
+    impl Dictionary for PhraseDict {
+        fn correct_ident<'s, 'w>(&'s self, _ident: tokens::Identifier<'w>) -> Option<Status<'s>> {
+            None
+        }
+
+        fn correct_word<'s, 'w>(&'s self, word: tokens::Word<'w>) -> Option<Status<'s>> {
+            if word.token() == "teh" {
+                Some(Status::Corrections(vec!["the".into()]))
+            } else {
+                None
+            }
+        }
+
+        fn correct_phrase<'s, 'w>(
+            &'s self,
+            idents: &[tokens::Identifier<'w>],
+        ) -> Option<(usize, Status<'s>)> {
+            self.0.correct(idents)
+        }
+    }
+
+    #[test]
+    fn check_phrases() {
+        let dict = PhraseDict(crate::Phrases::from_csv(
+            "can not,cannot\nteh end,teh end\n",
+        ));
+        let tokenizer = tokens::Tokenizer::new();
+
+        let input = "I can  not, can_not, or can\nnot see teh end of teh";
+        let actual: Vec<_> = check_str(input, &tokenizer, &dict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        let expected = vec![
+            (2, "can  not".to_owned()),
+            (24, "can\nnot".to_owned()),
+            (47, "teh".to_owned()),
+        ];
+        assert_eq!(actual, expected);
+
+        let actual: Vec<_> = check_bytes(input.as_bytes(), &tokenizer, &dict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    struct WordDict;
+
+    impl Dictionary for WordDict {
+        fn correct_ident<'s, 'w>(&'s self, _ident: tokens::Identifier<'w>) -> Option<Status<'s>> {
+            None
+        }
+
+        fn correct_word<'s, 'w>(&'s self, word: tokens::Word<'w>) -> Option<Status<'s>> {
+            match word.token() {
+                "occured" => Some(Status::Corrections(vec!["occurred".into()])),
+                "teh" => Some(Status::Corrections(vec!["the".into()])),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn check_whole_word_before_segmenting() {
+        let mut segmenter = tokens::Segmenter::new();
+        segmenter
+            .insert("occur", 500)
+            .insert("ed", 500)
+            .insert("data", 1000);
+        let mut tokenizer = tokens::Tokenizer::new();
+        tokenizer.segmenter(Some(std::sync::Arc::new(segmenter)));
+
+        let input = "occured tehdata";
+        let actual: Vec<_> = check_str(input, &tokenizer, &WordDict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        let expected = vec![(0, "occured".to_owned()), (8, "teh".to_owned())];
+        assert_eq!(actual, expected);
+    }
+
+    struct LocaleDict;
+
+    impl Dictionary for LocaleDict {
+        fn correct_ident<'s, 'w>(&'s self, _ident: tokens::Identifier<'w>) -> Option<Status<'s>> {
+            None
+        }
+
+        fn correct_word<'s, 'w>(&'s self, word: tokens::Word<'w>) -> Option<Status<'s>> {
+            match word.token() {
+                "color" => Some(Status::Corrections(vec!["colour".into()])),
+                _ => None,
+            }
+        }
+
+        fn correct_ident_word<'s, 'w>(&'s self, _word: tokens::Word<'w>) -> Option<Status<'s>> {
+            Some(Status::Valid)
+        }
+    }
+
+    #[test]
+    fn check_code_words_as_identifiers() {
+        let tokenizer = tokens::Tokenizer::new();
+
+        let input = "set_color(style.color) color";
+        let actual: Vec<_> = check_str(input, &tokenizer, &LocaleDict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        let expected = vec![(16, "color".to_owned()), (23, "color".to_owned())];
+        assert_eq!(actual, expected);
+
+        let actual: Vec<_> = check_code_str(input, &tokenizer, &LocaleDict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn check_repeated_words() {
+        let dict = PhraseDict(crate::Phrases::from_csv("had had,had had\n"));
+        let tokenizer = tokens::Tokenizer::new();
+
+        let input = "The the the end, had had, the\n  the  line, and and_more";
+        let actual: Vec<_> = check_repeated_str(input, &tokenizer, &dict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        let expected = vec![
+            (3, " the".to_owned()),
+            (7, " the".to_owned()),
+            (32, "the  ".to_owned()),
+        ];
+        assert_eq!(actual, expected);
+
+        let actual: Vec<_> = check_repeated_bytes(input.as_bytes(), &tokenizer, &dict)
+            .map(|t| (t.byte_offset, t.typo.into_owned()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+}
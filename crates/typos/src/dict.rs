@@ -11,6 +11,47 @@ pub trait Dictionary: Send + Sync {
     ///
     /// `None` if the status is unknown.
     fn correct_word<'s, 'w>(&'s self, word: crate::tokens::Word<'w>) -> Option<Status<'s>>;
+
+    /// Look up the validity of a Word from code rather than prose, like `color` in `set_color`
+    /// or, within source code, in `style.color`.
+    ///
+    /// `None` if the status is unknown.
+    fn correct_ident_word<'s, 'w>(&'s self, word: crate::tokens::Word<'w>) -> Option<Status<'s>> {
+        self.correct_word(word)
+    }
+
+    /// Look up the validity of a phrase, like `can not`, at the start of `idents`.
+    ///
+    /// `idents` are consecutive Identifiers separated only by whitespace.  Returns how many of
+    /// them the phrase spans.
+    ///
+    /// `None` if no phrase is known.
+    fn correct_phrase<'s, 'w>(
+        &'s self,
+        _idents: &[crate::tokens::Identifier<'w>],
+    ) -> Option<(usize, Status<'s>)> {
+        None
+    }
+
+    /// What this Dictionary's verdict on `typo` is based on.
+    ///
+    /// `typo` is the text of a Typo found with this Dictionary: an Identifier, a Word, or a
+    /// phrase.
+    fn source(&self, _typo: &str) -> Source {
+        Source::Dictionary
+    }
+}
+
+/// What a Dictionary's verdict on a term is based on.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Source {
+    /// A known misspelling
+    Dictionary,
+    /// A spelling for a different locale, like `color` when correcting to British English
+    Locale,
+    /// A correction added by the user
+    Custom,
 }
 
 /// Validity of a term in a Dictionary.
@@ -1,7 +1,9 @@
 mod check;
 mod dict;
+mod phrase;
 
 pub mod tokens;
 
 pub use check::*;
 pub use dict::*;
+pub use phrase::*;
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::tokens::Identifier;
+use crate::Status;
+
+/// Corrections for phrases of one or more words, like `can not` -> `cannot` or `alot` -> `a lot`.
+///
+/// Phrases match whole Identifiers, ignoring case.
+#[derive(Debug, Clone, Default)]
+pub struct Phrases<'p> {
+    /// Rules keyed by their lowercase first word, longest first
+    rules: HashMap<String, Vec<(Vec<String>, Status<'p>)>>,
+}
+
+impl<'p> Phrases<'p> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Read phrases from CSV, one `phrase,correction` per line.
+    ///
+    /// Like the word dictionaries, further columns are further corrections and a phrase without
+    /// any is never valid.  A phrase corrected to itself is always valid.
+    pub fn from_csv(data: &'p str) -> Self {
+        let mut phrases = Self::new();
+        for line in data.lines() {
+            let mut fields = line.split(',');
+            let phrase = fields.next().unwrap_or_default();
+            let corrections: Vec<_> = fields.filter(|f| !f.is_empty()).collect();
+            let status = if corrections.is_empty() {
+                Status::Invalid
+            } else if corrections == [phrase] {
+                Status::Valid
+            } else {
+                Status::Corrections(corrections.into_iter().map(Cow::Borrowed).collect())
+            };
+            phrases.insert(phrase, status);
+        }
+        phrases
+    }
+
+    /// Add a rule for `phrase`, made of whitespace-separated words.
+    pub fn insert(&mut self, phrase: &str, status: Status<'p>) -> &mut Self {
+        let words: Vec<_> = phrase.split_whitespace().map(str::to_lowercase).collect();
+        if let Some(first) = words.first() {
+            let rules = self.rules.entry(first.clone()).or_default();
+            rules.push((words, status));
+            rules.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether there is a rule for exactly `phrase`, ignoring case and spacing.
+    pub fn contains(&self, phrase: &str) -> bool {
+        let mut words = phrase.split_whitespace();
+        let rules = match words.next().and_then(|first| self.get(first)) {
+            Some(rules) => rules,
+            None => return false,
+        };
+        let rest: Vec<_> = words.collect();
+        rules.iter().any(|(words, _)| {
+            words.len() == rest.len() + 1
+                && words[1..]
+                    .iter()
+                    .zip(rest.iter())
+                    .all(|(word, token)| is_same_word(word, token))
+        })
+    }
+
+    /// Find the longest phrase at the start of `idents`, returning how many Identifiers it spans.
+    pub fn correct<'s, 'w>(&'s self, idents: &[Identifier<'w>]) -> Option<(usize, Status<'s>)> {
+        let first = idents.first()?;
+        let rules = self.get(first.token())?;
+        rules
+            .iter()
+            .find(|(words, _)| {
+                words.len() <= idents.len()
+                    && words
+                        .iter()
+                        .zip(idents.iter())
+                        .skip(1)
+                        .all(|(word, ident)| is_same_word(word, ident.token()))
+            })
+            .map(|(words, status)| (words.len(), status.borrow()))
+    }
+
+    fn get(&self, word: &str) -> Option<&[(Vec<String>, Status<'p>)]> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        // Avoid allocating to lowercase the common case
+        let mut buffer = [0; 64];
+        let rules = if word.is_ascii() && word.len() <= buffer.len() {
+            let buffer = &mut buffer[..word.len()];
+            buffer.copy_from_slice(word.as_bytes());
+            buffer.make_ascii_lowercase();
+            let word = std::str::from_utf8(buffer).expect("ASCII is valid UTF-8");
+            self.rules.get(word)
+        } else {
+            self.rules.get(&word.to_lowercase())
+        };
+        rules.map(|r| r.as_slice())
+    }
+}
+
+fn is_same_word(lower: &str, token: &str) -> bool {
+    token.chars().flat_map(char::to_lowercase).eq(lower.chars())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::tokens::Case;
+
+    fn idents<'s>(words: &[&'s str]) -> Vec<Identifier<'s>> {
+        words
+            .iter()
+            .map(|w| Identifier::new_unchecked(w, Case::None, 0))
+            .collect()
+    }
+
+    #[test]
+    fn correct_phrases() {
+        let phrases =
+            Phrases::from_csv("can not,cannot\nalot,a lot\nas well as,as well as\nteh,\n");
+        let cases = [
+            (
+                &["can", "not", "go"] as &[&str],
+                Some((2, Status::Corrections(vec!["cannot".into()]))),
+            ),
+            (
+                &["Can", "NOT"],
+                Some((2, Status::Corrections(vec!["cannot".into()]))),
+            ),
+            (&["can"], None),
+            (&["can", "go"], None),
+            (
+                &["alot"],
+                Some((1, Status::Corrections(vec!["a lot".into()]))),
+            ),
+            (&["as", "well", "as"], Some((3, Status::Valid))),
+            (&["teh"], Some((1, Status::Invalid))),
+            (&[], None),
+        ];
+        for (words, expected) in cases.iter() {
+            let actual = phrases.correct(&idents(words));
+            assert_eq!(actual, *expected, "{:?}", words);
+        }
+    }
+
+    #[test]
+    fn contains_phrases() {
+        let phrases = Phrases::from_csv("can not,cannot\nalot,a lot\n");
+        assert!(phrases.contains("can not"));
+        assert!(phrases.contains("Can\n  NOT"));
+        assert!(phrases.contains("alot"));
+        assert!(!phrases.contains("can"));
+        assert!(!phrases.contains("can not go"));
+        assert!(!phrases.contains(""));
+    }
+
+    #[test]
+    fn longest_phrase_wins() {
+        let mut phrases = Phrases::new();
+        phrases.insert("can not", Status::Corrections(vec!["cannot".into()]));
+        phrases.insert("can not only", Status::Valid);
+        assert_eq!(
+            phrases.correct(&idents(&["can", "not", "only"])),
+            Some((3, Status::Valid))
+        );
+        assert_eq!(
+            phrases.correct(&idents(&["can", "not", "go"])),
+            Some((2, Status::Corrections(vec!["cannot".into()])))
+        );
+    }
+}
@@ -4,6 +4,9 @@ pub struct TokenizerBuilder {
     unicode: bool,
     ignore_hex: bool,
     leading_digits: bool,
+    escapes: bool,
+    dashes: bool,
+    dots: bool,
 }
 
 impl TokenizerBuilder {
@@ -29,16 +32,52 @@ impl TokenizerBuilder {
         self
     }
 
+    /// Specify that escape sequences, format specifiers, and character references are not part
+    /// of Identifiers.
+    ///
+    /// For example, `\nparameter` is `parameter` rather than `nparameter`.  This covers C, Rust,
+    /// Python, and JSON escapes, `printf` and `{}` format specifiers, and `&amp;`-style
+    /// references.
+    pub fn escapes(&mut self, yes: bool) -> &mut Self {
+        self.escapes = yes;
+        self
+    }
+
+    /// Specify that `-` is allowed within Identifiers, like `serde-json` or `no-ignore-vcs`.
+    ///
+    /// Words are still split on `-`.
+    pub fn dashes(&mut self, yes: bool) -> &mut Self {
+        self.dashes = yes;
+        self
+    }
+
+    /// Specify that `.` is allowed within Identifiers, like `os.path`.
+    ///
+    /// Words are still split on `.`.
+    pub fn dots(&mut self, yes: bool) -> &mut Self {
+        self.dots = yes;
+        self
+    }
+
     pub fn build(&self) -> Tokenizer {
         let TokenizerBuilder {
             unicode,
             leading_digits,
             ignore_hex,
+            escapes,
+            dashes,
+            dots,
         } = self.clone();
         Tokenizer {
             unicode,
             leading_digits,
             ignore_hex,
+            options: ParseOptions {
+                escapes,
+                dashes,
+                dots,
+            },
+            segmenter: None,
         }
     }
 }
@@ -49,6 +88,9 @@ impl Default for TokenizerBuilder {
             unicode: true,
             leading_digits: false,
             ignore_hex: true,
+            escapes: false,
+            dashes: false,
+            dots: false,
         }
     }
 }
@@ -59,6 +101,8 @@ pub struct Tokenizer {
     unicode: bool,
     leading_digits: bool,
     ignore_hex: bool,
+    options: ParseOptions,
+    segmenter: Option<std::sync::Arc<Segmenter>>,
 }
 
 impl Tokenizer {
@@ -66,11 +110,43 @@ impl Tokenizer {
         TokenizerBuilder::default().build()
     }
 
+    /// Specify how to split run-together lowercase Words, like `copysign`.
+    pub fn segmenter(&mut self, segmenter: Option<std::sync::Arc<Segmenter>>) -> &mut Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Split an Identifier into Words, including run-together lowercase Words when there is a
+    /// `segmenter`.
+    pub fn split<'i, 's: 'i>(
+        &'s self,
+        ident: Identifier<'i>,
+    ) -> impl Iterator<Item = Word<'i>> + 'i {
+        match self.segmenter.as_deref() {
+            Some(segmenter) => {
+                itertools::Either::Left(ident.split().flat_map(move |w| segmenter.segment(w)))
+            }
+            None => itertools::Either::Right(ident.split()),
+        }
+    }
+
+    /// Split a Word into the run-together lowercase Words it is made of, when there is a
+    /// `segmenter`.
+    pub fn segment<'w>(&self, word: Word<'w>) -> impl Iterator<Item = Word<'w>> {
+        match self.segmenter.as_deref() {
+            Some(segmenter) => itertools::Either::Left(segmenter.segment(word)),
+            None => itertools::Either::Right(Some(word).into_iter()),
+        }
+    }
+
     pub fn parse_str<'c>(&'c self, content: &'c str) -> impl Iterator<Item = Identifier<'c>> {
         let iter = if self.unicode {
-            itertools::Either::Left(unicode_parser::iter_literals(content))
+            itertools::Either::Left(unicode_parser::iter_literals(content, self.options))
         } else {
-            itertools::Either::Right(ascii_parser::iter_literals(content.as_bytes()))
+            itertools::Either::Right(ascii_parser::iter_literals(
+                content.as_bytes(),
+                self.options,
+            ))
         };
         iter.filter_map(move |identifier| {
             let offset = offset(content.as_bytes(), identifier.as_bytes());
@@ -80,10 +156,12 @@ impl Tokenizer {
 
     pub fn parse_bytes<'c>(&'c self, content: &'c [u8]) -> impl Iterator<Item = Identifier<'c>> {
         let iter = if self.unicode {
-            let iter = Utf8Chunks::new(content).flat_map(move |c| unicode_parser::iter_literals(c));
+            let options = self.options;
+            let iter = Utf8Chunks::new(content)
+                .flat_map(move |c| unicode_parser::iter_literals(c, options));
             itertools::Either::Left(iter)
         } else {
-            itertools::Either::Right(ascii_parser::iter_literals(content))
+            itertools::Either::Right(ascii_parser::iter_literals(content, self.options))
         };
         iter.filter_map(move |identifier| {
             let offset = offset(content, identifier.as_bytes());
@@ -133,6 +211,11 @@ impl<'s> Utf8Chunks<'s> {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+use simdutf8::compat::from_utf8;
+#[cfg(target_arch = "wasm32")]
+use std::str::from_utf8;
+
 impl<'s> Iterator for Utf8Chunks<'s> {
     type Item = &'s str;
 
@@ -141,7 +224,7 @@ impl<'s> Iterator for Utf8Chunks<'s> {
             return None;
         }
 
-        match simdutf8::compat::from_utf8(self.source) {
+        match from_utf8(self.source) {
             Ok(valid) => {
                 self.source = b"";
                 Some(valid)
@@ -163,7 +246,10 @@ impl<'s> Iterator for Utf8Chunks<'s> {
 }
 
 fn is_number(ident: &[u8]) -> bool {
-    ident.iter().all(|b| is_digit(*b) || is_digit_sep(*b))
+    // `.` and `-` only show up when joining Identifiers, like in version numbers
+    ident
+        .iter()
+        .all(|b| is_digit(*b) || is_digit_sep(*b) || *b == b'.' || *b == b'-')
 }
 
 fn is_hex(ident: &[u8]) -> bool {
@@ -195,13 +281,138 @@ fn is_hex_digit(chr: u8) -> bool {
     chr.is_ascii_hexdigit()
 }
 
+/// How `unicode_parser` and `ascii_parser` find Identifiers.
+#[derive(Debug, Clone, Copy)]
+struct ParseOptions {
+    escapes: bool,
+    dashes: bool,
+    dots: bool,
+}
+
+impl ParseOptions {
+    /// Whether `chr` is allowed between the other characters of an Identifier.
+    #[inline]
+    fn is_joiner(self, chr: u8) -> bool {
+        (self.dashes && chr == b'-') || (self.dots && chr == b'.')
+    }
+}
+
+#[inline]
+fn is_escape_start(chr: u8) -> bool {
+    matches!(chr, b'\\' | b'%' | b'{' | b'&')
+}
+
+/// The length of the escape sequence, format specifier, or character reference at the start of
+/// `input`, if any.
+///
+/// Only ever ASCII, so the length is a `char` boundary.
+fn escape_len(input: &[u8]) -> Option<usize> {
+    let count_while = |start: usize, max: usize, pred: fn(u8) -> bool| {
+        input
+            .iter()
+            .skip(start)
+            .take(max)
+            .take_while(|b| pred(**b))
+            .count()
+    };
+    match *input.first()? {
+        b'\\' => {
+            let len = match *input.get(1)? {
+                // `\x7f`
+                b'x' => match count_while(2, 2, is_hex_digit) {
+                    0 => return None,
+                    digits => 2 + digits,
+                },
+                // Rust's `\u{7f}`
+                b'u' if input.get(2) == Some(&b'{') => {
+                    let digits = count_while(3, 6, is_hex_digit);
+                    if input.get(3 + digits) != Some(&b'}') {
+                        return None;
+                    }
+                    4 + digits
+                }
+                // `\u007f`, requiring all digits so `C:\users` is left alone
+                b'u' if count_while(2, 4, is_hex_digit) == 4 => 6,
+                b'U' if count_while(2, 8, is_hex_digit) == 8 => 10,
+                // Python's `\N{DELETE}`
+                b'N' if input.get(2) == Some(&b'{') => {
+                    3 + input[3..].iter().position(|b| *b == b'}')? + 1
+                }
+                // `\177`
+                b'0'..=b'7' => 1 + count_while(1, 3, |b| (b'0'..=b'7').contains(&b)),
+                b'n' | b't' | b'r' | b'a' | b'b' | b'f' | b'v' | b'e' | b'\\' => 2,
+                _ => return None,
+            };
+            Some(len)
+        }
+        b'%' => {
+            if input.get(1) == Some(&b'%') {
+                return Some(2);
+            }
+            // Python's `%(name)s`
+            let mut len = 1;
+            if input.get(1) == Some(&b'(') {
+                len += input[1..].iter().position(|b| *b == b')')? + 1;
+            }
+            len += count_while(len, usize::MAX, |b| {
+                // Not ` `, so `100% sure` isn't `% s`
+                matches!(b, b'-' | b'+' | b'#' | b'0' | b'\'')
+            });
+            len += count_while(len, usize::MAX, |b| b.is_ascii_digit() || b == b'*');
+            if input.get(len) == Some(&b'.') {
+                len += 1 + count_while(len + 1, usize::MAX, |b| b.is_ascii_digit() || b == b'*');
+            }
+            len += count_while(len, 2, |b| {
+                matches!(b, b'h' | b'l' | b'L' | b'q' | b'j' | b'z' | b'Z' | b't')
+            });
+            // The conversion, like `d` in `%5d`
+            let conversion = input.get(len)?;
+            if b"diouxXeEfFgGaAcspn@".contains(conversion) {
+                Some(len + 1)
+            } else {
+                None
+            }
+        }
+        b'{' => {
+            // Only fields with a spec, like `{0:>8}`, so `{name}` is still checked and JSON-like
+            // `{"key": value}` isn't mistaken for one
+            let close = input.iter().take(64).position(|b| *b == b'}')?;
+            let spec = &input[..close];
+            let is_format = spec.contains(&b':')
+                && !spec[1..]
+                    .iter()
+                    .any(|b| b.is_ascii_whitespace() || matches!(b, b'{' | b'"' | b'\''));
+            if is_format {
+                Some(close + 1)
+            } else {
+                None
+            }
+        }
+        b'&' => {
+            let name = 1 + usize::from(input.get(1) == Some(&b'#'));
+            let len = count_while(name, 32, |b| b.is_ascii_alphanumeric());
+            if 0 < len && input.get(name + len) == Some(&b';') {
+                Some(name + len + 1)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
 mod unicode_parser {
     use nom::bytes::complete::*;
     use nom::sequence::*;
     use nom::IResult;
 
-    pub(crate) fn iter_literals(mut input: &str) -> impl Iterator<Item = &str> {
-        std::iter::from_fn(move || match next_literal(input) {
+    use super::ParseOptions;
+
+    pub(crate) fn iter_literals(
+        mut input: &str,
+        options: ParseOptions,
+    ) -> impl Iterator<Item = &str> {
+        std::iter::from_fn(move || match next_literal(input, options) {
             Ok((i, o)) => {
                 input = i;
                 debug_assert_ne!(o, "");
@@ -211,20 +422,57 @@ mod unicode_parser {
         })
     }
 
-    fn next_literal(input: &str) -> IResult<&str, &str> {
-        preceded(literal_sep, identifier)(input)
+    fn next_literal(input: &str, options: ParseOptions) -> IResult<&str, &str> {
+        let identifier = |i| identifier(i, options);
+        if options.escapes {
+            preceded(escaped_literal_sep, identifier)(input)
+        } else {
+            preceded(literal_sep, identifier)(input)
+        }
     }
 
     fn literal_sep(input: &str) -> IResult<&str, &str> {
         take_till(unicode_xid::UnicodeXID::is_xid_continue)(input)
     }
 
-    fn identifier(input: &str) -> IResult<&str, &str> {
+    fn escaped_literal_sep(input: &str) -> IResult<&str, &str> {
+        let mut pos = 0;
+        loop {
+            let (rest, _) = take_till(|c: char| {
+                unicode_xid::UnicodeXID::is_xid_continue(c)
+                    || (c.is_ascii() && super::is_escape_start(c as u8))
+            })(&input[pos..])?;
+            pos = input.len() - rest.len();
+            match rest.as_bytes().first() {
+                Some(c) if super::is_escape_start(*c) => {
+                    // Escapes are ASCII, so this stays on a `char` boundary
+                    pos += super::escape_len(rest.as_bytes()).unwrap_or(1);
+                }
+                _ => return Ok((rest, &input[..pos])),
+            }
+        }
+    }
+
+    fn identifier(input: &str, options: ParseOptions) -> IResult<&str, &str> {
         // Generally a language would be `{XID_Start}{XID_Continue}*` but going with only
         // `{XID_Continue}+` because XID_Continue is a superset of XID_Start and rather catch odd
         // or unexpected cases than strip off start characters to a word since we aren't doing a
         // proper word boundary parse
-        take_while1(unicode_xid::UnicodeXID::is_xid_continue)(input)
+        let (mut rest, _) = take_while1(unicode_xid::UnicodeXID::is_xid_continue)(input)?;
+        loop {
+            let mut chars = rest.chars();
+            let joiner = chars.next().filter(char::is_ascii);
+            let joins = joiner.map_or(false, |c| options.is_joiner(c as u8))
+                && chars
+                    .next()
+                    .map_or(false, unicode_xid::UnicodeXID::is_xid_continue);
+            if !joins {
+                break;
+            }
+            let (r, _) = take_while1(unicode_xid::UnicodeXID::is_xid_continue)(&rest[1..])?;
+            rest = r;
+        }
+        Ok((rest, &input[..input.len() - rest.len()]))
     }
 }
 
@@ -233,8 +481,13 @@ mod ascii_parser {
     use nom::sequence::*;
     use nom::IResult;
 
-    pub(crate) fn iter_literals(mut input: &[u8]) -> impl Iterator<Item = &str> {
-        std::iter::from_fn(move || match next_literal(input) {
+    use super::ParseOptions;
+
+    pub(crate) fn iter_literals(
+        mut input: &[u8],
+        options: ParseOptions,
+    ) -> impl Iterator<Item = &str> {
+        std::iter::from_fn(move || match next_literal(input, options) {
             Ok((i, o)) => {
                 input = i;
                 debug_assert_ne!(o, b"");
@@ -247,20 +500,48 @@ mod ascii_parser {
         })
     }
 
-    fn next_literal(input: &[u8]) -> IResult<&[u8], &[u8]> {
-        preceded(literal_sep, identifier)(input)
+    fn next_literal(input: &[u8], options: ParseOptions) -> IResult<&[u8], &[u8]> {
+        let identifier = |i| identifier(i, options);
+        if options.escapes {
+            preceded(escaped_literal_sep, identifier)(input)
+        } else {
+            preceded(literal_sep, identifier)(input)
+        }
     }
 
     fn literal_sep(input: &[u8]) -> IResult<&[u8], &[u8]> {
         take_till(is_continue)(input)
     }
 
-    fn identifier(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    fn escaped_literal_sep(input: &[u8]) -> IResult<&[u8], &[u8]> {
+        let mut pos = 0;
+        loop {
+            let (rest, _) =
+                take_till(|c| is_continue(c) || super::is_escape_start(c))(&input[pos..])?;
+            pos = input.len() - rest.len();
+            match rest.first() {
+                Some(c) if super::is_escape_start(*c) => {
+                    pos += super::escape_len(rest).unwrap_or(1);
+                }
+                _ => return Ok((rest, &input[..pos])),
+            }
+        }
+    }
+
+    fn identifier(input: &[u8], options: ParseOptions) -> IResult<&[u8], &[u8]> {
         // Generally a language would be `{XID_Start}{XID_Continue}*` but going with only
         // `{XID_Continue}+` because XID_Continue is a superset of XID_Start and rather catch odd
         // or unexpected cases than strip off start characters to a word since we aren't doing a
         // proper word boundary parse
-        take_while1(is_continue)(input)
+        let (mut rest, _) = take_while1(is_continue)(input)?;
+        while let [joiner, next, ..] = rest {
+            if !(options.is_joiner(*joiner) && is_continue(*next)) {
+                break;
+            }
+            let (r, _) = take_while1(is_continue)(&rest[1..])?;
+            rest = r;
+        }
+        Ok((rest, &input[..input.len() - rest.len()]))
     }
 
     fn is_continue(c: u8) -> bool {
@@ -457,6 +738,144 @@ impl<'s> Iterator for SplitIdent<'s> {
     }
 }
 
+/// Split run-together lowercase Words, like `copysign` or `recieveddata`, into the Words they are
+/// most likely made of according to a word-frequency list.
+///
+/// Words missing from the list are allowed, at a cost that grows with their length, so a typo
+/// between known Words still gets split out.  A split is only made when the most likely
+/// segmentation is at least `min_confidence` of the probability of all of them, so ambiguous
+/// runs are left alone.
+#[derive(Debug, Clone)]
+pub struct Segmenter {
+    counts: std::collections::HashMap<String, u64>,
+    total: u64,
+    min_confidence: f64,
+}
+
+impl Segmenter {
+    /// Longest Word to segment, bounding the quadratic search.
+    const MAX_LEN: usize = 64;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add `count` occurrences of `word` to the word-frequency list.
+    pub fn insert(&mut self, word: &str, count: u64) -> &mut Self {
+        *self.counts.entry(word.to_lowercase()).or_insert(0) += count;
+        self.total += count;
+        self
+    }
+
+    /// Specify how likely, from `0.0` to `1.0`, a segmentation must be before a Word is split.
+    pub fn min_confidence(&mut self, confidence: f64) -> &mut Self {
+        self.min_confidence = confidence;
+        self
+    }
+
+    /// Split a lowercase Word into the Words it is made of.
+    ///
+    /// Yields `word` itself when it isn't lowercase, is a known word, or can't be confidently
+    /// split.
+    pub fn segment<'w>(&self, word: Word<'w>) -> impl Iterator<Item = Word<'w>> {
+        match self.segment_offsets(word) {
+            Some(bounds) => {
+                let words = bounds
+                    .windows(2)
+                    .map(|w| {
+                        Word::new_unchecked(
+                            &word.token[w[0]..w[1]],
+                            Case::Lower,
+                            word.offset + w[0],
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                itertools::Either::Left(words.into_iter())
+            }
+            None => itertools::Either::Right(Some(word).into_iter()),
+        }
+    }
+
+    /// The byte offsets of the Word boundaries within `word`, including `0` and its length.
+    fn segment_offsets(&self, word: Word<'_>) -> Option<Vec<usize>> {
+        let token = word.token;
+        if word.case != Case::Lower
+            || self.total == 0
+            || Self::MAX_LEN < token.len()
+            || self.counts.contains_key(token)
+        {
+            return None;
+        }
+
+        let bounds: Vec<_> = token
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(token.len()))
+            .collect();
+        // For each prefix, the log-probability of its best segmentation with where that
+        // segmentation's last Word starts, and the log of the sum of all segmentations'
+        // probabilities
+        let mut best = vec![(0.0, 0); bounds.len()];
+        let mut all = vec![0.0; bounds.len()];
+        for end in 1..bounds.len() {
+            let mut end_best = (f64::NEG_INFINITY, 0);
+            let mut end_all = f64::NEG_INFINITY;
+            for start in 0..end {
+                let score = self.log_prob(&token[bounds[start]..bounds[end]], end - start);
+                let candidate = best[start].0 + score;
+                if end_best.0 < candidate {
+                    end_best = (candidate, start);
+                }
+                end_all = log_add(end_all, all[start] + score);
+            }
+            best[end] = end_best;
+            all[end] = end_all;
+        }
+
+        let last = bounds.len() - 1;
+        let confidence = (best[last].0 - all[last]).exp();
+        let mut splits = vec![bounds[last]];
+        let mut end = last;
+        while end != 0 {
+            end = best[end].1;
+            splits.push(bounds[end]);
+        }
+        if splits.len() <= 2 || confidence < self.min_confidence {
+            return None;
+        }
+        splits.reverse();
+        Some(splits)
+    }
+
+    fn log_prob(&self, word: &str, chars: usize) -> f64 {
+        let total = self.total as f64;
+        match self.counts.get(word) {
+            Some(count) => (*count as f64 / total).ln(),
+            // Unknown words become less likely by an order of magnitude per character
+            None => (10.0 / total).ln() - chars as f64 * std::f64::consts::LN_10,
+        }
+    }
+}
+
+impl Default for Segmenter {
+    fn default() -> Self {
+        Self {
+            counts: Default::default(),
+            total: 0,
+            min_confidence: 0.75,
+        }
+    }
+}
+
+fn log_add(a: f64, b: f64) -> f64 {
+    let (max, min) = if a < b { (b, a) } else { (a, b) };
+    if max == f64::NEG_INFINITY {
+        max
+    } else {
+        max + (min - max).exp().ln_1p()
+    }
+}
+
 /// Format of the term.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Case {
@@ -674,6 +1093,121 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn tokenize_escapes_enabled() {
+        let parser = TokenizerBuilder::new().escapes(true).build();
+
+        let input = r"\nparameter\tthe %sfoo &amp;bar {0:>8}baz \\nqux C:\users \x7fquux";
+        let expected: Vec<Identifier> = vec![
+            Identifier::new_unchecked("parameter", Case::None, 2),
+            Identifier::new_unchecked("the", Case::None, 13),
+            Identifier::new_unchecked("foo", Case::None, 19),
+            Identifier::new_unchecked("bar", Case::None, 28),
+            Identifier::new_unchecked("baz", Case::None, 38),
+            Identifier::new_unchecked("nqux", Case::None, 44),
+            Identifier::new_unchecked("C", Case::None, 49),
+            Identifier::new_unchecked("users", Case::None, 52),
+            Identifier::new_unchecked("quux", Case::None, 62),
+        ];
+        let actual: Vec<_> = parser.parse_bytes(input.as_bytes()).collect();
+        assert_eq!(expected, actual);
+        let actual: Vec<_> = parser.parse_str(input).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn tokenize_escapes_disabled() {
+        let parser = TokenizerBuilder::new().escapes(false).build();
+
+        let input = r"\nparameter %sfoo &amp;bar";
+        let expected: Vec<Identifier> = vec![
+            Identifier::new_unchecked("nparameter", Case::None, 1),
+            Identifier::new_unchecked("sfoo", Case::None, 13),
+            Identifier::new_unchecked("amp", Case::None, 19),
+            Identifier::new_unchecked("bar", Case::None, 23),
+        ];
+        let actual: Vec<_> = parser.parse_bytes(input.as_bytes()).collect();
+        assert_eq!(expected, actual);
+        let actual: Vec<_> = parser.parse_str(input).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn tokenize_dashes_enabled() {
+        let parser = TokenizerBuilder::new().dashes(true).build();
+
+        let input = "--no-ignore-vcs serde-json trailing- a.b 1-2";
+        let expected: Vec<Identifier> = vec![
+            Identifier::new_unchecked("no-ignore-vcs", Case::None, 2),
+            Identifier::new_unchecked("serde-json", Case::None, 16),
+            Identifier::new_unchecked("trailing", Case::None, 27),
+            Identifier::new_unchecked("a", Case::None, 37),
+            Identifier::new_unchecked("b", Case::None, 39),
+        ];
+        let actual: Vec<_> = parser.parse_bytes(input.as_bytes()).collect();
+        assert_eq!(expected, actual);
+        let actual: Vec<_> = parser.parse_str(input).collect();
+        assert_eq!(expected, actual);
+
+        let words: Vec<_> = expected[0].split().collect();
+        let expected_words = vec![
+            Word::new_unchecked("no", Case::Lower, 2),
+            Word::new_unchecked("ignore", Case::Lower, 5),
+            Word::new_unchecked("vcs", Case::Lower, 12),
+        ];
+        assert_eq!(expected_words, words);
+    }
+
+    #[test]
+    fn tokenize_dots_enabled() {
+        let parser = TokenizerBuilder::new()
+            .dots(true)
+            .leading_digits(true)
+            .build();
+
+        let input = "os.path.join end. v1.2.3 1.2.3";
+        let expected: Vec<Identifier> = vec![
+            Identifier::new_unchecked("os.path.join", Case::None, 0),
+            Identifier::new_unchecked("end", Case::None, 13),
+            Identifier::new_unchecked("v1.2.3", Case::None, 18),
+        ];
+        let actual: Vec<_> = parser.parse_bytes(input.as_bytes()).collect();
+        assert_eq!(expected, actual);
+        let actual: Vec<_> = parser.parse_str(input).collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn escape_lengths() {
+        let cases: &[(&str, Option<usize>)] = &[
+            (r"\n", Some(2)),
+            (r"\\", Some(2)),
+            (r"\x7f", Some(4)),
+            (r"\xz", None),
+            (r"\u{1F600}", Some(9)),
+            (r"\u00e9", Some(6)),
+            (r"\users", None),
+            (r"\U0001F600", Some(10)),
+            (r"\N{DELETE}", Some(10)),
+            (r"\017", Some(4)),
+            (r"\q", None),
+            ("%s", Some(2)),
+            ("%%", Some(2)),
+            ("%-08.3lf", Some(8)),
+            ("%(name)s", Some(8)),
+            ("% sure", None),
+            ("&amp;", Some(5)),
+            ("&#233;", Some(6)),
+            ("& amp;", None),
+            ("{:x}", Some(4)),
+            ("{name}", None),
+            (r#"{"key":1}"#, None),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(escape_len(input.as_bytes()), *expected, "{}", input);
+        }
+    }
+
     #[test]
     fn split_ident() {
         let cases = [
@@ -736,4 +1270,75 @@ mod test {
             assert_eq!(&result, expected);
         }
     }
+
+    fn segmenter() -> Segmenter {
+        let mut segmenter = Segmenter::new();
+        for (word, count) in [
+            ("copy", 800),
+            ("sign", 500),
+            ("max", 600),
+            ("length", 700),
+            ("data", 1000),
+            ("received", 300),
+            ("the", 5000),
+            ("therapist", 10),
+            ("rapist", 5),
+        ]
+        .iter()
+        {
+            segmenter.insert(word, *count);
+        }
+        segmenter
+    }
+
+    #[test]
+    fn segment_words() {
+        let segmenter = segmenter();
+        let cases = [
+            (
+                "copysign",
+                &[("copy", 10usize), ("sign", 14)] as &[(&str, usize)],
+            ),
+            ("maxlength", &[("max", 10), ("length", 13)]),
+            ("recieveddata", &[("recieved", 10), ("data", 18)]),
+            ("received", &[("received", 10)]),
+            ("therapist", &[("therapist", 10)]),
+            ("unrelated", &[("unrelated", 10)]),
+        ];
+        for (input, expected) in cases.iter() {
+            let word = Word::new_unchecked(input, Case::Lower, 10);
+            let result: Vec<_> = segmenter
+                .segment(word)
+                .map(|w| (w.token, w.offset))
+                .collect();
+            assert_eq!(&result, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn segment_skips_cased_words() {
+        let segmenter = segmenter();
+        let word = Word::new_unchecked("COPYSIGN", Case::Upper, 0);
+        let result: Vec<_> = segmenter.segment(word).collect();
+        assert_eq!(result, vec![word]);
+    }
+
+    #[test]
+    fn segment_needs_confidence() {
+        let mut segmenter = segmenter();
+        let word = Word::new_unchecked("copysign", Case::Lower, 0);
+        segmenter.min_confidence(1.0);
+        assert_eq!(segmenter.segment(word).count(), 1);
+    }
+
+    #[test]
+    fn tokenizer_split_segments() {
+        let mut parser = Tokenizer::new();
+        let ident = Identifier::new_unchecked("get_copysign", Case::None, 0);
+        assert_eq!(parser.split(ident).count(), 2);
+
+        parser.segmenter(Some(std::sync::Arc::new(segmenter())));
+        let words: Vec<_> = parser.split(ident).map(|w| w.token).collect();
+        assert_eq!(words, vec!["get", "copy", "sign"]);
+    }
 }
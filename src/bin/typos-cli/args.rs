@@ -47,6 +47,7 @@ impl Default for Format {
         setting = structopt::clap::AppSettings::DontCollapseArgsInUsage
     )]
 #[structopt(group = structopt::clap::ArgGroup::with_name("mode").multiple(false))]
+#[structopt(group = structopt::clap::ArgGroup::with_name("git").multiple(false))]
 pub(crate) struct Args {
     #[structopt(parse(from_os_str), default_value = ".")]
     /// Paths to check with `-` for stdin
@@ -60,6 +61,41 @@ pub(crate) struct Args {
     /// Ignore implicit configuration files.
     pub(crate) isolated: bool,
 
+    #[structopt(long, group = "git")]
+    /// Only check files tracked by git
+    pub(crate) git_tracked: bool,
+
+    #[structopt(long, group = "git")]
+    /// Only check files with changes staged in git
+    pub(crate) git_staged: bool,
+
+    #[structopt(long, group = "git", value_name = "REF")]
+    /// Only check files changed since REF, according to git
+    pub(crate) from_git_diff: Option<String>,
+
+    #[structopt(
+        long,
+        requires = "git",
+        conflicts_with_all(&["diff", "write-changes", "files", "identifiers", "words"])
+    )]
+    /// Check the content staged in git, rather than the working tree
+    pub(crate) staged_content: bool,
+
+    #[structopt(long, group = "mode", value_name = "RANGE")]
+    /// Check the messages of the commits in RANGE, like `origin/main..HEAD`
+    pub(crate) commits: Option<String>,
+
+    #[structopt(long, group = "mode", parse(from_os_str), value_name = "FILE")]
+    /// Check a commit message file, as passed to a `commit-msg` hook
+    pub(crate) commit_msg: Option<std::path::PathBuf>,
+
+    #[structopt(
+        long,
+        conflicts_with_all(&["diff", "write-changes", "files", "identifiers", "words", "dump-config", "type-list", "clear-cache"])
+    )]
+    /// Check the name of the current git branch
+    pub(crate) branch_name: bool,
+
     #[structopt(long, group = "mode")]
     /// Print a diff of what would change
     pub(crate) diff: bool,
@@ -68,6 +104,14 @@ pub(crate) struct Args {
     /// Write fixes out
     pub(crate) write_changes: bool,
 
+    #[structopt(long, requires = "write-changes")]
+    /// When renaming files and directories, update references to them in the checked files
+    pub(crate) update_references: bool,
+
+    #[structopt(long, requires_all(&["git-staged", "write-changes"]))]
+    /// Stage the fixes, for use in a pre-commit hook.  Files with unstaged changes aren't fixed.
+    pub(crate) restage: bool,
+
     #[structopt(long, group = "mode")]
     /// Debug: Print each file that would be spellchecked.
     pub(crate) files: bool,
@@ -88,6 +132,14 @@ pub(crate) struct Args {
     /// Show all supported file types.
     pub(crate) type_list: bool,
 
+    #[structopt(long, group = "mode")]
+    /// Delete findings cached from prior runs.
+    pub(crate) clear_cache: bool,
+
+    #[structopt(long)]
+    /// Reuse the findings cached from prior runs for unchanged files, caching any new ones.
+    pub(crate) cache: bool,
+
     #[structopt(
         long,
         possible_values(&Format::variants()),
@@ -96,6 +148,10 @@ pub(crate) struct Args {
     )]
     pub(crate) format: Format,
 
+    #[structopt(long)]
+    /// Fail on warnings, not just errors.
+    pub(crate) deny_warnings: bool,
+
     #[structopt(short = "j", long = "threads", default_value = "0")]
     /// The approximate number of threads to use.
     pub(crate) threads: usize,
@@ -110,6 +166,20 @@ pub(crate) struct Args {
     pub(crate) verbose: clap_verbosity_flag::Verbosity,
 }
 
+impl Args {
+    pub(crate) fn git_selection(&self) -> Option<typos_cli::git::Selection> {
+        if self.git_tracked {
+            Some(typos_cli::git::Selection::Tracked)
+        } else if self.git_staged {
+            Some(typos_cli::git::Selection::Staged)
+        } else {
+            self.from_git_diff
+                .clone()
+                .map(typos_cli::git::Selection::ChangedSince)
+        }
+    }
+}
+
 #[derive(Debug, Clone, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 pub(crate) struct FileArgs {
@@ -150,6 +220,15 @@ impl FileArgs {
             binary: self.binary(),
             check_filename: self.check_filename(),
             check_file: self.check_file(),
+            fix_symlinks: None,
+            preserve_mtime: None,
+            legacy_encodings: None,
+            archives: None,
+            documents: None,
+            markup: None,
+            notebook_outputs: None,
+            repeated_words: None,
+            severity: None,
             tokenizer: Some(config::TokenizerConfig {
                 unicode: self.unicode(),
                 ..Default::default()
@@ -13,6 +13,7 @@ mod report;
 use proc_exit::WithCodeResultExt;
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
     human_panic::setup_panic!();
     let result = run();
     proc_exit::exit(result);
@@ -56,6 +57,10 @@ fn run() -> proc_exit::ExitResult {
         run_dump_config(&args, output_path)
     } else if args.type_list {
         run_type_list(&args)
+    } else if args.clear_cache {
+        run_clear_cache()
+    } else if args.commits.is_some() || args.commit_msg.is_some() || args.branch_name {
+        run_git_messages(&args, stdout_palette, stderr_palette)
     } else {
         run_checks(&args, stdout_palette, stderr_palette)
     }
@@ -148,6 +153,82 @@ fn run_type_list(args: &args::Args) -> proc_exit::ExitResult {
     Ok(())
 }
 
+fn run_clear_cache() -> proc_exit::ExitResult {
+    if let Some(dir) = typos_cli::cache::Cache::user_dir() {
+        let cache = typos_cli::cache::Cache::new(dir);
+        log::debug!("Clearing {}", cache.dir().display());
+        cache.clear()?;
+    }
+
+    Ok(())
+}
+
+fn run_git_messages(
+    args: &args::Args,
+    stdout_palette: report::Palette,
+    stderr_palette: report::Palette,
+) -> proc_exit::ExitResult {
+    let global_cwd = std::env::current_dir()?.canonicalize()?;
+
+    let storage = typos_cli::policy::ConfigStorage::new();
+    let mut engine = typos_cli::policy::ConfigEngine::new(&storage);
+    engine.set_isolated(args.isolated);
+
+    let mut overrides = typos_cli::config::Config::default();
+    if let Some(path) = args.custom_config.as_ref() {
+        let custom =
+            typos_cli::config::Config::from_file(path).with_code(proc_exit::Code::CONFIG_ERR)?;
+        overrides.update(&custom);
+    }
+    overrides.update(&args.config.to_config());
+    engine.set_overrides(overrides);
+
+    let repo = typos_cli::git::Repo::discover(&global_cwd);
+    let repo = if args.commit_msg.is_some() && !args.branch_name {
+        // Hooks run inside a repo but a message file can be checked on its own
+        repo.ok()
+    } else {
+        Some(repo.with_code(proc_exit::Code::USAGE_ERR)?)
+    };
+    let root = repo
+        .as_ref()
+        .map(|r| r.root().to_owned())
+        .unwrap_or(global_cwd);
+    engine
+        .init_dir(&root)
+        .with_code(proc_exit::Code::CONFIG_ERR)?;
+    // Messages are checked as if they were the file git edits them in
+    let policy = engine.policy(&root.join("COMMIT_EDITMSG"));
+
+    let output_reporter = args.format.reporter(stdout_palette, stderr_palette);
+    let mut status_reporter = report::MessageStatus::new(output_reporter.as_ref());
+    status_reporter.deny_warnings(args.deny_warnings);
+    let reporter: &dyn typos_cli::report::Report = &status_reporter;
+
+    if let Some(path) = args.commit_msg.as_ref() {
+        typos_cli::git::check_message_file(path, &policy, reporter)?;
+    }
+    if let (Some(range), Some(repo)) = (args.commits.as_ref(), repo.as_ref()) {
+        let commits = repo.commits(range).with_code(proc_exit::Code::USAGE_ERR)?;
+        for commit in commits.iter() {
+            typos_cli::git::check_commit(commit, &policy, reporter)?;
+        }
+    }
+    if let (true, Some(repo)) = (args.branch_name, repo.as_ref()) {
+        if let Some(branch) = repo.branch().with_code(proc_exit::Code::USAGE_ERR)? {
+            typos_cli::git::check_branch(&branch, &policy, reporter)?;
+        }
+    }
+
+    if status_reporter.errors_found() {
+        proc_exit::Code::FAILURE.ok()
+    } else if status_reporter.typos_found() {
+        proc_exit::Code::UNKNOWN.ok()
+    } else {
+        proc_exit::Code::SUCCESS.ok()
+    }
+}
+
 fn run_checks(
     args: &args::Args,
     stdout_palette: report::Palette,
@@ -168,8 +249,18 @@ fn run_checks(
     overrides.update(&args.config.to_config());
     engine.set_overrides(overrides);
 
+    let cache = if args.cache {
+        typos_cli::cache::Cache::user_dir().map(typos_cli::cache::Cache::new)
+    } else {
+        None
+    };
+
+    let mut fix_typos = typos_cli::file::FixTypos::new();
+    fix_typos.update_references(args.update_references);
+
     let mut typos_found = false;
     let mut errors_found = false;
+    let mut files_modified = false;
     for path in args.path.iter() {
         let cwd = if path == std::path::Path::new("-") {
             global_cwd.as_path()
@@ -188,32 +279,14 @@ fn run_checks(
         let threads = if path.is_file() { 1 } else { args.threads };
         let single_threaded = threads == 1;
 
-        let mut walk = ignore::WalkBuilder::new(path);
-        walk.threads(args.threads)
-            .hidden(walk_policy.ignore_hidden())
-            .ignore(walk_policy.ignore_dot())
-            .git_global(walk_policy.ignore_global())
-            .git_ignore(walk_policy.ignore_vcs())
-            .git_exclude(walk_policy.ignore_vcs())
-            .parents(walk_policy.ignore_parent());
-        if !walk_policy.extend_exclude.is_empty() {
-            let mut overrides = ignore::overrides::OverrideBuilder::new(".");
-            for pattern in walk_policy.extend_exclude.iter() {
-                overrides
-                    .add(&format!("!{}", pattern))
-                    .with_code(proc_exit::Code::CONFIG_ERR)?;
-            }
-            let overrides = overrides.build().with_code(proc_exit::Code::CONFIG_ERR)?;
-            walk.overrides(overrides);
-        }
-
         // HACK: Diff doesn't handle mixing content
         let output_reporter = if args.diff {
             Box::new(crate::report::PrintSilent)
         } else {
             args.format.reporter(stdout_palette, stderr_palette)
         };
-        let status_reporter = report::MessageStatus::new(output_reporter.as_ref());
+        let mut status_reporter = report::MessageStatus::new(output_reporter.as_ref());
+        status_reporter.deny_warnings(args.deny_warnings);
         let reporter: &dyn typos_cli::report::Report = &status_reporter;
 
         let selected_checks: &dyn typos_cli::file::FileChecker = if args.files {
@@ -223,29 +296,81 @@ fn run_checks(
         } else if args.words {
             &typos_cli::file::Words
         } else if args.write_changes {
-            &typos_cli::file::FixTypos
+            &fix_typos
         } else if args.diff {
             &typos_cli::file::DiffTypos
         } else {
             &typos_cli::file::Typos
         };
+        let git_selection = args.git_selection();
+        let repo = match git_selection {
+            Some(_) => {
+                Some(typos_cli::git::Repo::discover(&cwd).with_code(proc_exit::Code::USAGE_ERR)?)
+            }
+            None => None,
+        };
+        let index_typos;
+        let fix_staged_typos;
+        let selected_checks: &dyn typos_cli::file::FileChecker = match repo.as_ref() {
+            Some(repo) if args.staged_content => {
+                index_typos = typos_cli::git::IndexTypos::new(repo.index()?);
+                &index_typos
+            }
+            Some(repo) if args.restage => {
+                let unstaged = repo.unstaged().with_code(proc_exit::Code::FAILURE)?;
+                fix_staged_typos = typos_cli::git::FixStagedTypos::new(&fix_typos, unstaged);
+                &fix_staged_typos
+            }
+            _ => selected_checks,
+        };
+        let cached;
+        let selected_checks = match cache.as_ref() {
+            Some(cache) if is_cacheable(args) => {
+                cached = typos_cli::cache::Cached {
+                    cache,
+                    inner: selected_checks,
+                };
+                &cached
+            }
+            _ => selected_checks,
+        };
 
-        if single_threaded {
-            typos_cli::file::walk_path(walk.build(), selected_checks, &engine, reporter)
+        if let (Some(selection), Some(repo)) = (git_selection.as_ref(), repo.as_ref()) {
+            let files = repo
+                .files(selection, path, walk_policy)
+                .with_code(proc_exit::Code::USAGE_ERR)?;
+            typos_cli::file::check_files(&files, selected_checks, &engine, reporter)?;
         } else {
-            typos_cli::file::walk_path_parallel(
-                walk.build_parallel(),
-                selected_checks,
-                &engine,
-                reporter,
-            )
+            let mut walk = typos_cli::file::walk_builder(path, walk_policy)
+                .with_code(proc_exit::Code::CONFIG_ERR)?;
+            walk.threads(args.threads);
+            if single_threaded {
+                typos_cli::file::walk_path(walk.build(), selected_checks, &engine, reporter)
+            } else {
+                typos_cli::file::walk_path_parallel(
+                    walk.build_parallel(),
+                    selected_checks,
+                    &engine,
+                    reporter,
+                )
+            }
+            .map_err(|e| {
+                e.io_error()
+                    .map(|i| proc_exit::Code::from(i.kind()))
+                    .unwrap_or_default()
+                    .with_message(e)
+            })?;
+        }
+        if args.write_changes {
+            fix_typos.finish(reporter)?;
+        }
+        if let (true, Some(repo)) = (args.restage, repo.as_ref()) {
+            let modified = fix_typos.modified();
+            repo.stage(&modified).with_code(proc_exit::Code::FAILURE)?;
+            if !modified.is_empty() {
+                files_modified = true;
+            }
         }
-        .map_err(|e| {
-            e.io_error()
-                .map(|i| proc_exit::Code::from(i.kind()))
-                .unwrap_or_default()
-                .with_message(e)
-        })?;
         if status_reporter.typos_found() {
             typos_found = true;
         }
@@ -254,6 +379,12 @@ fn run_checks(
         }
     }
 
+    if let Some(cache) = cache.as_ref() {
+        if let Err(err) = cache.prune() {
+            log::debug!("Failed to prune {}: {}", cache.dir().display(), err);
+        }
+    }
+
     if errors_found {
         proc_exit::Code::FAILURE.ok()
     } else if typos_found {
@@ -262,11 +393,28 @@ fn run_checks(
         //
         // Can't use DataErr or anything else an std::io::ErrorKind might map to.
         proc_exit::Code::UNKNOWN.ok()
+    } else if files_modified {
+        // Like other formatters run from a pre-commit hook, fail so the fixes can be reviewed
+        // before committing
+        FILES_MODIFIED.ok()
     } else {
         proc_exit::Code::SUCCESS.ok()
     }
 }
 
+/// `--restage` staged fixes
+const FILES_MODIFIED: proc_exit::Code = proc_exit::Code::new(3);
+
+/// Only findings from read-only checks are cached.
+fn is_cacheable(args: &args::Args) -> bool {
+    !(args.files
+        || args.identifiers
+        || args.words
+        || args.write_changes
+        || args.diff
+        || args.staged_content)
+}
+
 fn init_logging(level: Option<log::Level>, colored: bool) {
     if let Some(level) = level {
         let mut builder = env_logger::Builder::new();
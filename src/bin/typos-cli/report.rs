@@ -3,7 +3,7 @@
 use std::io::{self, Write};
 use std::sync::atomic;
 
-use typos_cli::report::{Context, Message, Report, Typo};
+use typos_cli::report::{Context, Message, Report, Severity, Typo};
 
 #[derive(Copy, Clone, Debug)]
 pub struct Palette {
@@ -31,11 +31,21 @@ impl Palette {
             strong: yansi::Style::default(),
         }
     }
+
+    fn severity(&self, severity: Severity) -> yansi::Style {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warning => self.warn,
+            Severity::Error => self.error,
+        }
+    }
 }
 
 pub struct MessageStatus<'r> {
     typos_found: atomic::AtomicBool,
     errors_found: atomic::AtomicBool,
+    /// Typos less severe than this don't count as found
+    threshold: Severity,
     reporter: &'r dyn Report,
 }
 
@@ -44,10 +54,21 @@ impl<'r> MessageStatus<'r> {
         Self {
             typos_found: atomic::AtomicBool::new(false),
             errors_found: atomic::AtomicBool::new(false),
+            threshold: Severity::Error,
             reporter,
         }
     }
 
+    /// Count warnings as typos found, not just errors.
+    pub fn deny_warnings(&mut self, yes: bool) -> &mut Self {
+        self.threshold = if yes {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+        self
+    }
+
     pub fn typos_found(&self) -> bool {
         self.typos_found.load(atomic::Ordering::Relaxed)
     }
@@ -59,9 +80,13 @@ impl<'r> MessageStatus<'r> {
 
 impl<'r> Report for MessageStatus<'r> {
     fn report(&self, msg: Message) -> Result<(), std::io::Error> {
+        let denied = msg
+            .severity()
+            .map(|severity| self.threshold <= severity)
+            .unwrap_or(false);
         let _ = self.typos_found.compare_exchange(
             false,
-            msg.is_correction(),
+            msg.is_correction() && denied,
             atomic::Ordering::Relaxed,
             atomic::Ordering::Relaxed,
         );
@@ -149,16 +174,22 @@ fn print_brief_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::E
         true,
     )
     .count();
+    // Errors are the norm, so only call out the others
+    let severity = match msg.severity {
+        Severity::Error => String::new(),
+        severity => format!("{}: ", palette.severity(severity).paint(severity)),
+    };
     match &msg.corrections {
         typos::Status::Valid => {}
         typos::Status::Invalid => {
             let divider = ":";
             writeln!(
                 io::stdout(),
-                "{}{}{}: {}",
+                "{}{}{}: {}{}",
                 palette.info.paint(context_display(&msg.context)),
                 palette.info.paint(divider),
                 palette.info.paint(column),
+                severity,
                 palette
                     .strong
                     .paint(format_args!("`{}` is disallowed:", msg.typo)),
@@ -168,10 +199,11 @@ fn print_brief_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::E
             let divider = ":";
             writeln!(
                 io::stdout(),
-                "{}{}{}: {}",
+                "{}{}{}: {}{}",
                 palette.info.paint(context_display(&msg.context)),
                 palette.info.paint(divider),
                 palette.info.paint(column),
+                severity,
                 palette.strong.paint(format_args!(
                     "`{}` -> {}",
                     msg.typo,
@@ -198,7 +230,7 @@ fn print_long_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::Er
             writeln!(
                 handle,
                 "{}: {}",
-                palette.error.paint("error"),
+                palette.severity(msg.severity).paint(msg.severity),
                 palette
                     .strong
                     .paint(format_args!("`{}` is disallowed`", msg.typo))
@@ -208,7 +240,7 @@ fn print_long_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::Er
             writeln!(
                 handle,
                 "{}: {}",
-                palette.error.paint("error"),
+                palette.severity(msg.severity).paint(msg.severity),
                 palette.strong.paint(format_args!(
                     "`{}` should be {}",
                     msg.typo,
@@ -226,12 +258,22 @@ fn print_long_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::Er
         palette.info.paint(column)
     )?;
 
-    if let Some(Context::File(context)) = &msg.context {
-        let line_num = context.line_num.to_string();
+    let line_num = match &msg.context {
+        Some(Context::File(context)) => Some(context.line_num),
+        Some(Context::Archive(context)) => Some(context.line_num),
+        Some(Context::Document(context)) => Some(context.paragraph),
+        Some(Context::Notebook(context)) => Some(context.line_num),
+        Some(Context::Commit(context)) => Some(context.line_num),
+        _ => None,
+    };
+    if let Some(line_num) = line_num {
+        let line_num = line_num.to_string();
         let line_indent: String = itertools::repeat_n(" ", line_num.len()).collect();
 
         let hl_indent: String = itertools::repeat_n(" ", column).collect();
-        let hl: String = itertools::repeat_n("^", msg.typo.len()).collect();
+        // Phrases can continue onto the next line
+        let hl_len = msg.typo.lines().next().unwrap_or_default().len();
+        let hl: String = itertools::repeat_n("^", hl_len).collect();
 
         writeln!(handle, "{} |", line_indent)?;
         writeln!(
@@ -245,7 +287,7 @@ fn print_long_correction(msg: &Typo, palette: Palette) -> Result<(), std::io::Er
             "{} | {}{}",
             line_indent,
             hl_indent,
-            palette.error.paint(hl)
+            palette.severity(msg.severity).paint(hl)
         )?;
         writeln!(handle, "{} |", line_indent)?;
     }
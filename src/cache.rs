@@ -0,0 +1,462 @@
+use std::borrow::Cow;
+
+use crate::file::FileChecker;
+use crate::report;
+
+/// On-disk storage of findings from prior runs.
+pub struct Cache {
+    dir: std::path::PathBuf,
+    max_entries: usize,
+}
+
+impl Cache {
+    pub fn new(dir: std::path::PathBuf) -> Self {
+        Self {
+            dir,
+            max_entries: 20_000,
+        }
+    }
+
+    /// How many files' findings `prune` keeps.
+    pub fn max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// The per-user cache directory, if one can be found.
+    pub fn user_dir() -> Option<std::path::PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|d| !d.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(std::path::PathBuf::from))
+            .or_else(|| {
+                std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join(".cache"))
+            })?;
+        Some(base.join("typos"))
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// Remove all cached findings.
+    pub fn clear(&self) -> Result<(), std::io::Error> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Forget all but the most recently used entries, bounding the size of the cache.
+    pub fn prune(&self) -> Result<(), std::io::Error> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            dir => dir?,
+        };
+        let mut entries = Vec::new();
+        for entry in dir {
+            let entry = entry?;
+            let used = entry.metadata()?.modified()?;
+            entries.push((used, entry.path()));
+        }
+        if entries.len() <= self.max_entries {
+            return Ok(());
+        }
+        entries.sort_unstable_by_key(|(used, _)| std::cmp::Reverse(*used));
+        for (_, path) in entries.drain(self.max_entries..) {
+            match std::fs::remove_file(&path) {
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The findings stored under `name`, if they were stored for the same `key`.
+    fn load(&self, name: &str, key: &str) -> Option<Vec<Entry>> {
+        let entry_path = self.entry_path(name);
+        let data = std::fs::read(&entry_path).ok()?;
+        let stored: Stored = serde_json::from_slice(&data).ok()?;
+        if stored.key != key {
+            return None;
+        }
+        // Mark the entry as recently used for `prune`
+        let _ = filetime::set_file_mtime(&entry_path, filetime::FileTime::now());
+        Some(stored.entries)
+    }
+
+    /// Store findings under `name`, replacing any stored for another `key`.
+    fn store(&self, name: &str, key: &str, entries: Vec<Entry>) -> Result<(), std::io::Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        let stored = Stored {
+            key: key.to_owned(),
+            entries,
+        };
+        let data = serde_json::to_vec(&stored)?;
+        // Write then rename so a concurrent or interrupted run never sees a partial entry.
+        let tmp_path = self
+            .dir
+            .join(format!("{}.{}.tmp", name, std::process::id()));
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, self.entry_path(name))
+    }
+
+    fn entry_path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+/// Skip re-checking files whose findings are already in the `Cache`.
+///
+/// Findings are stored per path, and only replayed when the file content, `Policy::fingerprint`,
+/// and `typos` version are unchanged.  Only use with read-only `FileChecker`s.
+pub struct Cached<'c> {
+    pub cache: &'c Cache,
+    pub inner: &'c dyn FileChecker,
+}
+
+impl<'c> FileChecker for Cached<'c> {
+    fn check_file(
+        &self,
+        path: &std::path::Path,
+        explicit: bool,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        let fingerprint = match policy.fingerprint {
+            Some(fingerprint) if path != std::path::Path::new("-") => fingerprint,
+            _ => {
+                return self.inner.check_file(path, explicit, policy, reporter);
+            }
+        };
+        let content = match crate::file::FileBuffer::open(path) {
+            Ok(content) => content,
+            // Let `inner` report the error
+            Err(_) => {
+                return self.inner.check_file(path, explicit, policy, reporter);
+            }
+        };
+        let path_key = path_key(path, explicit);
+        let key = key(fingerprint, &content);
+
+        if let Some(entries) = self.cache.load(&path_key, &key) {
+            log::trace!("Using cached findings for {}", path.display());
+            for entry in entries.iter() {
+                reporter.report(entry.to_message(path))?;
+            }
+            return Ok(());
+        }
+
+        let recorder = Record::new(reporter);
+        self.inner
+            .check_buffer(path, explicit, content, policy, &recorder)?;
+        if let Some(entries) = recorder.into_entries() {
+            if let Err(err) = self.cache.store(&path_key, &key, entries) {
+                log::debug!("Failed to cache findings for {}: {}", path.display(), err);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_dir(
+        &self,
+        path: &std::path::Path,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        self.inner.check_dir(path, policy, reporter)
+    }
+}
+
+fn path_key(path: &std::path::Path, explicit: bool) -> String {
+    // Relative paths, like those under `.`, are shared by every directory checked
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(&[0, explicit as u8]);
+    hasher.finalize().to_hex().to_string()
+}
+
+fn key(fingerprint: u64, content: &[u8]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&fingerprint.to_le_bytes());
+    hasher.update(content);
+    hasher.finalize().to_hex().to_string()
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct Stored {
+    key: String,
+    entries: Vec<Entry>,
+}
+
+/// Forward messages while keeping an owned copy of them.
+struct Record<'r> {
+    reporter: &'r dyn report::Report,
+    /// `None` when a message can't be cached.
+    entries: std::sync::Mutex<Option<Vec<Entry>>>,
+}
+
+impl<'r> Record<'r> {
+    fn new(reporter: &'r dyn report::Report) -> Self {
+        Self {
+            reporter,
+            entries: std::sync::Mutex::new(Some(Vec::new())),
+        }
+    }
+
+    fn into_entries(self) -> Option<Vec<Entry>> {
+        self.entries.into_inner().unwrap()
+    }
+}
+
+impl<'r> report::Report for Record<'r> {
+    fn report(&self, msg: report::Message) -> Result<(), std::io::Error> {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            match Entry::from_message(&msg) {
+                Some(entry) => {
+                    if let Some(entries) = entries.as_mut() {
+                        entries.push(entry);
+                    }
+                }
+                None => {
+                    *entries = None;
+                }
+            }
+        }
+        self.reporter.report(msg)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "type")]
+enum Entry {
+    BinaryFile,
+    Typo {
+        /// `None` for typos in the path
+        line_num: Option<usize>,
+        line: Vec<u8>,
+        byte_offset: usize,
+        typo: String,
+        /// `None` for disallowed words
+        corrections: Option<Vec<String>>,
+        severity: report::Severity,
+    },
+}
+
+impl Entry {
+    fn from_message(msg: &report::Message) -> Option<Self> {
+        match msg {
+            report::Message::BinaryFile(_) => Some(Entry::BinaryFile),
+            report::Message::Typo(msg) => {
+                let line_num = match msg.context.as_ref()? {
+                    report::Context::File(context) => Some(context.line_num),
+                    report::Context::Path(_) => None,
+                    report::Context::Archive(_)
+                    | report::Context::Document(_)
+                    | report::Context::Notebook(_)
+                    | report::Context::Commit(_)
+                    | report::Context::Branch(_) => return None,
+                };
+                let corrections = match &msg.corrections {
+                    typos::Status::Valid => return None,
+                    typos::Status::Invalid => None,
+                    typos::Status::Corrections(corrections) => {
+                        Some(corrections.iter().map(|c| c.as_ref().to_owned()).collect())
+                    }
+                };
+                Some(Entry::Typo {
+                    line_num,
+                    line: msg.buffer.as_ref().to_owned(),
+                    byte_offset: msg.byte_offset,
+                    typo: msg.typo.to_owned(),
+                    corrections,
+                    severity: msg.severity,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_message<'m>(&'m self, path: &'m std::path::Path) -> report::Message<'m> {
+        match self {
+            Entry::BinaryFile => report::BinaryFile { path }.into(),
+            Entry::Typo {
+                line_num,
+                line,
+                byte_offset,
+                typo,
+                corrections,
+                severity,
+            } => {
+                let context = match line_num {
+                    Some(line_num) => report::FileContext {
+                        path,
+                        line_num: *line_num,
+                    }
+                    .into(),
+                    None => report::PathContext { path }.into(),
+                };
+                let corrections = match corrections {
+                    Some(corrections) => typos::Status::Corrections(
+                        corrections
+                            .iter()
+                            .map(|c| Cow::Borrowed(c.as_str()))
+                            .collect(),
+                    ),
+                    None => typos::Status::Invalid,
+                };
+                report::Typo {
+                    context: Some(context),
+                    buffer: Cow::Borrowed(line),
+                    byte_offset: *byte_offset,
+                    typo,
+                    corrections,
+                    severity: *severity,
+                }
+                .into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use assert_fs::prelude::*;
+
+    #[derive(Default)]
+    struct Collect {
+        entries: std::sync::Mutex<Vec<Entry>>,
+    }
+
+    impl report::Report for Collect {
+        fn report(&self, msg: report::Message) -> Result<(), std::io::Error> {
+            self.entries
+                .lock()
+                .unwrap()
+                .push(Entry::from_message(&msg).unwrap());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replays_findings() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let sample = temp.child("sample.txt");
+        sample.write_str("Hello\nApropriate world\n").unwrap();
+
+        let cache = Cache::new(temp.child("cache").path().to_owned());
+        let checks = Cached {
+            cache: &cache,
+            inner: &crate::file::Typos,
+        };
+        let mut severities = crate::policy::Severities::default();
+        severities.typo = report::Severity::Warning;
+        let policy = crate::policy::Policy::new()
+            .fingerprint(Some(42))
+            .severities(severities);
+
+        let miss = Collect::default();
+        checks
+            .check_file(sample.path(), true, &policy, &miss)
+            .unwrap();
+        let hit = Collect::default();
+        checks
+            .check_file(sample.path(), true, &policy, &hit)
+            .unwrap();
+
+        let expected = vec![Entry::Typo {
+            line_num: Some(2),
+            line: b"Apropriate world".to_vec(),
+            byte_offset: 0,
+            typo: "Apropriate".to_owned(),
+            corrections: Some(vec!["Appropriate".to_owned()]),
+            severity: report::Severity::Warning,
+        }];
+        assert_eq!(miss.entries.into_inner().unwrap(), expected);
+        assert_eq!(hit.entries.into_inner().unwrap(), expected);
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 1);
+
+        cache.clear().unwrap();
+        assert!(!cache.dir().exists());
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_replaces_stale_findings() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let sample = temp.child("sample.txt");
+        sample.write_str("Apropriate world\n").unwrap();
+
+        let cache = Cache::new(temp.child("cache").path().to_owned());
+        let checks = Cached {
+            cache: &cache,
+            inner: &crate::file::Typos,
+        };
+        let policy = crate::policy::Policy::new().fingerprint(Some(42));
+
+        checks
+            .check_file(sample.path(), true, &policy, &Collect::default())
+            .unwrap();
+        sample.write_str("Hello world\n").unwrap();
+        let changed = Collect::default();
+        checks
+            .check_file(sample.path(), true, &policy, &changed)
+            .unwrap();
+
+        assert_eq!(changed.entries.into_inner().unwrap(), vec![]);
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 1);
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_prune() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut cache = Cache::new(temp.child("cache").path().to_owned());
+        cache.max_entries(3);
+        cache.prune().unwrap();
+
+        for i in 0..5 {
+            cache.store(&i.to_string(), "key", Vec::new()).unwrap();
+        }
+        let epoch = filetime::FileTime::zero();
+        filetime::set_file_mtime(cache.entry_path("0"), epoch).unwrap();
+        assert!(cache.load("0", "key").is_some());
+        filetime::set_file_mtime(cache.entry_path("1"), epoch).unwrap();
+
+        cache.prune().unwrap();
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 3);
+        // Loading marked it as used
+        assert!(cache.load("0", "key").is_some());
+        assert!(cache.load("1", "key").is_none());
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_key_changes() {
+        let path = std::path::Path::new("foo.txt");
+        let base = path_key(path, true);
+        assert_eq!(base, path_key(path, true));
+        assert_ne!(base, path_key(std::path::Path::new("bar.txt"), true));
+        assert_ne!(base, path_key(path, false));
+
+        let relative = std::path::Path::new("Cargo.toml");
+        let absolute = std::env::current_dir().unwrap().join(relative);
+        assert_eq!(path_key(relative, true), path_key(&absolute, true));
+
+        let base = key(0, b"hello");
+        assert_eq!(base, key(0, b"hello"));
+        assert_ne!(base, key(1, b"hello"));
+        assert_ne!(base, key(0, b"world"));
+    }
+}
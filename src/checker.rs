@@ -0,0 +1,292 @@
+//! Check paths and buffers with the project's config, like the `typos` command does.
+//!
+//! ```no_run
+//! let storage = typos_cli::policy::ConfigStorage::new();
+//! let mut checker = typos_cli::checker::Checker::from_dir(&storage, std::path::Path::new("."))?;
+//! for finding in checker.check_path(std::path::Path::new("src"))? {
+//!     println!("{}: `{}`", finding.path.display(), finding.typo);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::policy::ConfigEngine;
+use crate::policy::ConfigStorage;
+use crate::report;
+
+/// Find typos, discovering config files the same way as the `typos` command.
+pub struct Checker<'s> {
+    engine: ConfigEngine<'s>,
+}
+
+impl<'s> Checker<'s> {
+    /// Checker that loads config files as paths are checked.
+    pub fn new(storage: &'s ConfigStorage) -> Self {
+        Self {
+            engine: ConfigEngine::new(storage),
+        }
+    }
+
+    /// Checker with the config for `dir` already loaded, reporting config errors up front.
+    pub fn from_dir(
+        storage: &'s ConfigStorage,
+        dir: &std::path::Path,
+    ) -> Result<Self, anyhow::Error> {
+        let mut checker = Self::new(storage);
+        checker.init_dir(dir)?;
+        Ok(checker)
+    }
+
+    /// Settings that take precedence over any config files.
+    ///
+    /// Only applies to directories loaded afterwards.
+    pub fn set_overrides(&mut self, overrides: crate::config::Config) -> &mut Self {
+        self.engine.set_overrides(overrides);
+        self
+    }
+
+    /// Ignore config files, only using the defaults and overrides.
+    ///
+    /// Only applies to directories loaded afterwards.
+    pub fn set_isolated(&mut self, isolated: bool) -> &mut Self {
+        self.engine.set_isolated(isolated);
+        self
+    }
+
+    /// Check a file or everything under a directory, honoring ignore files.
+    ///
+    /// Fails on the first file that can't be read.
+    pub fn check_path(&mut self, path: &std::path::Path) -> Result<Vec<Finding>, anyhow::Error> {
+        let cwd = if path.is_file() {
+            path.parent().unwrap()
+        } else {
+            path
+        };
+        let cwd = self.init_dir(cwd)?;
+        let walk = crate::file::walk_builder(path, self.engine.walk(&cwd))?;
+
+        let collect = Collect::default();
+        crate::file::walk_path(walk.build(), &crate::file::Typos, &self.engine, &collect)?;
+        collect.into_findings()
+    }
+
+    /// Check `buffer` as if it were the content of `path`.
+    ///
+    /// `path` selects which config applies and doesn't need to exist.  Its file name is not
+    /// checked.
+    pub fn check_buffer(
+        &mut self,
+        path: &std::path::Path,
+        buffer: &[u8],
+    ) -> Result<Vec<Finding>, anyhow::Error> {
+        let path = if path.is_absolute() {
+            path.to_owned()
+        } else {
+            std::env::current_dir()?.join(path)
+        };
+        let cwd = path.parent().unwrap_or(&path);
+        let cwd = self.init_dir(cwd)?;
+        let lookup_path = cwd.join(path.file_name().unwrap_or_default());
+
+        let collect = Collect::default();
+        let policy = self.engine.policy(&lookup_path);
+        crate::file::check_buffer(&path, buffer, &policy, &collect)?;
+        collect.into_findings()
+    }
+
+    fn init_dir(&mut self, dir: &std::path::Path) -> Result<std::path::PathBuf, anyhow::Error> {
+        let dir = dir.canonicalize()?;
+        self.engine.init_dir(&dir)?;
+        Ok(dir)
+    }
+}
+
+/// An owned copy of a typo that was found.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[non_exhaustive]
+pub struct Finding {
+    pub path: std::path::PathBuf,
+    /// The file within `path` when it is an archive
+    pub member: Option<std::path::PathBuf>,
+    /// `None` for typos in the file name
+    pub line_num: Option<usize>,
+    /// Offset within the line or file name
+    pub byte_offset: usize,
+    pub typo: String,
+    /// `None` for disallowed words
+    pub corrections: Option<Vec<String>>,
+    pub severity: report::Severity,
+}
+
+impl Finding {
+    fn from_typo(msg: &report::Typo) -> Option<Self> {
+        let (path, member, line_num) = match msg.context.as_ref()? {
+            report::Context::File(context) => (context.path, None, Some(context.line_num)),
+            report::Context::Path(context) => (context.path, None, None),
+            report::Context::Archive(context) => (
+                context.path,
+                Some(context.member.to_owned()),
+                Some(context.line_num),
+            ),
+            // Findings are located by line of the file, which extracted text doesn't have
+            report::Context::Document(_) | report::Context::Notebook(_) => return None,
+            report::Context::Commit(_) | report::Context::Branch(_) => return None,
+        };
+        let corrections = match &msg.corrections {
+            typos::Status::Valid => return None,
+            typos::Status::Invalid => None,
+            typos::Status::Corrections(corrections) => {
+                Some(corrections.iter().map(|c| c.as_ref().to_owned()).collect())
+            }
+        };
+        Some(Self {
+            path: path.to_owned(),
+            member,
+            line_num,
+            byte_offset: msg.byte_offset,
+            typo: msg.typo.to_owned(),
+            corrections,
+            severity: msg.severity,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Collect {
+    findings: std::sync::Mutex<Vec<Finding>>,
+    error: std::sync::Mutex<Option<String>>,
+}
+
+impl Collect {
+    fn into_findings(self) -> Result<Vec<Finding>, anyhow::Error> {
+        match self.error.into_inner().unwrap() {
+            Some(error) => Err(anyhow::anyhow!(error)),
+            None => Ok(self.findings.into_inner().unwrap()),
+        }
+    }
+}
+
+impl report::Report for Collect {
+    fn report(&self, msg: report::Message) -> Result<(), std::io::Error> {
+        match msg {
+            report::Message::Typo(msg) => {
+                if let Some(finding) = Finding::from_typo(&msg) {
+                    self.findings.lock().unwrap().push(finding);
+                }
+            }
+            report::Message::Error(msg) => {
+                let mut error = self.error.lock().unwrap();
+                if error.is_none() {
+                    let msg = match msg.context.as_ref().and_then(context_path) {
+                        Some(path) => format!("{}: {}", path.display(), msg.msg),
+                        None => msg.msg,
+                    };
+                    *error = Some(msg);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn context_path<'m>(context: &report::Context<'m>) -> Option<&'m std::path::Path> {
+    match context {
+        report::Context::File(context) => Some(context.path),
+        report::Context::Path(context) => Some(context.path),
+        report::Context::Archive(context) => Some(context.path),
+        report::Context::Document(context) => Some(context.path),
+        report::Context::Notebook(context) => Some(context.path),
+        report::Context::Commit(_) | report::Context::Branch(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn test_check_path_uses_config() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("_typos.toml")
+            .write_str(
+                "[default.severity]\ncustom = \"warning\"\n[default.extend-words]\nhellp = \"help\"\n",
+            )
+            .unwrap();
+        temp.child("src/sample.txt")
+            .write_str("Hello\nhellp apropriate world\n")
+            .unwrap();
+
+        let storage = ConfigStorage::new();
+        let mut checker = Checker::from_dir(&storage, temp.path()).unwrap();
+        let mut actual = checker.check_path(temp.child("src").path()).unwrap();
+        actual.sort_by_key(|f| f.byte_offset);
+
+        let path = temp.child("src/sample.txt").path().to_owned();
+        assert_eq!(
+            actual,
+            vec![
+                Finding {
+                    path: path.clone(),
+                    member: None,
+                    line_num: Some(2),
+                    byte_offset: 0,
+                    typo: "hellp".to_owned(),
+                    corrections: Some(vec!["help".to_owned()]),
+                    severity: report::Severity::Warning,
+                },
+                Finding {
+                    path,
+                    member: None,
+                    line_num: Some(2),
+                    byte_offset: 6,
+                    typo: "apropriate".to_owned(),
+                    corrections: Some(vec!["appropriate".to_owned()]),
+                    severity: report::Severity::Error,
+                },
+            ]
+        );
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_check_buffer_uses_config() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("_typos.toml")
+            .write_str("[default.extend-words]\nhellp = \"help\"\n")
+            .unwrap();
+
+        let storage = ConfigStorage::new();
+        let mut checker = Checker::new(&storage);
+        let path = temp.child("unsaved.txt").path().to_owned();
+        let actual = checker.check_buffer(&path, b"fine\n\nhellp\n").unwrap();
+
+        assert_eq!(
+            actual,
+            vec![Finding {
+                path,
+                member: None,
+                line_num: Some(3),
+                byte_offset: 0,
+                typo: "hellp".to_owned(),
+                corrections: Some(vec!["help".to_owned()]),
+                severity: report::Severity::Error,
+            }]
+        );
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_check_path_missing() {
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        let storage = ConfigStorage::new();
+        let mut checker = Checker::new(&storage);
+        assert!(checker.check_path(&temp.path().join("missing")).is_err());
+
+        temp.close().unwrap();
+    }
+}
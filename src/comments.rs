@@ -0,0 +1,162 @@
+//! Find the comments within source code, so they can be checked as prose and the rest as code.
+//!
+//! Like `markup`, this is a deliberately loose scanner: it only skips over double-quoted strings
+//! so a `//` in a URL isn't taken for a comment, and it works on raw bytes so the offsets of what
+//! it finds are exact.
+
+use std::ops::Range;
+
+/// How comments are written in a language.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Syntax {
+    /// Markers starting a comment that runs to the end of the line, like `//`
+    line: &'static [&'static str],
+    /// Markers opening and closing a comment, like `/*` and `*/`
+    block: &'static [(&'static str, &'static str)],
+}
+
+const C: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+};
+const CSS: Syntax = Syntax {
+    line: &[],
+    block: &[("/*", "*/")],
+};
+const PHP: Syntax = Syntax {
+    line: &["//", "#"],
+    block: &[("/*", "*/")],
+};
+const HASH: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+};
+const DASHES: Syntax = Syntax {
+    line: &["--"],
+    block: &[],
+};
+const HASKELL: Syntax = Syntax {
+    line: &["--"],
+    block: &[("{-", "-}")],
+};
+const SEMICOLON: Syntax = Syntax {
+    line: &[";"],
+    block: &[],
+};
+const PERCENT: Syntax = Syntax {
+    line: &["%"],
+    block: &[],
+};
+const XML: Syntax = Syntax {
+    line: &[],
+    block: &[("<!--", "-->")],
+};
+
+/// The comment syntax of a file type, by its name, `None` when it isn't source code or is
+/// unknown.
+pub fn syntax(type_name: &str) -> Option<&'static Syntax> {
+    let syntax = match type_name {
+        "c" | "cpp" | "cs" | "csharp" | "d" | "dart" | "go" | "groovy" | "h" | "java" | "js"
+        | "kotlin" | "less" | "objc" | "objcpp" | "protobuf" | "rust" | "sass" | "scala"
+        | "swift" | "ts" | "zig" => &C,
+        "css" => &CSS,
+        "php" => &PHP,
+        "awk" | "cmake" | "crystal" | "cython" | "elixir" | "fish" | "jl" | "julia" | "make"
+        | "mk" | "nim" | "perl" | "py" | "r" | "ruby" | "sh" | "tcl" | "toml" | "yaml" | "zsh" => {
+            &HASH
+        }
+        "elm" | "lua" | "sql" | "vhdl" => &DASHES,
+        "haskell" | "hs" => &HASKELL,
+        "clojure" | "elisp" | "lisp" | "racket" => &SEMICOLON,
+        "erlang" | "matlab" => &PERCENT,
+        "svg" | "xml" => &XML,
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// The comments in `buffer`, including their markers, in order.
+pub fn comments(syntax: &Syntax, buffer: &[u8]) -> Vec<Range<usize>> {
+    let mut comments = Vec::new();
+    let mut pos = 0;
+    while pos < buffer.len() {
+        let rest = &buffer[pos..];
+        if rest[0] == b'"' {
+            pos += string_len(rest);
+        } else if syntax.line.iter().any(|m| rest.starts_with(m.as_bytes())) {
+            let len = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            comments.push(pos..pos + len);
+            pos += len;
+        } else if let Some((open, close)) = syntax
+            .block
+            .iter()
+            .find(|(open, _)| rest.starts_with(open.as_bytes()))
+        {
+            let len = rest[open.len()..]
+                .windows(close.len())
+                .position(|w| w == close.as_bytes())
+                .map(|i| open.len() + i + close.len())
+                .unwrap_or(rest.len());
+            comments.push(pos..pos + len);
+            pos += len;
+        } else {
+            pos += 1;
+        }
+    }
+    comments
+}
+
+/// The length of the double-quoted string at the start of `text`, stopping at the end of the
+/// line when it isn't closed.
+fn string_len(text: &[u8]) -> usize {
+    let mut pos = 1;
+    while pos < text.len() {
+        match text[pos] {
+            b'\\' => pos += 2,
+            b'"' => return pos + 1,
+            b'\n' => return pos,
+            _ => pos += 1,
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commented(type_name: &str, buffer: &str) -> Vec<String> {
+        let syntax = syntax(type_name).unwrap();
+        comments(syntax, buffer.as_bytes())
+            .into_iter()
+            .map(|range| buffer[range].to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn c_comments() {
+        let buffer = "let url = \"http://x.y\"; // set the color\n/* a\nblock */ style.color\n";
+        assert_eq!(
+            commented("rust", buffer),
+            vec!["// set the color".to_owned(), "/* a\nblock */".to_owned()]
+        );
+    }
+
+    #[test]
+    fn hash_comments() {
+        let buffer = "s = \"# \\\" not\"  # but this\n";
+        assert_eq!(commented("py", buffer), vec!["# but this".to_owned()]);
+    }
+
+    #[test]
+    fn unclosed_block_runs_to_end() {
+        let buffer = "<a/><!-- open";
+        assert_eq!(commented("xml", buffer), vec!["<!-- open".to_owned()]);
+    }
+
+    #[test]
+    fn prose_has_no_syntax() {
+        assert_eq!(syntax("md"), None);
+        assert_eq!(syntax("txt"), None);
+    }
+}
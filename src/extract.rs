@@ -0,0 +1,436 @@
+//! Pull the text out of document formats that would otherwise be skipped as binary.
+
+/// Text from one place within a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    /// The page, for formats with fixed pagination like PDF
+    pub page: Option<usize>,
+    /// The file within the document, for formats made of several like EPUB
+    pub part: Option<String>,
+    /// Counted from 1, within the page or part when there is one
+    pub paragraph: usize,
+    /// The paragraph, with whitespace collapsed onto one line
+    pub text: String,
+}
+
+/// Extracts text from a document format.
+pub trait Extractor: Send + Sync {
+    /// The name of the format, for messages.
+    fn name(&self) -> &'static str;
+
+    /// Whether `buffer` is in this format.
+    fn matches(&self, buffer: &[u8]) -> bool;
+
+    fn extract(&self, buffer: &[u8]) -> Result<Vec<Section>, anyhow::Error>;
+}
+
+/// The built-in extractors, in the order they are tried.
+pub static EXTRACTORS: &[&dyn Extractor] = &[&Pdf, &Docx, &Odt, &Epub];
+
+/// The extractor for the format of `buffer`, if any.
+pub fn find(buffer: &[u8]) -> Option<&'static dyn Extractor> {
+    EXTRACTORS.iter().copied().find(|e| e.matches(buffer))
+}
+
+/// Whether `prefix` is the start of a format that might have an extractor.
+pub(crate) fn is_candidate(prefix: &[u8]) -> bool {
+    prefix.starts_with(PDF_MAGIC) || prefix.starts_with(ZIP_MAGIC)
+}
+
+const PDF_MAGIC: &[u8] = b"%PDF-";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Each block of text on a page is a paragraph.
+#[derive(Copy, Clone, Debug)]
+pub struct Pdf;
+
+impl Extractor for Pdf {
+    fn name(&self) -> &'static str {
+        "PDF"
+    }
+
+    fn matches(&self, buffer: &[u8]) -> bool {
+        buffer.starts_with(PDF_MAGIC)
+    }
+
+    fn extract(&self, buffer: &[u8]) -> Result<Vec<Section>, anyhow::Error> {
+        let document = lopdf::Document::load_mem(buffer)?;
+        let mut sections = Vec::new();
+        for page in document.get_pages().keys() {
+            let text = document.extract_text(&[*page])?;
+            let blocks = text.lines().map(collapse_whitespace);
+            sections.extend(
+                blocks
+                    .enumerate()
+                    .filter(|(_, t)| !t.is_empty())
+                    .map(|(i, text)| Section {
+                        page: Some(*page as usize),
+                        part: None,
+                        paragraph: i + 1,
+                        text,
+                    }),
+            );
+        }
+        Ok(sections)
+    }
+}
+
+/// Paragraphs of the main body of a Word document.
+#[derive(Copy, Clone, Debug)]
+pub struct Docx;
+
+impl Extractor for Docx {
+    fn name(&self) -> &'static str {
+        "DOCX"
+    }
+
+    fn matches(&self, buffer: &[u8]) -> bool {
+        buffer.starts_with(ZIP_MAGIC)
+            && zip::ZipArchive::new(std::io::Cursor::new(buffer))
+                .map(|mut a| a.by_name("word/document.xml").is_ok())
+                .unwrap_or(false)
+    }
+
+    fn extract(&self, buffer: &[u8]) -> Result<Vec<Section>, anyhow::Error> {
+        let xml = read_member(buffer, "word/document.xml")?;
+        let paragraphs = xml_paragraphs(
+            &xml,
+            |name| name == b"p",
+            |name| name == b"t",
+            |name| matches!(name, b"br" | b"cr" | b"tab"),
+        )?;
+        Ok(sections(None, paragraphs))
+    }
+}
+
+/// Paragraphs and headings of an OpenDocument text document.
+#[derive(Copy, Clone, Debug)]
+pub struct Odt;
+
+impl Extractor for Odt {
+    fn name(&self) -> &'static str {
+        "ODT"
+    }
+
+    fn matches(&self, buffer: &[u8]) -> bool {
+        has_mimetype(buffer, b"application/vnd.oasis.opendocument.text")
+    }
+
+    fn extract(&self, buffer: &[u8]) -> Result<Vec<Section>, anyhow::Error> {
+        let xml = read_member(buffer, "content.xml")?;
+        let paragraphs = xml_paragraphs(
+            &xml,
+            |name| matches!(name, b"p" | b"h"),
+            |_| true,
+            |name| matches!(name, b"s" | b"tab" | b"line-break"),
+        )?;
+        Ok(sections(None, paragraphs))
+    }
+}
+
+/// Block elements of each XHTML file in an EPUB, in the order they are stored.
+#[derive(Copy, Clone, Debug)]
+pub struct Epub;
+
+impl Extractor for Epub {
+    fn name(&self) -> &'static str {
+        "EPUB"
+    }
+
+    fn matches(&self, buffer: &[u8]) -> bool {
+        has_mimetype(buffer, b"application/epub+zip")
+    }
+
+    fn extract(&self, buffer: &[u8]) -> Result<Vec<Section>, anyhow::Error> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(buffer))?;
+        let mut sections = Vec::new();
+        for i in 0..archive.len() {
+            let mut member = archive.by_index(i)?;
+            let name = member.name().to_owned();
+            let is_xhtml = [".xhtml", ".html", ".htm"]
+                .iter()
+                .any(|ext| name.to_ascii_lowercase().ends_with(ext));
+            if !member.is_file() || !is_xhtml {
+                continue;
+            }
+            let mut xml = Vec::new();
+            std::io::Read::read_to_end(&mut member, &mut xml)?;
+            let paragraphs = xml_paragraphs(
+                &xml,
+                |name| {
+                    matches!(
+                        name,
+                        b"p" | b"h1"
+                            | b"h2"
+                            | b"h3"
+                            | b"h4"
+                            | b"h5"
+                            | b"h6"
+                            | b"li"
+                            | b"dt"
+                            | b"dd"
+                            | b"td"
+                            | b"th"
+                            | b"pre"
+                            | b"blockquote"
+                            | b"figcaption"
+                            | b"caption"
+                    )
+                },
+                |_| true,
+                |name| name == b"br",
+            )?;
+            sections.extend(self::sections(Some(name), paragraphs));
+        }
+        Ok(sections)
+    }
+}
+
+/// Whether `buffer` is a zip whose first member is an uncompressed `mimetype`, as OpenDocument
+/// and EPUB require.
+fn has_mimetype(buffer: &[u8], mimetype: &[u8]) -> bool {
+    const NAME_OFFSET: usize = 30;
+    buffer.starts_with(ZIP_MAGIC)
+        && buffer
+            .get(NAME_OFFSET..)
+            .map(|rest| rest.starts_with(b"mimetype") && rest[8..].starts_with(mimetype))
+            .unwrap_or(false)
+}
+
+fn read_member(buffer: &[u8], name: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(buffer))?;
+    let mut member = archive.by_name(name)?;
+    let mut content = Vec::new();
+    std::io::Read::read_to_end(&mut member, &mut content)?;
+    Ok(content)
+}
+
+fn sections(part: Option<String>, paragraphs: Vec<String>) -> Vec<Section> {
+    paragraphs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, text)| !text.is_empty())
+        .map(|(i, text)| Section {
+            page: None,
+            part: part.clone(),
+            paragraph: i + 1,
+            text,
+        })
+        .collect()
+}
+
+/// The text of each paragraph element, including empty ones so numbering matches the document.
+///
+/// Only text within `is_text` elements is kept and `is_break` elements separate words.
+/// Elements are matched by their name without the namespace.
+fn xml_paragraphs(
+    xml: &[u8],
+    is_paragraph: impl Fn(&[u8]) -> bool,
+    is_text: impl Fn(&[u8]) -> bool,
+    is_break: impl Fn(&[u8]) -> bool,
+) -> Result<Vec<String>, anyhow::Error> {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut paragraphs = Vec::new();
+    let mut paragraph_depth = 0;
+    let mut text_depth = 0;
+    let mut current = String::new();
+    loop {
+        match reader.read_event()? {
+            quick_xml::events::Event::Start(e) => {
+                let name = e.local_name();
+                if is_paragraph(name.as_ref()) {
+                    paragraph_depth += 1;
+                }
+                if is_text(name.as_ref()) {
+                    text_depth += 1;
+                }
+                if is_break(name.as_ref()) {
+                    current.push(' ');
+                }
+            }
+            quick_xml::events::Event::Empty(e) => {
+                let name = e.local_name();
+                if is_paragraph(name.as_ref()) && paragraph_depth == 0 {
+                    paragraphs.push(String::new());
+                }
+                if is_break(name.as_ref()) {
+                    current.push(' ');
+                }
+            }
+            quick_xml::events::Event::End(e) => {
+                let name = e.local_name();
+                if is_text(name.as_ref()) {
+                    text_depth -= 1;
+                }
+                if is_paragraph(name.as_ref()) {
+                    paragraph_depth -= 1;
+                    if paragraph_depth == 0 {
+                        paragraphs.push(collapse_whitespace(&current));
+                        current.clear();
+                    }
+                }
+            }
+            quick_xml::events::Event::Text(e) if 0 < paragraph_depth && 0 < text_depth => {
+                // XHTML has entities XML doesn't know about
+                let text = e.unescape_with(|entity| match entity {
+                    "nbsp" => Some("\u{a0}"),
+                    _ => None,
+                });
+                match text {
+                    Ok(text) => current.push_str(&text),
+                    Err(_) => current.push_str(&String::from_utf8_lossy(&e)),
+                }
+            }
+            quick_xml::events::Event::CData(e) if 0 < paragraph_depth && 0 < text_depth => {
+                current.push_str(&String::from_utf8_lossy(&e));
+            }
+            quick_xml::events::Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(paragraphs)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    itertools::join(text.split_whitespace(), " ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_xml_paragraphs_docx() {
+        let xml = br#"<w:document xmlns:w="w"><w:body>
+<w:p><w:r><w:t>Hello </w:t></w:r><w:r><w:t>teh</w:t><w:br/><w:t>world</w:t></w:r></w:p>
+<w:p/>
+<w:p><w:r><w:instrText>IGNORED</w:instrText><w:t>Bye</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        let actual = xml_paragraphs(
+            xml,
+            |name| name == b"p",
+            |name| name == b"t",
+            |name| matches!(name, b"br" | b"cr" | b"tab"),
+        )
+        .unwrap();
+        assert_eq!(actual, vec!["Hello teh world", "", "Bye"]);
+    }
+
+    #[test]
+    fn test_xml_paragraphs_nested() {
+        let xml = b"<html><body><ul><li><p>One &amp; two</p></li></ul>\n<p>Three&nbsp;four</p></body></html>";
+        let actual = xml_paragraphs(
+            xml,
+            |name| name == b"p" || name == b"li",
+            |_| true,
+            |_| false,
+        )
+        .unwrap();
+        assert_eq!(actual, vec!["One & two", "Three four"]);
+    }
+
+    #[test]
+    fn test_sections_skip_empty() {
+        let actual = sections(
+            None,
+            vec!["One".to_owned(), "".to_owned(), "Three".to_owned()],
+        );
+        assert_eq!(
+            actual,
+            vec![
+                Section {
+                    page: None,
+                    part: None,
+                    paragraph: 1,
+                    text: "One".to_owned(),
+                },
+                Section {
+                    page: None,
+                    part: None,
+                    paragraph: 3,
+                    text: "Three".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pdf() {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Object, Stream};
+
+        let mut document = lopdf::Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let font_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = document.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let text = |text: &str| {
+            vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Tj", vec![Object::string_literal(text)]),
+                Operation::new("ET", vec![]),
+            ]
+        };
+        let mut operations = text("Hello world");
+        operations.extend(text("Goodbye teh world"));
+        let content = Content { operations };
+        let content_id =
+            document.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => resources_id,
+        });
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalog_id);
+        let mut buffer = Vec::new();
+        document.save_to(&mut buffer).unwrap();
+
+        assert_eq!(find(&buffer).unwrap().name(), "PDF");
+        let actual = Pdf.extract(&buffer).unwrap();
+        assert_eq!(
+            actual,
+            vec![
+                Section {
+                    page: Some(1),
+                    part: None,
+                    paragraph: 1,
+                    text: "Hello world".to_owned(),
+                },
+                Section {
+                    page: Some(1),
+                    part: None,
+                    paragraph: 2,
+                    text: "Goodbye teh world".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_has_mimetype() {
+        let mut buffer = b"PK\x03\x04".to_vec();
+        buffer.resize(30, 0);
+        buffer.extend_from_slice(b"mimetypeapplication/epub+zip");
+        assert!(Epub.matches(&buffer));
+        assert!(!Odt.matches(&buffer));
+    }
+}
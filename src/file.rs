@@ -1,5 +1,5 @@
 use bstr::ByteSlice;
-use encoding::Encoding;
+use std::borrow::Cow;
 use std::io::Read;
 use std::io::Write;
 
@@ -13,6 +13,28 @@ pub trait FileChecker: Send + Sync {
         policy: &crate::policy::Policy,
         reporter: &dyn report::Report,
     ) -> Result<(), std::io::Error>;
+
+    /// Check a file whose `content` has already been read.
+    fn check_buffer(
+        &self,
+        path: &std::path::Path,
+        explicit: bool,
+        _content: FileBuffer,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        self.check_file(path, explicit, policy, reporter)
+    }
+
+    /// Check a directory found while walking, not including the root of the walk.
+    fn check_dir(
+        &self,
+        _path: &std::path::Path,
+        _policy: &crate::policy::Policy,
+        _reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -25,50 +47,177 @@ impl FileChecker for Typos {
         explicit: bool,
         policy: &crate::policy::Policy,
         reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        check_typos(path, explicit, None, policy, reporter)
+    }
+
+    fn check_buffer(
+        &self,
+        path: &std::path::Path,
+        explicit: bool,
+        content: FileBuffer,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        check_typos(path, explicit, Some(content), policy, reporter)
+    }
+
+    fn check_dir(
+        &self,
+        path: &std::path::Path,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
     ) -> Result<(), std::io::Error> {
         if policy.check_filenames {
-            if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                for typo in typos::check_str(file_name, policy.tokenizer, policy.dict) {
-                    let msg = report::Typo {
-                        context: Some(report::PathContext { path }.into()),
-                        buffer: std::borrow::Cow::Borrowed(file_name.as_bytes()),
-                        byte_offset: typo.byte_offset,
-                        typo: typo.typo.as_ref(),
-                        corrections: typo.corrections,
-                    };
-                    reporter.report(msg.into())?;
-                }
-            }
+            report_file_name(path, policy, reporter)?;
         }
+        Ok(())
+    }
+}
 
-        if policy.check_files {
-            let (buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && !policy.binary && content_type.is_binary() {
+/// Report the typos in `path`, reading it unless its `content` is already known.
+fn check_typos(
+    path: &std::path::Path,
+    explicit: bool,
+    content: Option<FileBuffer>,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    if policy.check_filenames {
+        report_file_name(path, policy, reporter)?;
+    }
+
+    let is_extracted = policy.check_files
+        && path != std::path::Path::new("-")
+        && ((policy.documents
+            && report_document(path, content.as_deref(), false, policy, reporter)?)
+            || (policy.archives
+                && report_archive(path, content.as_deref(), explicit, policy, reporter)?));
+    if policy.check_files && !is_extracted && is_notebook(path) {
+        let (buffer, _) = read_content(path, content, policy.legacy_encodings, reporter)?;
+        check_notebook(path, &buffer, false, policy, reporter)?;
+    } else if policy.check_files && !is_extracted && policy.markup != crate::config::Markup::Plain {
+        // Markup has to be scanned as a whole
+        let (buffer, encoding) = read_content(path, content, policy.legacy_encodings, reporter)?;
+        if !explicit && !policy.binary && encoding.is_binary() {
+            let msg = report::BinaryFile { path };
+            reporter.report(msg.into())?;
+        } else {
+            let mut accum_line_num = AccumulateLineNum::new();
+            report_content(path, &buffer, &mut accum_line_num, policy, reporter)?;
+        }
+    } else if policy.check_files && !is_extracted {
+        // Stream the content so huge files and never-ending pipes don't need to fit into
+        // memory.
+        let chunks = match content {
+            Some(content) => ChunkReader::from_buffer(content, policy.legacy_encodings),
+            None => ChunkReader::open(path, policy.legacy_encodings),
+        };
+        if let Some(mut chunks) = report_result(chunks.map(Some), reporter)? {
+            if !explicit && !policy.binary && chunks.encoding().is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
                 let mut accum_line_num = AccumulateLineNum::new();
-                for typo in typos::check_bytes(&buffer, policy.tokenizer, policy.dict) {
-                    let line_num = accum_line_num.line_num(&buffer, typo.byte_offset);
-                    let (line, line_offset) = extract_line(&buffer, typo.byte_offset);
-                    let msg = report::Typo {
-                        context: Some(report::FileContext { path, line_num }.into()),
-                        buffer: std::borrow::Cow::Borrowed(line),
-                        byte_offset: line_offset,
-                        typo: typo.typo.as_ref(),
-                        corrections: typo.corrections,
-                    };
-                    reporter.report(msg.into())?;
+                while let Some(buffer) = report_result(chunks.next_chunk(), reporter)? {
+                    report_content(path, &buffer, &mut accum_line_num, policy, reporter)?;
+                    accum_line_num.next_chunk(&buffer);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fix typos in file content, renaming files and directories with typos in their names.
+///
+/// Renames are deferred until `finish` so they don't disrupt the walk.
+#[derive(Debug, Default)]
+pub struct FixTypos {
+    update_references: bool,
+    pending: std::sync::Mutex<PendingRenames>,
+    modified: std::sync::Mutex<Vec<std::path::PathBuf>>,
+}
+
+#[derive(Debug, Default)]
+struct PendingRenames {
+    renames: Vec<(std::path::PathBuf, std::path::PathBuf)>,
+    /// Files that might refer to a renamed path
+    referrers: Vec<Referrer>,
+}
+
+#[derive(Debug)]
+struct Referrer {
+    path: std::path::PathBuf,
+    write: WriteOptions,
+    legacy_encodings: bool,
+}
+
+impl FixTypos {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Update references to renamed paths in the files whose content was checked.
+    ///
+    /// A reference is the old file or directory name appearing as a whole path component that is
+    /// clearly part of a path: next to a path separator, after a quote, or with its extension.
+    pub fn update_references(&mut self, yes: bool) -> &mut Self {
+        self.update_references = yes;
+        self
+    }
+
+    /// The paths written or renamed so far, including the old names of renamed paths.
+    pub fn modified(&self) -> Vec<std::path::PathBuf> {
+        self.modified.lock().unwrap().clone()
+    }
+
+    /// Apply the renames found while checking.
+    pub fn finish(&self, reporter: &dyn report::Report) -> Result<(), std::io::Error> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+
+        let mut renames = pending.renames;
+        // Rename the contents of a directory before the directory so the paths stay valid.
+        renames.sort_by_key(|(from, _)| std::cmp::Reverse(from.components().count()));
+        let mut targets = std::collections::HashSet::new();
+        let mut applied = Vec::new();
+        for (from, to) in renames {
+            let renamed = if targets.insert(to.clone()) {
+                report_result(rename_path(&from, &to), reporter)?
+            } else {
+                false
+            };
+            if renamed {
+                let mut modified = self.modified.lock().unwrap();
+                modified.push(from.clone());
+                modified.push(to.clone());
+                applied.push((from, to));
+            } else {
+                report_collision(&from, &to, reporter)?;
+            }
+        }
+
+        if self.update_references && !applied.is_empty() {
+            for referrer in pending.referrers {
+                let path = renamed_path(&referrer.path, &applied);
+                if update_references(&path, &applied, &referrer, reporter)? {
+                    self.modified.lock().unwrap().push(path);
                 }
             }
         }
 
         Ok(())
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-pub struct FixTypos;
+    fn defer_rename(&self, from: &std::path::Path, to: std::path::PathBuf) {
+        self.pending
+            .lock()
+            .unwrap()
+            .renames
+            .push((from.to_owned(), to));
+    }
+}
 
 impl FileChecker for FixTypos {
     fn check_file(
@@ -78,15 +227,28 @@ impl FileChecker for FixTypos {
         policy: &crate::policy::Policy,
         reporter: &dyn report::Report,
     ) -> Result<(), std::io::Error> {
-        if policy.check_files {
-            let (buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && !policy.binary && content_type.is_binary() {
+        let is_document = policy.check_files
+            && policy.documents
+            && path != std::path::Path::new("-")
+            && report_document(path, None, true, policy, reporter)?;
+        if policy.check_files && !is_document && is_notebook(path) {
+            let (buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            let edits = check_notebook(path, &buffer, true, policy, reporter)?;
+            if !edits.is_empty() {
+                let buffer = crate::notebook::splice(&buffer, edits);
+                if write_file(path, encoding, buffer, WriteOptions::new(policy), reporter)? {
+                    self.modified.lock().unwrap().push(path.to_owned());
+                }
+            }
+        } else if policy.check_files && !is_document {
+            let (buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            if !explicit && !policy.binary && encoding.is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
                 let mut fixes = Vec::new();
                 let mut accum_line_num = AccumulateLineNum::new();
-                for typo in typos::check_bytes(&buffer, policy.tokenizer, policy.dict) {
+                for (typo, severity) in check_content(&buffer, policy.markup, policy) {
                     if is_fixable(&typo) {
                         fixes.push(typo.into_owned());
                     } else {
@@ -98,48 +260,50 @@ impl FileChecker for FixTypos {
                             byte_offset: line_offset,
                             typo: typo.typo.as_ref(),
                             corrections: typo.corrections,
+                            severity,
                         };
                         reporter.report(msg.into())?;
                     }
                 }
                 if !fixes.is_empty() || path == std::path::Path::new("-") {
-                    let buffer = fix_buffer(buffer, fixes.into_iter());
-                    write_file(path, content_type, buffer, reporter)?;
+                    let buffer = fix_buffer(buffer.into_vec(), fixes.into_iter());
+                    if write_file(path, encoding, buffer, WriteOptions::new(policy), reporter)? {
+                        self.modified.lock().unwrap().push(path.to_owned());
+                    }
                 }
             }
         }
 
-        // Ensure the above write can happen before renaming the file.
+        if self.update_references && policy.check_files && path != std::path::Path::new("-") {
+            self.pending.lock().unwrap().referrers.push(Referrer {
+                path: path.to_owned(),
+                write: WriteOptions::new(policy),
+                legacy_encodings: policy.legacy_encodings,
+            });
+        }
+
         if policy.check_filenames {
-            if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                let mut fixes = Vec::new();
-                for typo in typos::check_str(file_name, policy.tokenizer, policy.dict) {
-                    if is_fixable(&typo) {
-                        fixes.push(typo.into_owned());
-                    } else {
-                        let msg = report::Typo {
-                            context: Some(report::PathContext { path }.into()),
-                            buffer: std::borrow::Cow::Borrowed(file_name.as_bytes()),
-                            byte_offset: typo.byte_offset,
-                            typo: typo.typo.as_ref(),
-                            corrections: typo.corrections,
-                        };
-                        reporter.report(msg.into())?;
-                    }
-                }
-                if !fixes.is_empty() {
-                    let file_name = file_name.to_owned().into_bytes();
-                    let new_name = fix_buffer(file_name, fixes.into_iter());
-                    let new_name =
-                        String::from_utf8(new_name).expect("corrections are valid utf-8");
-                    let new_path = path.with_file_name(new_name);
-                    std::fs::rename(path, new_path)?;
-                }
+            if let Some(new_path) = fix_file_name(path, policy, reporter)? {
+                self.defer_rename(path, new_path);
             }
         }
 
         Ok(())
     }
+
+    fn check_dir(
+        &self,
+        path: &std::path::Path,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        if policy.check_filenames {
+            if let Some(new_path) = fix_file_name(path, policy, reporter)? {
+                self.defer_rename(path, new_path);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -155,15 +319,26 @@ impl FileChecker for DiffTypos {
     ) -> Result<(), std::io::Error> {
         let mut content = Vec::new();
         let mut new_content = Vec::new();
-        if policy.check_files {
-            let (buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && !policy.binary && content_type.is_binary() {
+        let is_document = policy.check_files
+            && policy.documents
+            && path != std::path::Path::new("-")
+            && report_document(path, None, true, policy, reporter)?;
+        if policy.check_files && !is_document && is_notebook(path) {
+            let (buffer, _) = read_file(path, policy.legacy_encodings, reporter)?;
+            let edits = check_notebook(path, &buffer, true, policy, reporter)?;
+            if !edits.is_empty() {
+                new_content = crate::notebook::splice(&buffer, edits);
+                content = buffer.into_vec();
+            }
+        } else if policy.check_files && !is_document {
+            let (buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            if !explicit && !policy.binary && encoding.is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
                 let mut fixes = Vec::new();
                 let mut accum_line_num = AccumulateLineNum::new();
-                for typo in typos::check_bytes(&buffer, policy.tokenizer, policy.dict) {
+                for (typo, severity) in check_content(&buffer, policy.markup, policy) {
                     if is_fixable(&typo) {
                         fixes.push(typo.into_owned());
                     } else {
@@ -175,13 +350,14 @@ impl FileChecker for DiffTypos {
                             byte_offset: line_offset,
                             typo: typo.typo.as_ref(),
                             corrections: typo.corrections,
+                            severity,
                         };
                         reporter.report(msg.into())?;
                     }
                 }
                 if !fixes.is_empty() {
-                    new_content = fix_buffer(buffer.clone(), fixes.into_iter());
-                    content = buffer
+                    new_content = fix_buffer(buffer.to_vec(), fixes.into_iter());
+                    content = buffer.into_vec();
                 }
             }
         }
@@ -189,33 +365,17 @@ impl FileChecker for DiffTypos {
         // Match FixTypos ordering for easy diffing.
         let mut new_path = None;
         if policy.check_filenames {
-            if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                let mut fixes = Vec::new();
-                for typo in typos::check_str(file_name, policy.tokenizer, policy.dict) {
-                    if is_fixable(&typo) {
-                        fixes.push(typo.into_owned());
-                    } else {
-                        let msg = report::Typo {
-                            context: Some(report::PathContext { path }.into()),
-                            buffer: std::borrow::Cow::Borrowed(file_name.as_bytes()),
-                            byte_offset: typo.byte_offset,
-                            typo: typo.typo.as_ref(),
-                            corrections: typo.corrections,
-                        };
-                        reporter.report(msg.into())?;
-                    }
-                }
-                if !fixes.is_empty() {
-                    let file_name = file_name.to_owned().into_bytes();
-                    let new_name = fix_buffer(file_name, fixes.into_iter());
-                    let new_name =
-                        String::from_utf8(new_name).expect("corrections are valid utf-8");
-                    new_path = Some(path.with_file_name(new_name));
+            if let Some(fixed_path) = fix_file_name(path, policy, reporter)? {
+                if is_taken(path, &fixed_path) {
+                    report_collision(path, &fixed_path, reporter)?;
+                } else {
+                    print_rename(path, &fixed_path);
+                    new_path = Some(fixed_path);
                 }
             }
         }
 
-        if new_path.is_some() || !content.is_empty() {
+        if !content.is_empty() {
             let original_path = path.display().to_string();
             let fixed_path = new_path.as_deref().unwrap_or(path).display().to_string();
             let original_content: Vec<_> = content
@@ -242,6 +402,24 @@ impl FileChecker for DiffTypos {
 
         Ok(())
     }
+
+    fn check_dir(
+        &self,
+        path: &std::path::Path,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        if policy.check_filenames {
+            if let Some(fixed_path) = fix_file_name(path, policy, reporter)? {
+                if is_taken(path, &fixed_path) {
+                    report_collision(path, &fixed_path, reporter)?;
+                } else {
+                    print_rename(path, &fixed_path);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -269,8 +447,8 @@ impl FileChecker for Identifiers {
         }
 
         if policy.check_files {
-            let (buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && !policy.binary && content_type.is_binary() {
+            let (buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            if !explicit && !policy.binary && encoding.is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
@@ -309,7 +487,7 @@ impl FileChecker for Words {
                 for word in policy
                     .tokenizer
                     .parse_str(file_name)
-                    .flat_map(|i| i.split())
+                    .flat_map(|i| policy.tokenizer.split(i))
                 {
                     let msg = report::Parse {
                         context: Some(report::PathContext { path }.into()),
@@ -322,15 +500,15 @@ impl FileChecker for Words {
         }
 
         if policy.check_files {
-            let (buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && !policy.binary && content_type.is_binary() {
+            let (buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            if !explicit && !policy.binary && encoding.is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
                 for word in policy
                     .tokenizer
                     .parse_bytes(&buffer)
-                    .flat_map(|i| i.split())
+                    .flat_map(|i| policy.tokenizer.split(i))
                 {
                     // HACK: Don't look up the line_num per entry to better match the performance
                     // of Typos for comparison purposes.  We don't really get much out of it
@@ -366,8 +544,8 @@ impl FileChecker for FoundFiles {
             let msg = report::File::new(path);
             reporter.report(msg.into())?;
         } else {
-            let (_buffer, content_type) = read_file(path, reporter)?;
-            if !explicit && content_type.is_binary() {
+            let (_buffer, encoding) = read_file(path, policy.legacy_encodings, reporter)?;
+            if !explicit && encoding.is_binary() {
                 let msg = report::BinaryFile { path };
                 reporter.report(msg.into())?;
             } else {
@@ -382,176 +560,1737 @@ impl FileChecker for FoundFiles {
 
 fn read_file(
     path: &std::path::Path,
+    legacy_encodings: bool,
+    reporter: &dyn report::Report,
+) -> Result<(FileBuffer, ContentEncoding), std::io::Error> {
+    read_content(path, None, legacy_encodings, reporter)
+}
+
+/// Decode `content`, reading it from `path` when it hasn't been already.
+fn read_content(
+    path: &std::path::Path,
+    content: Option<FileBuffer>,
+    legacy_encodings: bool,
     reporter: &dyn report::Report,
-) -> Result<(Vec<u8>, content_inspector::ContentType), std::io::Error> {
-    let buffer = if path == std::path::Path::new("-") {
+) -> Result<(FileBuffer, ContentEncoding), std::io::Error> {
+    let buffer = if let Some(content) = content {
+        content
+    } else if path == std::path::Path::new("-") {
         let mut buffer = Vec::new();
         report_result(std::io::stdin().read_to_end(&mut buffer), reporter)?;
-        buffer
+        FileBuffer::Read(buffer)
     } else {
-        report_result(std::fs::read(path), reporter)?
+        report_result(FileBuffer::open(path), reporter)?
     };
 
-    let content_type = content_inspector::inspect(&buffer);
-
-    let (buffer, content_type) = match content_type {
-        content_inspector::ContentType::BINARY |
-        // HACK: We don't support UTF-32 yet
-        content_inspector::ContentType::UTF_32LE |
-        content_inspector::ContentType::UTF_32BE => {
-            (buffer, content_inspector::ContentType::BINARY)
-        },
-        content_inspector::ContentType::UTF_8 |
-        content_inspector::ContentType::UTF_8_BOM => {
-            (buffer, content_type)
-        },
-        content_inspector::ContentType::UTF_16LE => {
-            let buffer = report_result(encoding::all::UTF_16LE.decode(&buffer, encoding::DecoderTrap::Strict), reporter)?;
-            (buffer.into_bytes(), content_type)
-        }
-        content_inspector::ContentType::UTF_16BE => {
-            let buffer = report_result(encoding::all::UTF_16BE.decode(&buffer, encoding::DecoderTrap::Strict), reporter)?;
-            (buffer.into_bytes(), content_type)
-        },
-    };
+    let encoding = ContentEncoding::inspect(&buffer, legacy_encodings);
+    let buffer = report_result(encoding.decode(buffer), reporter)?;
 
-    Ok((buffer, content_type))
+    Ok((buffer, encoding))
 }
 
-fn write_file(
-    path: &std::path::Path,
-    content_type: content_inspector::ContentType,
-    buffer: Vec<u8>,
-    reporter: &dyn report::Report,
-) -> Result<(), std::io::Error> {
-    let buffer = match content_type {
-        // HACK: We don't support UTF-32 yet
-        content_inspector::ContentType::UTF_32LE | content_inspector::ContentType::UTF_32BE => {
-            unreachable!("read_file should prevent these from being passed along");
-        }
-        content_inspector::ContentType::BINARY
-        | content_inspector::ContentType::UTF_8
-        | content_inspector::ContentType::UTF_8_BOM => buffer,
-        content_inspector::ContentType::UTF_16LE => {
-            let buffer = report_result(String::from_utf8(buffer), reporter)?;
-            if buffer.is_empty() {
-                // Error occurred, don't clear out the file
-                return Ok(());
-            }
-            report_result(
-                encoding::all::UTF_16LE.encode(&buffer, encoding::EncoderTrap::Strict),
-                reporter,
-            )?
-        }
-        content_inspector::ContentType::UTF_16BE => {
-            let buffer = report_result(String::from_utf8(buffer), reporter)?;
-            if buffer.is_empty() {
-                // Error occurred, don't clear out the file
-                return Ok(());
+/// How a file's bytes map to the UTF-8 that gets checked, so fixes can be written back the same
+/// way.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ContentEncoding {
+    Binary,
+    /// Checked as-is, even when it isn't valid UTF-8
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    /// Only detected with `legacy-encodings`
+    Legacy(LegacyEncoding),
+}
+
+impl ContentEncoding {
+    /// Classify content by its prefix.
+    fn inspect(buffer: &[u8], legacy_encodings: bool) -> Self {
+        match content_inspector::inspect(buffer) {
+            content_inspector::ContentType::BINARY => Self::Binary,
+            content_inspector::ContentType::UTF_8 => {
+                let prefix = &buffer[..buffer.len().min(CHUNK_SIZE)];
+                if legacy_encodings && !is_utf8_prefix(prefix) {
+                    Self::Legacy(LegacyEncoding::detect(prefix))
+                } else {
+                    Self::Utf8
+                }
             }
-            report_result(
-                encoding::all::UTF_16BE.encode(&buffer, encoding::EncoderTrap::Strict),
-                reporter,
-            )?
+            content_inspector::ContentType::UTF_8_BOM => Self::Utf8Bom,
+            content_inspector::ContentType::UTF_16LE => Self::Utf16Le,
+            content_inspector::ContentType::UTF_16BE => Self::Utf16Be,
+            content_inspector::ContentType::UTF_32LE => Self::Utf32Le,
+            content_inspector::ContentType::UTF_32BE => Self::Utf32Be,
         }
-    };
+    }
 
-    if path == std::path::Path::new("-") {
-        report_result(std::io::stdout().write_all(&buffer), reporter)?;
-    } else {
-        report_result(std::fs::write(path, buffer), reporter)?;
+    fn is_binary(self) -> bool {
+        self == Self::Binary
     }
 
-    Ok(())
-}
+    /// The byte order mark that was stripped from the content.
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Self::Utf8Bom => b"\xEF\xBB\xBF",
+            Self::Utf16Le => b"\xFF\xFE",
+            Self::Utf16Be => b"\xFE\xFF",
+            Self::Utf32Le => b"\xFF\xFE\x00\x00",
+            Self::Utf32Be => b"\x00\x00\xFE\xFF",
+            Self::Binary | Self::Utf8 | Self::Legacy(_) => b"",
+        }
+    }
 
-fn report_result<T: Default, E: ToString>(
-    value: Result<T, E>,
-    reporter: &dyn report::Report,
-) -> Result<T, std::io::Error> {
-    let buffer = match value {
-        Ok(value) => value,
-        Err(err) => {
-            report_error(err, reporter)?;
-            Default::default()
+    /// What to transcode the content with, `None` when it is used as-is.
+    fn encoding(self) -> Option<encoding::EncodingRef> {
+        match self {
+            Self::Binary | Self::Utf8 | Self::Utf8Bom => None,
+            Self::Utf16Le => Some(encoding::all::UTF_16LE),
+            Self::Utf16Be => Some(encoding::all::UTF_16BE),
+            Self::Utf32Le => Some(&UTF_32LE),
+            Self::Utf32Be => Some(&UTF_32BE),
+            Self::Legacy(legacy) => Some(legacy.encoding()),
         }
-    };
-    Ok(buffer)
+    }
+
+    /// Convert the file's bytes to UTF-8.
+    ///
+    /// Fails if the bytes couldn't be written back exactly as they were.
+    fn decode(self, buffer: FileBuffer) -> Result<FileBuffer, String> {
+        let content = &buffer[self.bom().len()..];
+        match self.encoding() {
+            Some(encoding) => {
+                let decoded = encoding
+                    .decode(content, encoding::DecoderTrap::Strict)
+                    .map_err(|err| format!("invalid {}: {}", encoding.name(), err))?;
+                // Some encodings have several ways of writing the same character
+                let encoded = encoding.encode(&decoded, encoding::EncoderTrap::Strict);
+                if encoded.as_deref() != Ok(content) {
+                    return Err(format!("can't preserve the {} encoding", encoding.name()));
+                }
+                Ok(FileBuffer::Read(decoded.into_bytes()))
+            }
+            None if self.bom().is_empty() => Ok(buffer),
+            None => Ok(FileBuffer::Read(content.to_vec())),
+        }
+    }
+
+    /// Convert UTF-8 back to the file's bytes.
+    fn encode(self, buffer: Vec<u8>) -> Result<Vec<u8>, String> {
+        let content = match self.encoding() {
+            Some(encoding) => {
+                let buffer = String::from_utf8(buffer).map_err(|err| err.to_string())?;
+                encoding
+                    .encode(&buffer, encoding::EncoderTrap::Strict)
+                    .map_err(|err| format!("can't write as {}: {}", encoding.name(), err))?
+            }
+            None => buffer,
+        };
+        if self.bom().is_empty() {
+            Ok(content)
+        } else {
+            let mut buffer = self.bom().to_vec();
+            buffer.extend_from_slice(&content);
+            Ok(buffer)
+        }
+    }
 }
 
-fn report_error<E: ToString>(err: E, reporter: &dyn report::Report) -> Result<(), std::io::Error> {
-    let msg = report::Error::new(err.to_string());
-    reporter.report(msg.into())?;
-    Ok(())
+/// Whether `buffer` is valid UTF-8, except for a character cut off at the end.
+fn is_utf8_prefix(buffer: &[u8]) -> bool {
+    match std::str::from_utf8(buffer) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
 }
 
-struct AccumulateLineNum {
-    line_num: usize,
-    last_offset: usize,
+/// Text encodings from before UTF-8 took over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LegacyEncoding {
+    ShiftJis,
+    Windows1252,
+    Latin1,
 }
 
-impl AccumulateLineNum {
-    fn new() -> Self {
-        Self {
-            // 1-indexed
-            line_num: 1,
-            last_offset: 0,
+impl LegacyEncoding {
+    /// Guess the encoding of text that isn't UTF-8.
+    fn detect(buffer: &[u8]) -> Self {
+        // Latin text rarely passes for Shift-JIS: accented letters are usually followed by
+        // bytes that can't continue a Shift-JIS character, or else decode as half-width katakana.
+        let is_shift_jis = decode_prefix(encoding::all::WINDOWS_31J, buffer)
+            .map(|s| !s.chars().any(|c| ('\u{FF61}'..='\u{FF9F}').contains(&c)))
+            .unwrap_or(false);
+        if is_shift_jis {
+            Self::ShiftJis
+        } else if buffer.iter().any(|b| (0x80..=0x9F).contains(b)) {
+            // Only printable in Windows-1252
+            Self::Windows1252
+        } else {
+            Self::Latin1
         }
     }
 
-    fn line_num(&mut self, buffer: &[u8], byte_offset: usize) -> usize {
-        assert!(self.last_offset <= byte_offset);
-        let slice = &buffer[self.last_offset..byte_offset];
-        let newlines = slice.find_iter(b"\n").count();
-        let line_num = self.line_num + newlines;
-        self.line_num = line_num;
-        self.last_offset = byte_offset;
-        line_num
+    fn encoding(self) -> encoding::EncodingRef {
+        match self {
+            Self::ShiftJis => encoding::all::WINDOWS_31J,
+            Self::Windows1252 => encoding::all::WINDOWS_1252,
+            Self::Latin1 => encoding::all::ISO_8859_1,
+        }
     }
 }
 
-fn extract_line(buffer: &[u8], byte_offset: usize) -> (&[u8], usize) {
-    let line_start = buffer[0..byte_offset]
-        .rfind_byte(b'\n')
-        // Skip the newline
-        .map(|s| s + 1)
-        .unwrap_or(0);
-    let line = buffer[line_start..]
-        .lines()
-        .next()
-        .expect("should always be at least a line");
-    let line_offset = byte_offset - line_start;
-    (line, line_offset)
-}
-
-fn extract_fix<'t>(typo: &'t typos::Typo<'t>) -> Option<&'t str> {
-    match &typo.corrections {
-        typos::Status::Corrections(c) if c.len() == 1 => Some(c[0].as_ref()),
-        _ => None,
+/// Decode `buffer`, ignoring a character cut off at the end.
+fn decode_prefix(encoding: encoding::EncodingRef, buffer: &[u8]) -> Option<String> {
+    let mut decoded = String::new();
+    let (_, error) = encoding.raw_decoder().raw_feed(buffer, &mut decoded);
+    match error {
+        Some(_) => None,
+        None => Some(decoded),
     }
 }
 
-fn is_fixable(typo: &typos::Typo<'_>) -> bool {
-    extract_fix(typo).is_some()
+/// UTF-32, which `encoding` doesn't support.
+struct Utf32 {
+    big_endian: bool,
 }
 
-fn fix_buffer(mut buffer: Vec<u8>, typos: impl Iterator<Item = typos::Typo<'static>>) -> Vec<u8> {
-    let mut offset = 0isize;
-    for typo in typos {
-        let fix = extract_fix(&typo).expect("Caller only provides fixable typos");
-        let start = ((typo.byte_offset as isize) + offset) as usize;
-        let end = start + typo.typo.len();
+static UTF_32LE: Utf32 = Utf32 { big_endian: false };
+static UTF_32BE: Utf32 = Utf32 { big_endian: true };
 
-        buffer.splice(start..end, fix.as_bytes().iter().copied());
+impl encoding::Encoding for Utf32 {
+    fn name(&self) -> &'static str {
+        if self.big_endian {
+            "utf-32be"
+        } else {
+            "utf-32le"
+        }
+    }
 
-        offset += (fix.len() as isize) - (typo.typo.len() as isize);
+    fn raw_encoder(&self) -> Box<dyn encoding::RawEncoder> {
+        Box::new(Utf32Encoder {
+            big_endian: self.big_endian,
+        })
+    }
+
+    fn raw_decoder(&self) -> Box<dyn encoding::RawDecoder> {
+        Box::new(Utf32Decoder {
+            big_endian: self.big_endian,
+            unit: [0; 4],
+            unit_len: 0,
+        })
     }
-    buffer
 }
 
-pub fn walk_path(
-    walk: ignore::Walk,
-    checks: &dyn FileChecker,
+struct Utf32Encoder {
+    big_endian: bool,
+}
+
+impl encoding::RawEncoder for Utf32Encoder {
+    fn from_self(&self) -> Box<dyn encoding::RawEncoder> {
+        Box::new(Utf32Encoder {
+            big_endian: self.big_endian,
+        })
+    }
+
+    fn raw_feed(
+        &mut self,
+        input: &str,
+        output: &mut dyn encoding::ByteWriter,
+    ) -> (usize, Option<encoding::CodecError>) {
+        output.writer_hint(input.len() * 4);
+        for c in input.chars() {
+            let bytes = if self.big_endian {
+                u32::from(c).to_be_bytes()
+            } else {
+                u32::from(c).to_le_bytes()
+            };
+            output.write_bytes(&bytes);
+        }
+        (input.len(), None)
+    }
+
+    fn raw_finish(
+        &mut self,
+        _output: &mut dyn encoding::ByteWriter,
+    ) -> Option<encoding::CodecError> {
+        None
+    }
+}
+
+struct Utf32Decoder {
+    big_endian: bool,
+    /// A code unit split across calls to `raw_feed`
+    unit: [u8; 4],
+    unit_len: usize,
+}
+
+impl encoding::RawDecoder for Utf32Decoder {
+    fn from_self(&self) -> Box<dyn encoding::RawDecoder> {
+        Box::new(Utf32Decoder {
+            big_endian: self.big_endian,
+            unit: [0; 4],
+            unit_len: 0,
+        })
+    }
+
+    fn raw_feed(
+        &mut self,
+        input: &[u8],
+        output: &mut dyn encoding::StringWriter,
+    ) -> (usize, Option<encoding::CodecError>) {
+        output.writer_hint(input.len() / 4);
+        let mut processed = 0;
+        for (i, b) in input.iter().enumerate() {
+            self.unit[self.unit_len] = *b;
+            self.unit_len += 1;
+            if self.unit_len < self.unit.len() {
+                continue;
+            }
+            self.unit_len = 0;
+
+            let value = if self.big_endian {
+                u32::from_be_bytes(self.unit)
+            } else {
+                u32::from_le_bytes(self.unit)
+            };
+            match std::char::from_u32(value) {
+                Some(c) => {
+                    output.write_char(c);
+                    processed = i + 1;
+                }
+                None => {
+                    let error = encoding::CodecError {
+                        upto: (i + 1) as isize,
+                        cause: "invalid sequence".into(),
+                    };
+                    return (processed, Some(error));
+                }
+            }
+        }
+        (processed, None)
+    }
+
+    fn raw_finish(
+        &mut self,
+        _output: &mut dyn encoding::StringWriter,
+    ) -> Option<encoding::CodecError> {
+        if self.unit_len == 0 {
+            None
+        } else {
+            self.unit_len = 0;
+            Some(encoding::CodecError {
+                upto: 0,
+                cause: "incomplete sequence".into(),
+            })
+        }
+    }
+}
+
+/// Files at least this large are memory-mapped rather than copied into a buffer.
+const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// The content of a file, either read into memory or memory-mapped.
+pub enum FileBuffer {
+    Read(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl FileBuffer {
+    /// Read `path`, memory-mapping it when it is large enough to be worth it.
+    pub fn open(path: &std::path::Path) -> Result<Self, std::io::Error> {
+        let mut file = std::fs::File::open(path)?;
+        if let Some(mapped) = map_file(&file)? {
+            Ok(FileBuffer::Mapped(mapped))
+        } else {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)?;
+            Ok(FileBuffer::Read(buffer))
+        }
+    }
+
+    /// Read all of `path` into memory.
+    pub fn read(path: &std::path::Path) -> Result<Self, std::io::Error> {
+        std::fs::read(path).map(FileBuffer::Read)
+    }
+
+    /// Memory-map `path`, regardless of its size.
+    pub fn map(path: &std::path::Path) -> Result<Self, std::io::Error> {
+        let file = std::fs::File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            // Empty files can't be mapped
+            return Ok(FileBuffer::Read(Vec::new()));
+        }
+        // Safe as long as the file isn't truncated while mapped, same as `ripgrep` assumes.
+        let mapped = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(FileBuffer::Mapped(mapped))
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        match self {
+            FileBuffer::Read(buffer) => buffer,
+            FileBuffer::Mapped(mapped) => mapped.to_vec(),
+        }
+    }
+}
+
+impl std::ops::Deref for FileBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBuffer::Read(buffer) => buffer,
+            FileBuffer::Mapped(mapped) => mapped,
+        }
+    }
+}
+
+impl AsRef<[u8]> for FileBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Default for FileBuffer {
+    fn default() -> Self {
+        FileBuffer::Read(Vec::new())
+    }
+}
+
+fn map_file(file: &std::fs::File) -> Result<Option<memmap2::Mmap>, std::io::Error> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() < MMAP_THRESHOLD {
+        return Ok(None);
+    }
+
+    // Safe as long as the file isn't truncated while mapped, same as `ripgrep` assumes.
+    //
+    // Fallback to reading the file if it can't be mapped.
+    let mapped = unsafe { memmap2::Mmap::map(file) }.ok();
+    Ok(mapped)
+}
+
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Force a chunk boundary when a line grows beyond this, rather than buffering it all.
+const MAX_LINE_SIZE: usize = 1024 * 1024;
+
+/// Incrementally read a file as UTF-8 chunks that end on line boundaries.
+///
+/// Identifiers, UTF-8 sequences, and lines never span chunks, except for lines longer than
+/// `MAX_LINE_SIZE` which get split between identifiers.  Large UTF-8 files are memory-mapped and
+/// returned as a single chunk, as is content that was already read.
+struct ChunkReader {
+    reader: Box<dyn Read>,
+    whole: Option<FileBuffer>,
+    encoding: ContentEncoding,
+    decoder: Option<Box<dyn encoding::RawDecoder>>,
+    read_buffer: Vec<u8>,
+    pending: Vec<u8>,
+    eof: bool,
+}
+
+impl ChunkReader {
+    fn open(path: &std::path::Path, legacy_encodings: bool) -> Result<Self, std::io::Error> {
+        if path == std::path::Path::new("-") {
+            return Self::new(Box::new(std::io::stdin()), CHUNK_SIZE, legacy_encodings);
+        }
+
+        let file = std::fs::File::open(path)?;
+        if let Some(mapped) = map_file(&file)? {
+            return Self::from_buffer(FileBuffer::Mapped(mapped), legacy_encodings);
+        }
+        Self::new(Box::new(file), CHUNK_SIZE, legacy_encodings)
+    }
+
+    fn from_buffer(buffer: FileBuffer, legacy_encodings: bool) -> Result<Self, std::io::Error> {
+        let encoding = ContentEncoding::inspect(&buffer, legacy_encodings);
+        if !matches!(encoding, ContentEncoding::Binary | ContentEncoding::Utf8) {
+            // Still decode incrementally
+            let reader = Box::new(std::io::Cursor::new(buffer));
+            return Self::new(reader, CHUNK_SIZE, legacy_encodings);
+        }
+        Ok(Self {
+            reader: Box::new(std::io::empty()),
+            whole: Some(buffer),
+            encoding,
+            decoder: None,
+            read_buffer: Vec::new(),
+            pending: Vec::new(),
+            eof: false,
+        })
+    }
+
+    fn new(
+        mut reader: Box<dyn Read>,
+        chunk_size: usize,
+        legacy_encodings: bool,
+    ) -> Result<Self, std::io::Error> {
+        let mut read_buffer = vec![0; chunk_size];
+        let len = read_some(&mut reader, &mut read_buffer)?;
+        let raw = &read_buffer[..len];
+
+        let encoding = ContentEncoding::inspect(raw, legacy_encodings);
+        let decoder = encoding.encoding().map(|e| e.raw_decoder());
+
+        let mut chunks = Self {
+            reader,
+            whole: None,
+            encoding,
+            decoder,
+            read_buffer,
+            pending: Vec::new(),
+            eof: len == 0,
+        };
+        chunks.decode(encoding.bom().len()..len)?;
+        Ok(chunks)
+    }
+
+    fn encoding(&self) -> ContentEncoding {
+        self.encoding
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Cow<'_, [u8]>>, std::io::Error> {
+        if self.whole.is_some() {
+            if self.eof {
+                return Ok(None);
+            } else {
+                self.eof = true;
+                return Ok(self.whole.as_deref().map(Cow::Borrowed));
+            }
+        }
+
+        loop {
+            if self.eof {
+                if self.pending.is_empty() {
+                    return Ok(None);
+                } else {
+                    return Ok(Some(Cow::Owned(std::mem::take(&mut self.pending))));
+                }
+            }
+
+            if let Some(end) = self.pending.rfind_byte(b'\n') {
+                let rest = self.pending.split_off(end + 1);
+                return Ok(Some(Cow::Owned(std::mem::replace(&mut self.pending, rest))));
+            }
+
+            if MAX_LINE_SIZE <= self.pending.len() {
+                if let Some(end) = self.pending.iter().rposition(|b| is_chunk_boundary(*b)) {
+                    let rest = self.pending.split_off(end + 1);
+                    return Ok(Some(Cow::Owned(std::mem::replace(&mut self.pending, rest))));
+                }
+            }
+
+            let len = read_some(&mut self.reader, &mut self.read_buffer)?;
+            self.eof = len == 0;
+            self.decode(0..len)?;
+        }
+    }
+
+    fn decode(&mut self, range: std::ops::Range<usize>) -> Result<(), std::io::Error> {
+        let raw = &self.read_buffer[range];
+        if let Some(decoder) = self.decoder.as_mut() {
+            let mut decoded = String::new();
+            let (_, error) = decoder.raw_feed(raw, &mut decoded);
+            let error = match error {
+                Some(error) => Some(error),
+                None if self.eof => decoder.raw_finish(&mut decoded),
+                None => None,
+            };
+            if let Some(error) = error {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    error.cause.into_owned(),
+                ));
+            }
+            self.pending.extend_from_slice(decoded.as_bytes());
+        } else {
+            self.pending.extend_from_slice(raw);
+        }
+        Ok(())
+    }
+}
+
+fn read_some(reader: &mut dyn Read, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
+    loop {
+        match reader.read(buffer) {
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+/// Whether a chunk can end after `b` without splitting an identifier or a UTF-8 sequence.
+fn is_chunk_boundary(b: u8) -> bool {
+    b.is_ascii() && !b.is_ascii_alphanumeric() && b != b'_'
+}
+
+/// How `replace_file` treats the file being fixed.
+#[derive(Copy, Clone, Debug)]
+struct WriteOptions {
+    fix_symlinks: bool,
+    preserve_mtime: bool,
+}
+
+impl WriteOptions {
+    fn new(policy: &crate::policy::Policy) -> Self {
+        Self {
+            fix_symlinks: policy.fix_symlinks,
+            preserve_mtime: policy.preserve_mtime,
+        }
+    }
+}
+
+/// Returns whether `path` was changed.
+fn write_file(
+    path: &std::path::Path,
+    encoding: ContentEncoding,
+    buffer: Vec<u8>,
+    options: WriteOptions,
+    reporter: &dyn report::Report,
+) -> Result<bool, std::io::Error> {
+    if buffer.is_empty() && encoding.encoding().is_some() {
+        // Error occurred while decoding, don't clear out the file
+        return Ok(false);
+    }
+    let buffer = match encoding.encode(buffer) {
+        Ok(buffer) => buffer,
+        Err(err) => {
+            let msg = report::Error::new(format!("not fixed: {}", err))
+                .context(Some(report::PathContext { path }.into()));
+            reporter.report(msg.into())?;
+            return Ok(false);
+        }
+    };
+
+    if path == std::path::Path::new("-") {
+        report_result(std::io::stdout().write_all(&buffer), reporter)?;
+        Ok(false)
+    } else if let Err(err) = replace_file(path, &buffer, options) {
+        let msg = report::Error::new(format!("not fixed: {}", err))
+            .context(Some(report::PathContext { path }.into()));
+        reporter.report(msg.into())?;
+        Ok(false)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Replace the content of `path` without ever leaving it partially written.
+///
+/// The new content is written to a temporary file next to the target which is then renamed
+/// over it, keeping the original permissions.  Files with other hard links are instead
+/// overwritten in place so the links keep sharing the content.  The modification time is
+/// updated, like any other edit, unless `preserve_mtime`.
+fn replace_file(
+    path: &std::path::Path,
+    buffer: &[u8],
+    options: WriteOptions,
+) -> Result<(), std::io::Error> {
+    let mut path = std::borrow::Cow::Borrowed(path);
+    if std::fs::symlink_metadata(&path)?.file_type().is_symlink() {
+        if !options.fix_symlinks {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "it is a symlink, see `fix-symlinks`",
+            ));
+        }
+        path = std::borrow::Cow::Owned(path.canonicalize()?);
+    }
+    let metadata = std::fs::metadata(&path)?;
+    let modified = if options.preserve_mtime {
+        Some(metadata.modified()?)
+    } else {
+        None
+    };
+    if has_other_links(&metadata) {
+        // Renaming over it would split it off from its other links
+        return write_in_place(&path, buffer, modified);
+    }
+
+    write_atomic(&path, buffer, metadata.permissions(), modified)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_atomic(
+    path: &std::path::Path,
+    buffer: &[u8],
+    permissions: std::fs::Permissions,
+    modified: Option<std::time::SystemTime>,
+) -> Result<(), std::io::Error> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let mut temp = tempfile::Builder::new()
+        .prefix(".typos-")
+        .tempfile_in(dir)?;
+    temp.write_all(buffer)?;
+    temp.as_file().set_permissions(permissions)?;
+    if let Some(modified) = modified {
+        set_modified(temp.as_file(), modified)?;
+    }
+    temp.as_file().sync_all()?;
+    temp.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_atomic(
+    path: &std::path::Path,
+    buffer: &[u8],
+    _permissions: std::fs::Permissions,
+    modified: Option<std::time::SystemTime>,
+) -> Result<(), std::io::Error> {
+    // No `tempfile` without a source of randomness
+    write_in_place(path, buffer, modified)
+}
+
+/// Truncate and rewrite `path`, which isn't atomic but keeps its identity.
+fn write_in_place(
+    path: &std::path::Path,
+    buffer: &[u8],
+    modified: Option<std::time::SystemTime>,
+) -> Result<(), std::io::Error> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(buffer)?;
+    if let Some(modified) = modified {
+        set_modified(&file, modified)?;
+    }
+    file.sync_all()
+}
+
+/// Set the modification time of `file`, leaving its access time alone.
+fn set_modified(
+    file: &std::fs::File,
+    modified: std::time::SystemTime,
+) -> Result<(), std::io::Error> {
+    let modified = filetime::FileTime::from_system_time(modified);
+    filetime::set_file_handle_times(file, None, Some(modified))
+}
+
+#[cfg(unix)]
+fn has_other_links(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() > 1
+}
+
+#[cfg(not(unix))]
+fn has_other_links(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+fn report_result<T: Default, E: ToString>(
+    value: Result<T, E>,
+    reporter: &dyn report::Report,
+) -> Result<T, std::io::Error> {
+    let buffer = match value {
+        Ok(value) => value,
+        Err(err) => {
+            report_error(err, reporter)?;
+            Default::default()
+        }
+    };
+    Ok(buffer)
+}
+
+fn report_error<E: ToString>(err: E, reporter: &dyn report::Report) -> Result<(), std::io::Error> {
+    let msg = report::Error::new(err.to_string());
+    reporter.report(msg.into())?;
+    Ok(())
+}
+
+struct AccumulateLineNum {
+    line_num: usize,
+    last_offset: usize,
+}
+
+impl AccumulateLineNum {
+    fn new() -> Self {
+        Self {
+            // 1-indexed
+            line_num: 1,
+            last_offset: 0,
+        }
+    }
+
+    fn line_num(&mut self, buffer: &[u8], byte_offset: usize) -> usize {
+        assert!(self.last_offset <= byte_offset);
+        let slice = &buffer[self.last_offset..byte_offset];
+        let newlines = slice.find_iter(b"\n").count();
+        let line_num = self.line_num + newlines;
+        self.line_num = line_num;
+        self.last_offset = byte_offset;
+        line_num
+    }
+
+    /// Account for the rest of `buffer` before moving on to the next chunk.
+    fn next_chunk(&mut self, buffer: &[u8]) {
+        self.line_num(buffer, buffer.len());
+        self.last_offset = 0;
+    }
+}
+
+fn extract_line(buffer: &[u8], byte_offset: usize) -> (&[u8], usize) {
+    let line_start = buffer[0..byte_offset]
+        .rfind_byte(b'\n')
+        // Skip the newline
+        .map(|s| s + 1)
+        .unwrap_or(0);
+    let line = buffer[line_start..]
+        .lines()
+        .next()
+        .expect("should always be at least a line");
+    let line_offset = byte_offset - line_start;
+    (line, line_offset)
+}
+
+fn extract_fix<'t>(typo: &'t typos::Typo<'t>) -> Option<&'t str> {
+    match &typo.corrections {
+        typos::Status::Corrections(c) if c.len() == 1 => Some(c[0].as_ref()),
+        _ => None,
+    }
+}
+
+/// Check the text extracted from a document, returning `false` if `path` isn't one.
+///
+/// When `fixing`, typos are reported as not fixed since documents can't be written back.
+fn report_document(
+    path: &std::path::Path,
+    content: Option<&[u8]>,
+    fixing: bool,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<bool, std::io::Error> {
+    // Leave it to the regular check to report errors opening the file
+    let opened;
+    let buffer = match content {
+        Some(content) => content,
+        None => {
+            match read_prefix(path, 4) {
+                Some(prefix) if crate::extract::is_candidate(&prefix) => {}
+                _ => return Ok(false),
+            }
+            opened = match FileBuffer::open(path) {
+                Ok(buffer) => buffer,
+                Err(_) => return Ok(false),
+            };
+            &opened[..]
+        }
+    };
+    if !crate::extract::is_candidate(buffer) {
+        return Ok(false);
+    }
+    let extractor = match crate::extract::find(buffer) {
+        Some(extractor) => extractor,
+        None => return Ok(false),
+    };
+    let sections = match extractor.extract(buffer) {
+        Ok(sections) => sections,
+        Err(err) => {
+            let err = format!("could not read {} document: {}", extractor.name(), err);
+            report_path_error(path, err, reporter)?;
+            return Ok(true);
+        }
+    };
+
+    let status = CorrectionStatus::new(reporter);
+    for section in sections.iter() {
+        for (typo, severity) in policy.check_str(&section.text) {
+            let msg = report::Typo {
+                context: Some(
+                    report::DocumentContext {
+                        path,
+                        part: section.part.as_deref(),
+                        page: section.page,
+                        paragraph: section.paragraph,
+                    }
+                    .into(),
+                ),
+                buffer: std::borrow::Cow::Borrowed(section.text.as_bytes()),
+                byte_offset: typo.byte_offset,
+                typo: typo.typo.as_ref(),
+                corrections: typo.corrections,
+                severity,
+            };
+            report::Report::report(&status, msg.into())?;
+        }
+    }
+    if fixing && status.found() {
+        let err = format!("not fixed: {} documents can't be fixed", extractor.name());
+        report_path_error(path, err, reporter)?;
+    }
+    Ok(true)
+}
+
+fn is_notebook(path: &std::path::Path) -> bool {
+    path.extension().map(|ext| ext == "ipynb").unwrap_or(false)
+}
+
+/// Check the cells of a Jupyter notebook, returning the edits to its JSON strings that fix the
+/// typos in their source when `fixing`.
+///
+/// Markdown cells are checked as prose and code cells with the settings for the notebook's
+/// language.  Typos in outputs are only ever reported.
+fn check_notebook(
+    path: &std::path::Path,
+    buffer: &[u8],
+    fixing: bool,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<Vec<(std::ops::Range<usize>, String)>, std::io::Error> {
+    let notebook = std::str::from_utf8(buffer)
+        .map_err(|err| err.to_string())
+        .and_then(|buffer| crate::notebook::Notebook::parse(buffer).map_err(|err| err.to_string()));
+    let notebook = match notebook {
+        Ok(notebook) => notebook,
+        Err(err) => {
+            let err = format!("could not read notebook: {}", err);
+            report_path_error(path, err, reporter)?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let code_policy = match (notebook.language.as_deref(), policy.code_types) {
+        (Some(lang), Some(code_types)) => code_types.policy(lang),
+        _ => None,
+    };
+    // Code in languages without a type of their own is checked like the notebook
+    let code_policy = code_policy.as_ref().unwrap_or(policy);
+
+    let mut edits = Vec::new();
+    for (i, cell) in notebook.cells.iter().enumerate() {
+        let cell_num = i + 1;
+        let source = cell.source.text.as_bytes();
+        let typos = match cell.kind {
+            crate::notebook::CellKind::Markdown => {
+                check_content(source, crate::config::Markup::Markdown, policy)
+            }
+            crate::notebook::CellKind::Code if code_policy.check_files => {
+                check_content(source, crate::config::Markup::Plain, code_policy)
+            }
+            crate::notebook::CellKind::Code => Vec::new(),
+            crate::notebook::CellKind::Raw => {
+                check_content(source, crate::config::Markup::Plain, policy)
+            }
+        };
+
+        let mut fixes = Vec::new();
+        let mut accum_line_num = AccumulateLineNum::new();
+        for (typo, severity) in typos {
+            // Phrases and repeated words can span lines, and so the JSON strings holding them,
+            // which can't be edited independently
+            let end = typo.byte_offset + typo.typo.len();
+            let in_one_piece = cell
+                .source
+                .pieces
+                .iter()
+                .any(|piece| piece.text.start <= typo.byte_offset && end <= piece.text.end);
+            if fixing && is_fixable(&typo) && in_one_piece {
+                fixes.push(typo.into_owned());
+            } else {
+                let line_num = accum_line_num.line_num(source, typo.byte_offset);
+                let (line, line_offset) = extract_line(source, typo.byte_offset);
+                let context = report::NotebookContext {
+                    path,
+                    cell: cell_num,
+                    output: None,
+                    line_num,
+                };
+                let msg = report::Typo {
+                    context: Some(context.into()),
+                    buffer: std::borrow::Cow::Borrowed(line),
+                    byte_offset: line_offset,
+                    typo: typo.typo.as_ref(),
+                    corrections: typo.corrections,
+                    severity,
+                };
+                reporter.report(msg.into())?;
+            }
+        }
+        // Each fix is within a single JSON string
+        for piece in cell.source.pieces.iter() {
+            let piece_fixes: Vec<_> = fixes
+                .iter()
+                .filter(|typo| piece.text.contains(&typo.byte_offset))
+                .map(|typo| typos::Typo {
+                    byte_offset: typo.byte_offset - piece.text.start,
+                    ..typo.clone()
+                })
+                .collect();
+            if !piece_fixes.is_empty() {
+                let text = cell.source.text[piece.text.clone()].as_bytes().to_vec();
+                let text = fix_buffer(text, piece_fixes.into_iter());
+                let text = String::from_utf8(text).expect("corrections are valid utf-8");
+                edits.push((piece.raw.clone(), text));
+            }
+        }
+
+        if policy.notebook_outputs {
+            for (i, output) in cell.outputs.iter().enumerate() {
+                let output_num = i + 1;
+                let text = output.text.as_bytes();
+                let mut accum_line_num = AccumulateLineNum::new();
+                for (typo, severity) in check_content(text, crate::config::Markup::Plain, policy) {
+                    let line_num = accum_line_num.line_num(text, typo.byte_offset);
+                    let (line, line_offset) = extract_line(text, typo.byte_offset);
+                    let context = report::NotebookContext {
+                        path,
+                        cell: cell_num,
+                        output: Some(output_num),
+                        line_num,
+                    };
+                    let msg = report::Typo {
+                        context: Some(context.into()),
+                        buffer: std::borrow::Cow::Borrowed(line),
+                        byte_offset: line_offset,
+                        typo: typo.typo.as_ref(),
+                        corrections: typo.corrections,
+                        severity,
+                    };
+                    reporter.report(msg.into())?;
+                }
+            }
+        }
+    }
+    Ok(edits)
+}
+
+/// Forward messages, noting whether any were corrections.
+pub(crate) struct CorrectionStatus<'r> {
+    found: std::sync::atomic::AtomicBool,
+    reporter: &'r dyn report::Report,
+}
+
+impl<'r> CorrectionStatus<'r> {
+    pub(crate) fn new(reporter: &'r dyn report::Report) -> Self {
+        Self {
+            found: std::sync::atomic::AtomicBool::new(false),
+            reporter,
+        }
+    }
+
+    pub(crate) fn found(&self) -> bool {
+        self.found.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl<'r> report::Report for CorrectionStatus<'r> {
+    fn report(&self, msg: report::Message) -> Result<(), std::io::Error> {
+        if msg.is_correction() {
+            self.found.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+        self.reporter.report(msg)
+    }
+}
+
+/// Decompressed content beyond this isn't checked, guarding against decompression bombs.
+///
+/// This bounds an archive's decompressed stream and, separately, all of its members together.
+const MAX_ARCHIVE_CONTENT: u64 = 64 * 1024 * 1024;
+
+/// How much more decompressed content an archive may produce.
+struct Budget(std::cell::Cell<u64>);
+
+impl Budget {
+    fn new() -> Self {
+        Self(std::cell::Cell::new(MAX_ARCHIVE_CONTENT))
+    }
+
+    fn spend(&self, len: usize) -> Result<(), std::io::Error> {
+        let remaining = self.0.get().checked_sub(len as u64).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("more than {} bytes once decompressed", MAX_ARCHIVE_CONTENT),
+            )
+        })?;
+        self.0.set(remaining);
+        Ok(())
+    }
+}
+
+/// Compression undone before checking the content.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    fn inspect(buffer: &[u8]) -> Option<Self> {
+        if buffer.starts_with(b"\x1f\x8b") {
+            Some(Compression::Gzip)
+        } else if buffer.starts_with(b"\x28\xb5\x2f\xfd") {
+            Some(Compression::Zstd)
+        } else if buffer.starts_with(b"\xfd7zXZ\x00") {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Undo the compression of `reader` as it's read, failing once `budget` is spent.
+    fn decoder<'r, R: std::io::BufRead + 'r>(
+        self,
+        mut reader: R,
+        budget: &'r Budget,
+    ) -> Result<Box<dyn Read + 'r>, std::io::Error> {
+        let decoder: Box<dyn Read + 'r> = match self {
+            Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+            Compression::Zstd => {
+                Box::new(ruzstd::StreamingDecoder::new(reader).map_err(|err| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+                })?)
+            }
+            Compression::Xz => {
+                // `lzma_rs` can only decompress xz all at once
+                let mut content = LimitedWriter::new(budget);
+                lzma_rs::xz_decompress(&mut reader, &mut content).map_err(|err| match err {
+                    lzma_rs::error::Error::IoError(err) => err,
+                    err => {
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{:?}", err))
+                    }
+                })?;
+                return Ok(Box::new(std::io::Cursor::new(content.content)));
+            }
+        };
+        Ok(Box::new(LimitedReader {
+            inner: decoder,
+            budget,
+        }))
+    }
+}
+
+/// Reads from `inner`, failing once the budget is spent.
+struct LimitedReader<'b, R> {
+    inner: R,
+    budget: &'b Budget,
+}
+
+impl<'b, R: Read> Read for LimitedReader<'b, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        let len = self.inner.read(buf)?;
+        self.budget.spend(len)?;
+        Ok(len)
+    }
+}
+
+/// Collects output, failing once the budget is spent.
+struct LimitedWriter<'b> {
+    content: Vec<u8>,
+    budget: &'b Budget,
+}
+
+impl<'b> LimitedWriter<'b> {
+    fn new(budget: &'b Budget) -> Self {
+        Self {
+            content: Vec::new(),
+            budget,
+        }
+    }
+}
+
+impl<'b> Write for LimitedWriter<'b> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        self.budget.spend(buf.len())?;
+        self.content.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+fn is_zip(buffer: &[u8]) -> bool {
+    buffer.starts_with(b"PK\x03\x04") || buffer.starts_with(b"PK\x05\x06")
+}
+
+fn is_tar(buffer: &[u8]) -> bool {
+    buffer.get(257..262) == Some(b"ustar")
+}
+
+/// How much of a file identifies it as an archive.
+const ARCHIVE_PREFIX_LEN: u64 = 262;
+
+/// Check the content of a compressed file or the members of an archive, returning `false` if
+/// `path` is neither.
+fn report_archive(
+    path: &std::path::Path,
+    content: Option<&[u8]>,
+    explicit: bool,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<bool, std::io::Error> {
+    let result = match content {
+        Some(content) => {
+            if !is_archive(content) {
+                return Ok(false);
+            }
+            report_archive_reader(
+                path,
+                std::io::Cursor::new(content),
+                explicit,
+                policy,
+                reporter,
+            )
+        }
+        None => {
+            // Leave it to the regular check to report errors opening the file
+            match read_prefix(path, ARCHIVE_PREFIX_LEN) {
+                Some(prefix) if is_archive(&prefix) => {}
+                _ => return Ok(false),
+            }
+            std::fs::File::open(path).and_then(|file| {
+                let reader = std::io::BufReader::new(file);
+                report_archive_reader(path, reader, explicit, policy, reporter)
+            })
+        }
+    };
+    if let Err(err) = result {
+        report_path_error(path, err, reporter)?;
+    }
+    Ok(true)
+}
+
+/// Check an archive as it's read, rather than holding all of it in memory.
+fn report_archive_reader<R: std::io::BufRead + std::io::Seek>(
+    path: &std::path::Path,
+    mut reader: R,
+    explicit: bool,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let stream = Budget::new();
+    let members = Budget::new();
+
+    let mut prefix = Vec::new();
+    (&mut reader)
+        .take(ARCHIVE_PREFIX_LEN)
+        .read_to_end(&mut prefix)?;
+    reader.seek(std::io::SeekFrom::Start(0))?;
+
+    if let Some(compression) = Compression::inspect(&prefix) {
+        let mut decoder = compression.decoder(reader, &stream)?;
+        let mut prefix = Vec::new();
+        (&mut decoder)
+            .take(ARCHIVE_PREFIX_LEN)
+            .read_to_end(&mut prefix)?;
+        if is_tar(&prefix) {
+            let decoder = std::io::Cursor::new(prefix).chain(decoder);
+            report_tar(path, decoder, policy, &members, reporter)
+        } else {
+            // Already bounded by the `stream` budget
+            let mut content = prefix;
+            decoder.read_to_end(&mut content)?;
+            if is_zip(&content) {
+                // Zip's index is at its end, so it has to be seekable
+                report_zip(
+                    path,
+                    std::io::Cursor::new(content),
+                    policy,
+                    &members,
+                    reporter,
+                )
+            } else {
+                // A single compressed file
+                report_file_content(path, content, explicit, policy, reporter)
+            }
+        }
+    } else if is_zip(&prefix) {
+        report_zip(path, reader, policy, &members, reporter)
+    } else if is_tar(&prefix) {
+        report_tar(path, reader, policy, &members, reporter)
+    } else {
+        Ok(())
+    }
+}
+
+fn report_zip<R: Read + std::io::Seek>(
+    path: &std::path::Path,
+    reader: R,
+    policy: &crate::policy::Policy,
+    budget: &Budget,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    for i in 0..archive.len() {
+        let mut member = archive.by_index(i)?;
+        if !member.is_file() {
+            continue;
+        }
+        let name = std::path::PathBuf::from(member.name());
+        report_member(path, &name, &mut member, policy, budget, reporter)?;
+    }
+    Ok(())
+}
+
+fn report_tar<R: Read>(
+    path: &std::path::Path,
+    reader: R,
+    policy: &crate::policy::Policy,
+    budget: &Budget,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let mut archive = tar::Archive::new(reader);
+    for member in archive.entries()? {
+        let mut member = member?;
+        if !member.header().entry_type().is_file() {
+            continue;
+        }
+        let name = member.path()?.into_owned();
+        report_member(path, &name, &mut member, policy, budget, reporter)?;
+    }
+    Ok(())
+}
+
+fn is_archive(prefix: &[u8]) -> bool {
+    Compression::inspect(prefix).is_some() || is_zip(prefix) || is_tar(prefix)
+}
+
+/// The first `len` bytes of `path`, if it can be read.
+fn read_prefix(path: &std::path::Path, len: u64) -> Option<Vec<u8>> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut prefix = Vec::new();
+    file.take(len).read_to_end(&mut prefix).ok()?;
+    Some(prefix)
+}
+
+/// Check an archive member with the policy for its own name.
+fn report_member(
+    path: &std::path::Path,
+    member: &std::path::Path,
+    reader: &mut dyn Read,
+    policy: &crate::policy::Policy,
+    budget: &Budget,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let member_policy = policy.code_types.map(|types| types.member_policy(member));
+    let policy = member_policy.as_ref().unwrap_or(policy);
+    if !policy.check_files {
+        return Ok(());
+    }
+
+    let mut content = LimitedWriter::new(budget);
+    std::io::copy(reader, &mut content)?;
+    let content = content.content;
+
+    let encoding = ContentEncoding::inspect(&content, policy.legacy_encodings);
+    if !policy.binary && encoding.is_binary() {
+        let path = std::path::PathBuf::from(format!("{}!{}", path.display(), member.display()));
+        let msg = report::BinaryFile { path: &path };
+        return reporter.report(msg.into());
+    }
+    let content = match encoding.decode(FileBuffer::Read(content)) {
+        Ok(content) => content,
+        Err(err) => {
+            let err = format!("{}: {}", member.display(), err);
+            return report_path_error(path, err, reporter);
+        }
+    };
+
+    let mut accum_line_num = AccumulateLineNum::new();
+    for (typo, severity) in policy.check_bytes(&content) {
+        let line_num = accum_line_num.line_num(&content, typo.byte_offset);
+        let (line, line_offset) = extract_line(&content, typo.byte_offset);
+        let msg = report::Typo {
+            context: Some(
+                report::ArchiveContext {
+                    path,
+                    member,
+                    line_num,
+                }
+                .into(),
+            ),
+            buffer: std::borrow::Cow::Borrowed(line),
+            byte_offset: line_offset,
+            typo: typo.typo.as_ref(),
+            corrections: typo.corrections,
+            severity,
+        };
+        reporter.report(msg.into())?;
+    }
+    Ok(())
+}
+
+fn report_path_error<E: ToString>(
+    path: &std::path::Path,
+    err: E,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let msg =
+        report::Error::new(err.to_string()).context(Some(report::PathContext { path }.into()));
+    reporter.report(msg.into())
+}
+
+/// Check content read from somewhere other than `path`, the same way as `Typos`.
+pub(crate) fn report_file_content(
+    path: &std::path::Path,
+    buffer: Vec<u8>,
+    explicit: bool,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let encoding = ContentEncoding::inspect(&buffer, policy.legacy_encodings);
+    if !explicit && !policy.binary && encoding.is_binary() {
+        let msg = report::BinaryFile { path };
+        reporter.report(msg.into())?;
+    } else {
+        let buffer = report_result(encoding.decode(FileBuffer::Read(buffer)), reporter)?;
+        let mut accum_line_num = AccumulateLineNum::new();
+        report_content(path, &buffer, &mut accum_line_num, policy, reporter)?;
+    }
+    Ok(())
+}
+
+/// Check `buffer` as if it were the content of `path`.
+///
+/// The file name is not checked and binary content is not skipped.
+pub fn check_buffer(
+    path: &std::path::Path,
+    buffer: &[u8],
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let mut accum_line_num = AccumulateLineNum::new();
+    report_content(path, buffer, &mut accum_line_num, policy, reporter)
+}
+
+fn report_content(
+    path: &std::path::Path,
+    buffer: &[u8],
+    accum_line_num: &mut AccumulateLineNum,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    for (typo, severity) in check_content(buffer, policy.markup, policy) {
+        let line_num = accum_line_num.line_num(buffer, typo.byte_offset);
+        let (line, line_offset) = extract_line(buffer, typo.byte_offset);
+        let msg = report::Typo {
+            context: Some(report::FileContext { path, line_num }.into()),
+            buffer: std::borrow::Cow::Borrowed(line),
+            byte_offset: line_offset,
+            typo: typo.typo.as_ref(),
+            corrections: typo.corrections,
+            severity,
+        };
+        reporter.report(msg.into())?;
+    }
+    Ok(())
+}
+
+/// Check the prose of `buffer`, written in `markup`, and any code embedded in it.
+fn check_content<'b, 's: 'b>(
+    buffer: &'b [u8],
+    markup: crate::config::Markup,
+    policy: &crate::policy::Policy<'s, 's>,
+) -> Vec<(typos::Typo<'b>, report::Severity)> {
+    if markup == crate::config::Markup::Plain {
+        return policy.check_bytes(buffer);
+    }
+
+    let mut typos = Vec::new();
+    for region in crate::markup::regions(markup, buffer) {
+        let code;
+        let (range, region_policy) = match region {
+            crate::markup::Region::Text(range) => (range, policy),
+            crate::markup::Region::Code { lang, range } => {
+                match policy.code_types.and_then(|types| types.policy(lang)) {
+                    Some(found) if found.check_files => {
+                        code = found;
+                        (range, &code)
+                    }
+                    _ => continue,
+                }
+            }
+        };
+        let offset = range.start;
+        typos.extend(region_policy.check_bytes(&buffer[range]).into_iter().map(
+            |(mut typo, severity)| {
+                typo.byte_offset += offset;
+                (typo, severity)
+            },
+        ));
+    }
+    typos
+}
+
+pub(crate) fn report_file_name(
+    path: &std::path::Path,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
+        for typo in typos::check_str(file_name, policy.tokenizer, policy.dict) {
+            let msg = report::Typo {
+                context: Some(report::PathContext { path }.into()),
+                buffer: std::borrow::Cow::Borrowed(file_name.as_bytes()),
+                byte_offset: typo.byte_offset,
+                typo: typo.typo.as_ref(),
+                corrections: typo.corrections,
+                severity: policy.severities.filename,
+            };
+            reporter.report(msg.into())?;
+        }
+    }
+    Ok(())
+}
+
+/// Report the typos in the name of `path` that can't be fixed, returning the fixed path if any
+/// can.
+fn fix_file_name(
+    path: &std::path::Path,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<Option<std::path::PathBuf>, std::io::Error> {
+    if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
+        let mut fixes = Vec::new();
+        for typo in typos::check_str(file_name, policy.tokenizer, policy.dict) {
+            if is_fixable(&typo) {
+                fixes.push(typo.into_owned());
+            } else {
+                let msg = report::Typo {
+                    context: Some(report::PathContext { path }.into()),
+                    buffer: std::borrow::Cow::Borrowed(file_name.as_bytes()),
+                    byte_offset: typo.byte_offset,
+                    typo: typo.typo.as_ref(),
+                    corrections: typo.corrections,
+                    severity: policy.severities.filename,
+                };
+                reporter.report(msg.into())?;
+            }
+        }
+        if !fixes.is_empty() {
+            let file_name = file_name.to_owned().into_bytes();
+            let new_name = fix_buffer(file_name, fixes.into_iter());
+            let new_name = String::from_utf8(new_name).expect("corrections are valid utf-8");
+            return Ok(Some(path.with_file_name(new_name)));
+        }
+    }
+    Ok(None)
+}
+
+fn print_rename(from: &std::path::Path, to: &std::path::Path) {
+    println!("diff --git a/{} b/{}", from.display(), to.display());
+    println!("rename from {}", from.display());
+    println!("rename to {}", to.display());
+}
+
+/// Whether renaming `from` to `to` would replace another file.
+fn is_taken(from: &std::path::Path, to: &std::path::Path) -> bool {
+    to.symlink_metadata().is_ok() && !is_case_rename(from, to)
+}
+
+/// Whether `to` is `from` on a case-insensitive filesystem.
+fn is_case_rename(from: &std::path::Path, to: &std::path::Path) -> bool {
+    let (from_name, to_name) = match (from.file_name(), to.file_name()) {
+        (Some(from_name), Some(to_name)) => {
+            (from_name.to_string_lossy(), to_name.to_string_lossy())
+        }
+        _ => return false,
+    };
+    from_name != to_name
+        && from_name.to_lowercase() == to_name.to_lowercase()
+        && same_file::is_same_file(from, to).unwrap_or(false)
+}
+
+/// Rename without replacing another file, returning `false` if `to` is taken.
+fn rename_path(from: &std::path::Path, to: &std::path::Path) -> Result<bool, std::io::Error> {
+    if is_taken(from, to) {
+        return Ok(false);
+    }
+    if is_case_rename(from, to) {
+        // Renaming directly can be a no-op on case-insensitive filesystems
+        let mut tmp_name = to.file_name().unwrap_or_default().to_owned();
+        tmp_name.push(".typos-rename");
+        let tmp = to.with_file_name(tmp_name);
+        std::fs::rename(from, &tmp)?;
+        std::fs::rename(&tmp, to)?;
+    } else {
+        std::fs::rename(from, to)?;
+    }
+    Ok(true)
+}
+
+fn report_collision(
+    from: &std::path::Path,
+    to: &std::path::Path,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let msg = report::Error::new(format!(
+        "not renaming to `{}`, it already exists",
+        to.display()
+    ))
+    .context(Some(report::PathContext { path: from }.into()));
+    reporter.report(msg.into())
+}
+
+/// Where `path` ended up after `renames`, applied in order.
+fn renamed_path(
+    path: &std::path::Path,
+    renames: &[(std::path::PathBuf, std::path::PathBuf)],
+) -> std::path::PathBuf {
+    let mut path = path.to_owned();
+    for (from, to) in renames {
+        if let Ok(rest) = path.strip_prefix(from) {
+            // Joining an empty `rest` would add a trailing separator
+            path = if rest.as_os_str().is_empty() {
+                to.clone()
+            } else {
+                to.join(rest)
+            };
+        }
+    }
+    path
+}
+
+fn update_references(
+    path: &std::path::Path,
+    renames: &[(std::path::PathBuf, std::path::PathBuf)],
+    referrer: &Referrer,
+    reporter: &dyn report::Report,
+) -> Result<bool, std::io::Error> {
+    let (buffer, encoding) = read_file(path, referrer.legacy_encodings, reporter)?;
+    if encoding.is_binary() {
+        return Ok(false);
+    }
+    let mut buffer = buffer.into_vec();
+    let mut changed = false;
+    for (from, to) in renames {
+        let from = from.file_name().and_then(|s| s.to_str());
+        let to = to.file_name().and_then(|s| s.to_str());
+        if let (Some(from), Some(to)) = (from, to) {
+            if let Some(replaced) = replace_component(&buffer, from.as_bytes(), to.as_bytes()) {
+                buffer = replaced;
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        write_file(path, encoding, buffer, referrer.write, reporter)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Replace `from` where it is a whole path component of a path, returning `None` if there were
+/// none.
+///
+/// Names with an extension, like `teh.md`, are specific enough on their own.  Otherwise, the
+/// name must follow a path separator or quote, or be followed by a path separator, so a
+/// renamed `teh/` directory doesn't rewrite every `teh` in prose.
+fn replace_component(buffer: &[u8], from: &[u8], to: &[u8]) -> Option<Vec<u8>> {
+    let is_name_byte = |b: u8| !b.is_ascii() || b.is_ascii_alphanumeric() || b"_-.".contains(&b);
+    let is_separator = |b: u8| b"/\\".contains(&b);
+    let is_quote = |b: u8| b"\"'`".contains(&b);
+    let has_extension = from.iter().skip(1).any(|b| *b == b'.');
+    let mut replaced = Vec::new();
+    let mut last = 0;
+    for start in buffer.find_iter(from) {
+        let end = start + from.len();
+        if start < last {
+            continue;
+        }
+        let before = start.checked_sub(1).map(|i| buffer[i]);
+        let after = buffer.get(end).copied();
+        let is_start = before.map(|b| !is_name_byte(b)).unwrap_or(true);
+        let is_end = after.map(|b| !is_name_byte(b)).unwrap_or(true);
+        let is_path = has_extension
+            || before
+                .map(|b| is_separator(b) || is_quote(b))
+                .unwrap_or(false)
+            || after.map(is_separator).unwrap_or(false);
+        if is_start && is_end && is_path {
+            replaced.extend_from_slice(&buffer[last..start]);
+            replaced.extend_from_slice(to);
+            last = end;
+        }
+    }
+    if last == 0 {
+        return None;
+    }
+    replaced.extend_from_slice(&buffer[last..]);
+    Some(replaced)
+}
+
+fn is_fixable(typo: &typos::Typo<'_>) -> bool {
+    extract_fix(typo).is_some()
+}
+
+fn fix_buffer(mut buffer: Vec<u8>, typos: impl Iterator<Item = typos::Typo<'static>>) -> Vec<u8> {
+    let mut offset = 0isize;
+    for typo in typos {
+        let fix = extract_fix(&typo).expect("Caller only provides fixable typos");
+        let start = ((typo.byte_offset as isize) + offset) as usize;
+        let end = start + typo.typo.len();
+
+        buffer.splice(start..end, fix.as_bytes().iter().copied());
+
+        offset += (fix.len() as isize) - (typo.typo.len() as isize);
+    }
+    buffer
+}
+
+/// Prepare to walk `path`, honoring the ignore settings in `walk_policy`.
+pub fn walk_builder(
+    path: &std::path::Path,
+    walk_policy: &crate::config::Walk,
+) -> Result<ignore::WalkBuilder, ignore::Error> {
+    let mut walk = ignore::WalkBuilder::new(path);
+    walk.hidden(walk_policy.ignore_hidden())
+        .ignore(walk_policy.ignore_dot())
+        .git_global(walk_policy.ignore_global())
+        .git_ignore(walk_policy.ignore_vcs())
+        .git_exclude(walk_policy.ignore_vcs())
+        .parents(walk_policy.ignore_parent());
+    if !walk_policy.extend_exclude.is_empty() {
+        walk.overrides(exclude_overrides(walk_policy)?);
+    }
+    Ok(walk)
+}
+
+/// Match the paths excluded by `extend-exclude`.
+pub fn exclude_overrides(
+    walk_policy: &crate::config::Walk,
+) -> Result<ignore::overrides::Override, ignore::Error> {
+    let mut overrides = ignore::overrides::OverrideBuilder::new(".");
+    for pattern in walk_policy.extend_exclude.iter() {
+        overrides.add(&format!("!{}", pattern))?;
+    }
+    overrides.build()
+}
+
+/// Check files found some other way than walking, like with `git`.
+pub fn check_files(
+    paths: &[std::path::PathBuf],
+    checks: &dyn FileChecker,
+    engine: &crate::policy::ConfigEngine,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    for path in paths {
+        // The file may be gone from the working tree while still being in git
+        let lookup_path = match path.canonicalize() {
+            Ok(lookup_path) => lookup_path,
+            Err(_) => {
+                let dir = match path.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir.canonicalize()?,
+                    _ => std::env::current_dir()?,
+                };
+                dir.join(path.file_name().unwrap_or_default())
+            }
+        };
+        let policy = engine.policy(&lookup_path);
+        checks.check_file(path, false, &policy, reporter)?;
+    }
+    Ok(())
+}
+
+pub fn walk_path(
+    walk: ignore::Walk,
+    checks: &dyn FileChecker,
     engine: &crate::policy::ConfigEngine,
     reporter: &dyn report::Report,
 ) -> Result<(), ignore::Error> {
@@ -596,7 +2335,11 @@ fn walk_entry(
             return Ok(());
         }
     };
-    if entry.file_type().map(|t| t.is_file()).unwrap_or(true) {
+    if entry.depth() > 0 && entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+        let path = entry.path();
+        let policy = engine.policy(&path.canonicalize()?);
+        checks.check_dir(path, &policy, reporter)?;
+    } else if entry.file_type().map(|t| t.is_file()).unwrap_or(true) {
         let explicit = entry.depth() == 0;
         let (path, lookup_path) = if entry.is_stdin() {
             let path = std::path::Path::new("-");
@@ -700,6 +2443,293 @@ mod test {
         assert_eq!(line_num, 3);
     }
 
+    #[test]
+    fn test_line_count_next_chunk() {
+        let mut accum_line_num = AccumulateLineNum::new();
+        let line_num = accum_line_num.line_num(b"1\n2\n", 2);
+        assert_eq!(line_num, 2);
+        accum_line_num.next_chunk(b"1\n2\n");
+        let line_num = accum_line_num.line_num(b"3\n4", 0);
+        assert_eq!(line_num, 3);
+        let line_num = accum_line_num.line_num(b"3\n4", 2);
+        assert_eq!(line_num, 4);
+    }
+
+    fn collect_chunks(input: &'static [u8], chunk_size: usize) -> Vec<Vec<u8>> {
+        let mut chunks = ChunkReader::new(Box::new(input), chunk_size, false).unwrap();
+        let mut actual = Vec::new();
+        while let Some(chunk) = chunks.next_chunk().unwrap() {
+            actual.push(chunk.into_owned());
+        }
+        actual
+    }
+
+    #[test]
+    fn test_chunks_end_on_lines() {
+        let actual = collect_chunks(b"hello\nworld\nfoo", 4);
+        let expected: Vec<Vec<u8>> =
+            vec![b"hello\n".to_vec(), b"world\n".to_vec(), b"foo".to_vec()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_chunks_empty() {
+        let actual = collect_chunks(b"", 4);
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_chunks_preserve_content() {
+        let input = "Hello wörld\nApropriate\r\n\nend";
+        let actual = collect_chunks(input.as_bytes(), 3);
+        assert_eq!(actual.concat(), input.as_bytes());
+    }
+
+    #[test]
+    fn test_chunks_utf16() {
+        let input = b"\xFF\xFEh\x00i\x00\n\x00t\x00h\x00e\x00r\x00e\x00";
+        let actual = collect_chunks(input, 3);
+        let expected: Vec<Vec<u8>> = vec!["hi\n".into(), "there".into()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_chunks_utf32() {
+        let input = b"\x00\x00\xFE\xFF\x00\x00\x00h\x00\x00\x00i\x00\x00\x00\n\x00\x00\x00!";
+        let actual = collect_chunks(input, 7);
+        let expected: Vec<Vec<u8>> = vec!["hi\n".into(), "!".into()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_chunks_mapped() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.child("large.txt");
+        let content = "Hello world\n".repeat((MMAP_THRESHOLD as usize) / 10);
+        file.write_str(&content).unwrap();
+
+        let mut chunks = ChunkReader::open(file.path(), false).unwrap();
+        assert!(matches!(chunks.whole, Some(FileBuffer::Mapped(_))));
+        assert_eq!(
+            chunks.next_chunk().unwrap().as_deref(),
+            Some(content.as_bytes())
+        );
+        assert_eq!(chunks.next_chunk().unwrap(), None);
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_file_buffer_map_matches_read() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.child("small.txt");
+        file.write_str("Hello world").unwrap();
+
+        let read = FileBuffer::read(file.path()).unwrap();
+        let mapped = FileBuffer::map(file.path()).unwrap();
+        let opened = FileBuffer::open(file.path()).unwrap();
+        assert!(matches!(opened, FileBuffer::Read(_)));
+        assert_eq!(&*read, b"Hello world");
+        assert_eq!(&*mapped, b"Hello world");
+        assert_eq!(&*opened, b"Hello world");
+        assert_eq!(mapped.into_vec(), b"Hello world".to_vec());
+
+        temp.close().unwrap();
+    }
+
+    /// Decode `input`, fix `teh`, and encode it again.
+    fn fix_encoded(input: &[u8], expected_encoding: ContentEncoding) -> Vec<u8> {
+        let encoding = ContentEncoding::inspect(input, true);
+        assert_eq!(encoding, expected_encoding);
+        let decoded = encoding
+            .decode(FileBuffer::Read(input.to_vec()))
+            .unwrap()
+            .into_vec();
+        let fixed = decoded.replace("teh", "the");
+        encoding.encode(fixed).unwrap()
+    }
+
+    #[test]
+    fn test_encoding_utf8() {
+        let actual = fix_encoded(b"caf\xC3\xA9 teh\r\nteh\n", ContentEncoding::Utf8);
+        assert_eq!(actual, b"caf\xC3\xA9 the\r\nthe\n");
+    }
+
+    #[test]
+    fn test_encoding_utf8_bom() {
+        let actual = fix_encoded(b"\xEF\xBB\xBFteh\r\n", ContentEncoding::Utf8Bom);
+        assert_eq!(actual, b"\xEF\xBB\xBFthe\r\n");
+    }
+
+    #[test]
+    fn test_encoding_utf16le() {
+        let actual = fix_encoded(
+            b"\xFF\xFEt\x00e\x00h\x00\r\x00\n\x00",
+            ContentEncoding::Utf16Le,
+        );
+        assert_eq!(actual, b"\xFF\xFEt\x00h\x00e\x00\r\x00\n\x00");
+    }
+
+    #[test]
+    fn test_encoding_utf16be() {
+        let actual = fix_encoded(b"\xFE\xFF\x00t\x00e\x00h\x00\n", ContentEncoding::Utf16Be);
+        assert_eq!(actual, b"\xFE\xFF\x00t\x00h\x00e\x00\n");
+    }
+
+    #[test]
+    fn test_encoding_utf32le() {
+        let actual = fix_encoded(
+            b"\xFF\xFE\x00\x00t\x00\x00\x00e\x00\x00\x00h\x00\x00\x00\x00\xF6\x01\x00",
+            ContentEncoding::Utf32Le,
+        );
+        assert_eq!(
+            actual,
+            b"\xFF\xFE\x00\x00t\x00\x00\x00h\x00\x00\x00e\x00\x00\x00\x00\xF6\x01\x00"
+        );
+    }
+
+    #[test]
+    fn test_encoding_utf32be() {
+        let actual = fix_encoded(
+            b"\x00\x00\xFE\xFF\x00\x00\x00t\x00\x00\x00e\x00\x00\x00h",
+            ContentEncoding::Utf32Be,
+        );
+        assert_eq!(
+            actual,
+            b"\x00\x00\xFE\xFF\x00\x00\x00t\x00\x00\x00h\x00\x00\x00e"
+        );
+    }
+
+    #[test]
+    fn test_encoding_utf32_invalid() {
+        let input = b"\xFF\xFE\x00\x00\x00\xD8\x00\x00";
+        let encoding = ContentEncoding::inspect(input, false);
+        assert!(encoding.decode(FileBuffer::Read(input.to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_encoding_latin1() {
+        let actual = fix_encoded(
+            b"caf\xE9 teh\n",
+            ContentEncoding::Legacy(LegacyEncoding::Latin1),
+        );
+        assert_eq!(actual, b"caf\xE9 the\n");
+    }
+
+    #[test]
+    fn test_encoding_windows1252() {
+        let actual = fix_encoded(
+            b"\x93teh\x94\n",
+            ContentEncoding::Legacy(LegacyEncoding::Windows1252),
+        );
+        assert_eq!(actual, b"\x93the\x94\n");
+    }
+
+    #[test]
+    fn test_encoding_shift_jis() {
+        // "日本語 teh"
+        let actual = fix_encoded(
+            b"\x93\xFA\x96{\x8C\xEA teh\r\n",
+            ContentEncoding::Legacy(LegacyEncoding::ShiftJis),
+        );
+        assert_eq!(actual, b"\x93\xFA\x96{\x8C\xEA the\r\n");
+    }
+
+    #[test]
+    fn test_encoding_legacy_disabled() {
+        let input = b"caf\xE9 teh\n";
+        assert_eq!(
+            ContentEncoding::inspect(input, false),
+            ContentEncoding::Utf8
+        );
+    }
+
+    fn decompress(compressed: &[u8]) -> Vec<u8> {
+        let budget = Budget::new();
+        let compression = Compression::inspect(compressed).unwrap();
+        let mut actual = Vec::new();
+        compression
+            .decoder(compressed, &budget)
+            .unwrap()
+            .read_to_end(&mut actual)
+            .unwrap();
+        actual
+    }
+
+    #[test]
+    fn test_compression_gzip() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(b"Hello teh world").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(Compression::inspect(&compressed), Some(Compression::Gzip));
+        assert_eq!(decompress(&compressed), b"Hello teh world");
+    }
+
+    #[test]
+    fn test_compression_zstd() {
+        let content = b"Hello teh world";
+        // `ruzstd` can't compress, so hand-craft a frame holding one raw block
+        let mut compressed = b"\x28\xb5\x2f\xfd\x20".to_vec();
+        compressed.push(content.len() as u8);
+        let block_header = (content.len() as u32) << 3 | 1;
+        compressed.extend_from_slice(&block_header.to_le_bytes()[..3]);
+        compressed.extend_from_slice(content);
+
+        assert_eq!(Compression::inspect(&compressed), Some(Compression::Zstd));
+        assert_eq!(decompress(&compressed), content);
+    }
+
+    #[test]
+    fn test_compression_xz() {
+        let mut compressed = Vec::new();
+        lzma_rs::xz_compress(&mut &b"Hello teh world"[..], &mut compressed).unwrap();
+
+        assert_eq!(Compression::inspect(&compressed), Some(Compression::Xz));
+        assert_eq!(decompress(&compressed), b"Hello teh world");
+    }
+
+    #[test]
+    fn test_compression_none() {
+        assert_eq!(Compression::inspect(b"Hello teh world"), None);
+    }
+
+    #[test]
+    fn test_budget_is_shared() {
+        let budget = Budget::new();
+        let half = vec![0; (MAX_ARCHIVE_CONTENT / 2) as usize];
+        LimitedWriter::new(&budget).write_all(&half).unwrap();
+        LimitedWriter::new(&budget).write_all(&half).unwrap();
+        assert!(LimitedWriter::new(&budget).write_all(b"x").is_err());
+    }
+
+    #[test]
+    fn test_is_tar() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "hello.txt", &b"Hello"[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+        assert!(is_tar(&archive));
+        assert!(!is_tar(b"Hello"));
+    }
+
+    #[test]
+    fn test_chunk_boundary() {
+        assert!(is_chunk_boundary(b' '));
+        assert!(is_chunk_boundary(b'.'));
+        assert!(!is_chunk_boundary(b'a'));
+        assert!(!is_chunk_boundary(b'_'));
+        assert!(!is_chunk_boundary(0xC3));
+    }
+
     #[test]
     fn test_extract_line_single_line() {
         let buffer = b"hello world";
@@ -776,4 +2806,160 @@ mod test {
         assert_eq!(offset, 28);
         assert_eq!(line[offset], buffer[buffer_offset]);
     }
+
+    #[test]
+    fn test_replace_component() {
+        let actual = replace_component(b"see teh.md, dir/teh.md", b"teh.md", b"the.md");
+        assert_eq!(actual.as_deref(), Some(&b"see the.md, dir/the.md"[..]));
+    }
+
+    #[test]
+    fn test_replace_component_dir() {
+        let actual = replace_component(
+            b"say teh; see teh/a.md, ./teh, \"teh\" and 'teh'",
+            b"teh",
+            b"the",
+        );
+        assert_eq!(
+            actual.as_deref(),
+            Some(&b"say teh; see the/a.md, ./the, \"the\" and 'the'"[..])
+        );
+        assert_eq!(replace_component(b"teh is a word", b"teh", b"the"), None);
+    }
+
+    #[test]
+    fn test_replace_component_partial() {
+        assert_eq!(
+            replace_component(b"teh.mdx my_teh.md", b"teh.md", b"the.md"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_renamed_path() {
+        let renames = vec![
+            ("a/teh/fiel".into(), "a/teh/file".into()),
+            ("a/teh".into(), "a/the".into()),
+        ];
+        let actual = renamed_path(std::path::Path::new("a/teh/fiel"), &renames);
+        assert_eq!(actual.as_os_str(), "a/the/file");
+        let actual = renamed_path(std::path::Path::new("a/teh/other"), &renames);
+        assert_eq!(actual, std::path::Path::new("a/the/other"));
+    }
+
+    #[test]
+    fn test_rename_path_taken() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let from = temp.child("teh.txt");
+        from.write_str("original").unwrap();
+        let to = temp.child("the.txt");
+        to.write_str("existing").unwrap();
+
+        assert!(!rename_path(from.path(), to.path()).unwrap());
+        from.assert("original");
+        to.assert("existing");
+
+        std::fs::remove_file(to.path()).unwrap();
+        assert!(rename_path(from.path(), to.path()).unwrap());
+        to.assert("original");
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_replace_file_keeps_permissions() {
+        use assert_fs::prelude::*;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.child("run.sh");
+        file.write_str("teh").unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o751)).unwrap();
+
+        replace_file(file.path(), b"the", write_options()).unwrap();
+        file.assert("the");
+        let mode = std::fs::metadata(file.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o751);
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 1);
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_replace_file_symlink() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let target = temp.child("target.txt");
+        target.write_str("teh").unwrap();
+        let link = temp.child("link.txt");
+        std::os::unix::fs::symlink(target.path(), link.path()).unwrap();
+
+        let refuse = WriteOptions {
+            fix_symlinks: false,
+            ..write_options()
+        };
+        assert!(replace_file(link.path(), b"the", refuse).is_err());
+        target.assert("teh");
+
+        replace_file(link.path(), b"the", write_options()).unwrap();
+        target.assert("the");
+        let link_type = std::fs::symlink_metadata(link.path()).unwrap().file_type();
+        assert!(link_type.is_symlink());
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_replace_file_hard_link() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.child("file.txt");
+        file.write_str("teh").unwrap();
+        let other = temp.child("other.txt");
+        std::fs::hard_link(file.path(), other.path()).unwrap();
+
+        replace_file(file.path(), b"the", write_options()).unwrap();
+        file.assert("the");
+        other.assert("the");
+
+        temp.close().unwrap();
+    }
+
+    #[test]
+    fn test_replace_file_preserve_mtime() {
+        use assert_fs::prelude::*;
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let file = temp.child("file.txt");
+        file.write_str("teh").unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        filetime::set_file_mtime(file.path(), filetime::FileTime::from_system_time(old)).unwrap();
+
+        replace_file(file.path(), b"the", write_options()).unwrap();
+        let modified = std::fs::metadata(file.path()).unwrap().modified().unwrap();
+        assert_ne!(modified, old);
+
+        let preserve = WriteOptions {
+            preserve_mtime: true,
+            ..write_options()
+        };
+        filetime::set_file_mtime(file.path(), filetime::FileTime::from_system_time(old)).unwrap();
+        replace_file(file.path(), b"then", preserve).unwrap();
+        file.assert("then");
+        let modified = std::fs::metadata(file.path()).unwrap().modified().unwrap();
+        assert_eq!(modified, old);
+
+        temp.close().unwrap();
+    }
+
+    fn write_options() -> WriteOptions {
+        WriteOptions::new(&crate::policy::Policy::new())
+    }
 }
@@ -0,0 +1,660 @@
+//! Select files to check from git, rather than walking directories, and check commit messages
+//! and branch names.
+
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+
+use crate::report;
+
+/// Which files git should select.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// Files in the index
+    Tracked,
+    /// Files with changes staged for the next commit
+    Staged,
+    /// Files that changed since a commit, whether staged or not
+    ChangedSince(String),
+}
+
+/// A git working tree.
+#[derive(Clone, Debug)]
+pub struct Repo {
+    root: std::path::PathBuf,
+}
+
+impl Repo {
+    /// The working tree containing `dir`.
+    pub fn discover(dir: &std::path::Path) -> Result<Self, anyhow::Error> {
+        let output = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let root = String::from_utf8(output)?;
+        let root = std::path::Path::new(root.trim_end_matches('\n')).canonicalize()?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    /// The files under `path` that git selects, skipping those excluded by `walk_policy`.
+    ///
+    /// Files are relative to `path` the same way as when walking it.  Deleted files and
+    /// submodules are left out.
+    pub fn files(
+        &self,
+        selection: &Selection,
+        path: &std::path::Path,
+        walk_policy: &crate::config::Walk,
+    ) -> Result<Vec<std::path::PathBuf>, anyhow::Error> {
+        let abs_path = path.canonicalize()?;
+        let abs_path_arg = abs_path
+            .as_os_str()
+            .to_str()
+            .ok_or_else(|| anyhow::format_err!("{} is not valid UTF-8", abs_path.display()))?;
+        let rels = match selection {
+            Selection::Tracked => self.tracked(abs_path_arg)?,
+            Selection::Staged => self.changed("--cached", abs_path_arg)?,
+            Selection::ChangedSince(rev) => self.changed(rev, abs_path_arg)?,
+        };
+
+        let excludes = crate::file::exclude_overrides(walk_policy)?;
+        let mut files = Vec::new();
+        for rel in rels {
+            let file = self.root.join(rel);
+            let rest = file.strip_prefix(&abs_path).unwrap_or(file.as_path());
+            if walk_policy.ignore_hidden() && is_hidden(rest) {
+                continue;
+            }
+            let file = if rest.as_os_str().is_empty() {
+                path.to_owned()
+            } else {
+                path.join(rest)
+            };
+            if excludes.matched(&file, false).is_ignore() {
+                continue;
+            }
+            files.push(file);
+        }
+        Ok(files)
+    }
+
+    /// Files in the index under `path`, leaving out submodules and files deleted from the
+    /// working tree.
+    fn tracked(&self, path: &str) -> Result<Vec<String>, anyhow::Error> {
+        let deleted = git(&self.root, &["ls-files", "-z", "--deleted", "--", path])?;
+        let deleted: std::collections::HashSet<_> = split_paths(&deleted).collect();
+
+        let output = git(&self.root, &["ls-files", "-z", "--stage", "--", path])?;
+        let mut files: Vec<String> = Vec::new();
+        for entry in split_paths(&output) {
+            // `<mode> <object> <stage>\t<path>`
+            let tab = entry
+                .iter()
+                .position(|b| *b == b'\t')
+                .ok_or_else(|| anyhow::format_err!("unexpected `git ls-files` output"))?;
+            let (info, rel) = (&entry[..tab], &entry[tab + 1..]);
+            if info.starts_with(GITLINK_MODE) || deleted.contains(rel) {
+                continue;
+            }
+            let rel = std::str::from_utf8(rel)?;
+            // Conflicted files are listed once per stage
+            if files.last().map(String::as_str) == Some(rel) {
+                continue;
+            }
+            files.push(rel.to_owned());
+        }
+        Ok(files)
+    }
+
+    /// Files under `path` changed since `rev`, leaving out submodules and deleted files.
+    fn changed(&self, rev: &str, path: &str) -> Result<Vec<String>, anyhow::Error> {
+        let args = [
+            "diff",
+            "--name-only",
+            "-z",
+            "--diff-filter=d",
+            "--ignore-submodules",
+            rev,
+            "--",
+            path,
+        ];
+        let output = git(&self.root, &args)?;
+        let files = split_paths(&output)
+            .map(|rel| Ok(std::str::from_utf8(rel)?.to_owned()))
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(files)
+    }
+
+    /// Read staged content.
+    pub fn index(&self) -> Result<Index, std::io::Error> {
+        Index::new(self.root.clone())
+    }
+
+    /// Files whose working tree content differs from what is staged.
+    pub fn unstaged(&self) -> Result<std::collections::HashSet<std::path::PathBuf>, anyhow::Error> {
+        let output = git(&self.root, &["diff", "--name-only", "-z"])?;
+        let mut files = std::collections::HashSet::new();
+        for rel in output.split(|b| *b == b'\0').filter(|p| !p.is_empty()) {
+            let rel = std::str::from_utf8(rel)?;
+            files.insert(self.root.join(rel));
+        }
+        Ok(files)
+    }
+
+    /// Stage the current content of `paths`, including their removal.
+    pub fn stage(&self, paths: &[std::path::PathBuf]) -> Result<(), anyhow::Error> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let mut args = vec!["add", "--all", "--"];
+        for path in paths {
+            let path = path
+                .to_str()
+                .ok_or_else(|| anyhow::format_err!("{} is not valid UTF-8", path.display()))?;
+            args.push(path);
+        }
+        // Paths are relative to where we were run and may no longer exist
+        let cwd = std::env::current_dir()?;
+        git(&cwd, &args)?;
+        Ok(())
+    }
+
+    /// The commits in `range`, as accepted by `git log` (e.g. `origin/main..HEAD`).
+    pub fn commits(&self, range: &str) -> Result<Vec<Commit>, anyhow::Error> {
+        let output = git(&self.root, &["log", "-z", "--format=%H%n%B", range, "--"])?;
+        let commits = output
+            .split(|b| *b == b'\0')
+            .filter(|c| !c.is_empty())
+            .map(|c| {
+                let c = String::from_utf8_lossy(c);
+                let (id, message) = c.split_at(c.find('\n').unwrap_or(c.len()));
+                Commit {
+                    id: id.to_owned(),
+                    message: message.strip_prefix('\n').unwrap_or(message).to_owned(),
+                }
+            })
+            .collect();
+        Ok(commits)
+    }
+
+    /// The name of the checked out branch, `None` when `HEAD` is detached.
+    pub fn branch(&self) -> Result<Option<String>, anyhow::Error> {
+        let output = git(&self.root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+        let branch = String::from_utf8(output)?;
+        let branch = branch.trim_end_matches('\n');
+        if branch == "HEAD" {
+            Ok(None)
+        } else {
+            Ok(Some(branch.to_owned()))
+        }
+    }
+}
+
+/// A commit and its message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub id: String,
+    pub message: String,
+}
+
+/// Check a commit message, skipping its trailers.
+pub fn check_commit(
+    commit: &Commit,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    for (line_num, line) in message_lines(&commit.message, false) {
+        let context = || {
+            report::CommitContext {
+                commit: commit.id.as_str(),
+                line_num,
+            }
+            .into()
+        };
+        report_line(line, context, policy, reporter)?;
+    }
+    Ok(())
+}
+
+/// Check a commit message being edited, as passed to a `commit-msg` hook.
+///
+/// Comments and trailers are skipped, as is everything below the scissors line of
+/// `git commit --verbose`.
+pub fn check_message_file(
+    path: &std::path::Path,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    let message = match std::fs::read(path) {
+        Ok(message) => message,
+        Err(err) => {
+            let msg = report::Error::new(err.to_string())
+                .context(Some(report::PathContext { path }.into()));
+            reporter.report(msg.into())?;
+            return Ok(());
+        }
+    };
+    let message = String::from_utf8_lossy(&message);
+    for (line_num, line) in message_lines(&message, true) {
+        let context = || report::FileContext { path, line_num }.into();
+        report_line(line, context, policy, reporter)?;
+    }
+    Ok(())
+}
+
+/// Check the name of a branch.
+pub fn check_branch(
+    branch: &str,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    report_line(
+        branch,
+        || report::BranchContext { branch }.into(),
+        policy,
+        reporter,
+    )
+}
+
+fn report_line<'m>(
+    line: &'m str,
+    context: impl Fn() -> report::Context<'m>,
+    policy: &crate::policy::Policy,
+    reporter: &dyn report::Report,
+) -> Result<(), std::io::Error> {
+    for (typo, severity) in policy.check_str(line) {
+        let msg = report::Typo {
+            context: Some(context()),
+            buffer: std::borrow::Cow::Borrowed(line.as_bytes()),
+            byte_offset: typo.byte_offset,
+            typo: typo.typo.as_ref(),
+            corrections: typo.corrections,
+            severity,
+        };
+        reporter.report(msg.into())?;
+    }
+    Ok(())
+}
+
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// The lines of `message` worth checking, with their line numbers.
+fn message_lines(message: &str, strip_comments: bool) -> Vec<(usize, &str)> {
+    let mut lines: Vec<_> = message
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .collect();
+    if strip_comments {
+        if let Some(end) = lines.iter().position(|(_, line)| *line == SCISSORS) {
+            lines.truncate(end);
+        }
+        lines.retain(|(_, line)| !line.starts_with('#'));
+    }
+    trim_blank_lines(&mut lines);
+
+    // Like git, trailers are only looked for in the last paragraph, and not in the subject
+    let start = lines
+        .iter()
+        .rposition(|(_, l)| l.trim().is_empty())
+        .map(|i| i + 1);
+    if let Some(start) = start {
+        let paragraph = &lines[start..];
+        let trailers = paragraph.iter().filter(|(_, l)| is_trailer(l)).count();
+        let generated = paragraph.iter().any(|(_, l)| is_generated_trailer(l));
+        let continuations = paragraph.iter().filter(|(_, l)| is_continuation(l)).count();
+        let is_trailer_block = trailers + continuations == paragraph.len()
+            || (generated && paragraph.len() <= trailers * 4);
+        if trailers != 0 && is_trailer_block {
+            let mut in_trailer = false;
+            let mut i = start;
+            lines.retain(|(_, line)| {
+                let keep = if i < start {
+                    true
+                } else if is_trailer(line) {
+                    in_trailer = true;
+                    false
+                } else if in_trailer && is_continuation(line) {
+                    false
+                } else {
+                    in_trailer = false;
+                    true
+                };
+                i += 1;
+                keep
+            });
+            trim_blank_lines(&mut lines);
+        }
+    }
+
+    lines
+}
+
+fn trim_blank_lines(lines: &mut Vec<(usize, &str)>) {
+    while lines.last().map(|(_, l)| l.trim().is_empty()) == Some(true) {
+        lines.pop();
+    }
+}
+
+/// Whether `line` is `Key: value`, like `Signed-off-by: Jane Doe <jane@example.com>`
+fn is_trailer(line: &str) -> bool {
+    if is_generated_trailer(line) {
+        return true;
+    }
+    match line.find(':') {
+        Some(end) => {
+            let key = &line[..end];
+            !key.is_empty() && key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        }
+        None => false,
+    }
+}
+
+fn is_generated_trailer(line: &str) -> bool {
+    line.starts_with("Signed-off-by: ") || line.starts_with("(cherry picked from commit ")
+}
+
+fn is_continuation(line: &str) -> bool {
+    line.starts_with(|c: char| c.is_whitespace())
+}
+
+/// Resolve the directory of `path`, leaving the file name alone.
+fn absolute(path: &std::path::Path) -> Result<std::path::PathBuf, std::io::Error> {
+    // The file may be gone from the working tree but its directory shouldn't be
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.canonicalize()?,
+        _ => std::env::current_dir()?.canonicalize()?,
+    };
+    Ok(dir.join(path.file_name().unwrap_or_default()))
+}
+
+fn is_hidden(path: &std::path::Path) -> bool {
+    path.components().any(|c| {
+        c.as_os_str()
+            .to_str()
+            .map(|s| s.starts_with('.'))
+            .unwrap_or(false)
+    })
+}
+
+/// Content staged in git's index.
+pub struct Index {
+    root: std::path::PathBuf,
+    cat_file: std::sync::Mutex<CatFile>,
+}
+
+struct CatFile {
+    child: std::process::Child,
+    /// Closing it tells `git cat-file` to exit
+    stdin: Option<std::process::ChildStdin>,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+}
+
+impl Index {
+    fn new(root: std::path::PathBuf) -> Result<Self, std::io::Error> {
+        let mut child = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(&["cat-file", "--batch"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = std::io::BufReader::new(child.stdout.take().expect("stdout was piped"));
+        let cat_file = CatFile {
+            child,
+            stdin,
+            stdout,
+        };
+        Ok(Self {
+            root,
+            cat_file: std::sync::Mutex::new(cat_file),
+        })
+    }
+
+    /// The staged content of `path`, `None` if it isn't in the index.
+    pub fn read(&self, path: &std::path::Path) -> Result<Option<Vec<u8>>, std::io::Error> {
+        let rel = self.relative(path)?;
+        let rel = rel.to_str().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not valid UTF-8", rel.display()),
+            )
+        })?;
+        if rel.contains('\n') {
+            return Ok(None);
+        }
+        // `/` is the only separator git accepts
+        let rel = rel.replace(std::path::MAIN_SEPARATOR, "/");
+
+        let mut cat_file = self.cat_file.lock().unwrap();
+        let stdin = cat_file.stdin.as_mut().expect("only closed on drop");
+        writeln!(stdin, ":{}", rel)?;
+        stdin.flush()?;
+
+        let mut header = String::new();
+        cat_file.stdout.read_line(&mut header)?;
+        let size = match header
+            .trim_end()
+            .rsplitn(3, ' ')
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [size, "blob", _] => size.parse::<usize>().ok(),
+            _ => None,
+        };
+        let size = match size {
+            Some(size) => size,
+            None if header.ends_with(" missing\n") => return Ok(None),
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "unexpected output from `git cat-file`: {}",
+                        header.trim_end()
+                    ),
+                ));
+            }
+        };
+        // Content is followed by a newline
+        let mut content = vec![0; size + 1];
+        cat_file.stdout.read_exact(&mut content)?;
+        content.pop();
+        Ok(Some(content))
+    }
+
+    fn relative(&self, path: &std::path::Path) -> Result<std::path::PathBuf, std::io::Error> {
+        absolute(path)?
+            .strip_prefix(&self.root)
+            .map(|rel| rel.to_owned())
+            .map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is outside of {}", path.display(), self.root.display()),
+                )
+            })
+    }
+}
+
+impl Drop for Index {
+    fn drop(&mut self) {
+        if let Ok(cat_file) = self.cat_file.get_mut() {
+            cat_file.stdin.take();
+            let _ = cat_file.child.wait();
+        }
+    }
+}
+
+/// Find typos in the content staged in git, rather than in the working tree.
+pub struct IndexTypos {
+    index: Index,
+}
+
+impl IndexTypos {
+    pub fn new(index: Index) -> Self {
+        Self { index }
+    }
+}
+
+impl crate::file::FileChecker for IndexTypos {
+    fn check_file(
+        &self,
+        path: &std::path::Path,
+        explicit: bool,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        if policy.check_filenames {
+            crate::file::report_file_name(path, policy, reporter)?;
+        }
+
+        if policy.check_files {
+            match self.index.read(path) {
+                Ok(Some(buffer)) => {
+                    crate::file::report_file_content(path, buffer, explicit, policy, reporter)?;
+                }
+                Ok(None) => {
+                    let msg = report::Error::new("not staged".to_owned())
+                        .context(Some(report::PathContext { path }.into()));
+                    reporter.report(msg.into())?;
+                }
+                Err(err) => {
+                    let msg = report::Error::new(err.to_string())
+                        .context(Some(report::PathContext { path }.into()));
+                    reporter.report(msg.into())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fix typos in staged files, leaving alone files with unstaged changes.
+///
+/// Fixes to a file with unstaged changes couldn't be staged without staging those changes too,
+/// so its typos are only reported.
+pub struct FixStagedTypos<'f> {
+    fix: &'f crate::file::FixTypos,
+    unstaged: std::collections::HashSet<std::path::PathBuf>,
+}
+
+impl<'f> FixStagedTypos<'f> {
+    pub fn new(
+        fix: &'f crate::file::FixTypos,
+        unstaged: std::collections::HashSet<std::path::PathBuf>,
+    ) -> Self {
+        Self { fix, unstaged }
+    }
+}
+
+impl<'f> crate::file::FileChecker for FixStagedTypos<'f> {
+    fn check_file(
+        &self,
+        path: &std::path::Path,
+        explicit: bool,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        let is_unstaged = absolute(path)
+            .map(|p| self.unstaged.contains(&p))
+            .unwrap_or(false);
+        if is_unstaged {
+            let status = crate::file::CorrectionStatus::new(reporter);
+            crate::file::Typos.check_file(path, explicit, policy, &status)?;
+            if status.found() {
+                let msg = report::Error::new("not fixed: has unstaged changes".to_owned())
+                    .context(Some(report::PathContext { path }.into()));
+                reporter.report(msg.into())?;
+            }
+            Ok(())
+        } else {
+            self.fix.check_file(path, explicit, policy, reporter)
+        }
+    }
+
+    fn check_dir(
+        &self,
+        path: &std::path::Path,
+        policy: &crate::policy::Policy,
+        reporter: &dyn report::Report,
+    ) -> Result<(), std::io::Error> {
+        self.fix.check_dir(path, policy, reporter)
+    }
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) -> Result<Vec<u8>, anyhow::Error> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|err| anyhow::format_err!("could not run `git`: {}", err))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("`git {}` failed: {}", args.join(" "), stderr.trim_end())
+    }
+}
+
+/// The mode `git ls-files --stage` lists submodules with.
+const GITLINK_MODE: &[u8] = b"160000 ";
+
+/// The entries of `-z` output.
+fn split_paths(output: &[u8]) -> impl Iterator<Item = &[u8]> {
+    output.split(|b| *b == b'\0').filter(|p| !p.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_message_lines_skips_trailers() {
+        let message = "Fix parser\n\nBody text\n\nSigned-off-by: A U Thor <a@example.com>\nCo-authored-by: Someone\n  Else\n";
+        let actual = message_lines(message, false);
+        assert_eq!(actual, vec![(1, "Fix parser"), (2, ""), (3, "Body text")]);
+    }
+
+    #[test]
+    fn test_message_lines_keeps_prose_with_colons() {
+        let message = "Fix parser\n\nNote: this is prose\nthat continues here\n";
+        let actual = message_lines(message, false);
+        assert_eq!(
+            actual,
+            vec![
+                (1, "Fix parser"),
+                (2, ""),
+                (3, "Note: this is prose"),
+                (4, "that continues here"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_message_lines_mixed_with_sign_off() {
+        let message = "Fix parser\n\nMore details here\nSigned-off-by: A U Thor <a@example.com>\n";
+        let actual = message_lines(message, false);
+        assert_eq!(
+            actual,
+            vec![(1, "Fix parser"), (2, ""), (3, "More details here")]
+        );
+    }
+
+    #[test]
+    fn test_message_lines_subject_is_not_trailer() {
+        let message = "docs: Fix parser\n";
+        let actual = message_lines(message, false);
+        assert_eq!(actual, vec![(1, "docs: Fix parser")]);
+    }
+
+    #[test]
+    fn test_message_lines_strips_comments() {
+        let message = "Fix parser\n# Please enter the commit message\n\n# ------------------------ >8 ------------------------\ndiff --git a/b b/b\n";
+        let actual = message_lines(message, true);
+        assert_eq!(actual, vec![(1, "Fix parser")]);
+    }
+}
@@ -2,12 +2,25 @@
 /// stabilized.
 
 #[doc(hidden)]
-pub mod config;
+pub mod cache;
+pub mod checker;
 #[doc(hidden)]
-pub mod dict;
+pub mod comments;
+#[doc(hidden)]
+pub use typos_config::config;
+#[doc(hidden)]
+pub use typos_config::dict;
+#[doc(hidden)]
+pub mod extract;
 #[doc(hidden)]
 pub mod file;
 #[doc(hidden)]
+pub mod git;
+#[doc(hidden)]
+pub mod markup;
+#[doc(hidden)]
+pub mod notebook;
+#[doc(hidden)]
 pub mod policy;
 #[doc(hidden)]
 pub mod report;
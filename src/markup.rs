@@ -0,0 +1,1038 @@
+//! Find the prose within markup so code, link targets, and the markup itself aren't checked as
+//! if they were words.
+//!
+//! These are deliberately loose scanners rather than full parsers: they only need to know what to
+//! skip, and they work on raw bytes so the offsets of what remains are exact.
+
+use std::ops::Range;
+
+use crate::config::Markup;
+
+/// A part of a buffer to check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Region<'b> {
+    /// Prose
+    Text(Range<usize>),
+    /// Code in a language named by the markup, like the info string of a Markdown fenced block
+    Code { lang: &'b str, range: Range<usize> },
+}
+
+/// The regions of `buffer` to check, in order.
+///
+/// Everything between them, like URLs, tags, and code in no particular language, is skipped.
+pub fn regions(markup: Markup, buffer: &[u8]) -> Vec<Region<'_>> {
+    let mut spans = Spans::default();
+    match markup {
+        Markup::Plain => return vec![Region::Text(0..buffer.len())],
+        Markup::Markdown => markdown(buffer, &mut spans),
+        Markup::Html => html(buffer, &mut spans),
+        Markup::Rst => rst(buffer, &mut spans),
+    }
+    spans.into_regions(buffer)
+}
+
+/// The parts of a buffer that aren't prose.
+#[derive(Default)]
+struct Spans<'b> {
+    /// `None` for what is skipped, otherwise the language of the code
+    spans: Vec<(Range<usize>, Option<&'b str>)>,
+}
+
+impl<'b> Spans<'b> {
+    fn skip(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.spans.push((range, None));
+        }
+    }
+
+    fn code(&mut self, lang: &'b str, range: Range<usize>) {
+        if !range.is_empty() {
+            self.spans.push((range, Some(lang)));
+        }
+    }
+
+    fn into_regions(mut self, buffer: &'b [u8]) -> Vec<Region<'b>> {
+        self.spans.sort_by_key(|(range, _)| range.start);
+        let mut regions = Vec::new();
+        let mut pos = 0;
+        for (range, lang) in self.spans {
+            if pos < range.start {
+                push_text(buffer, pos..range.start, &mut regions);
+            }
+            let start = pos.max(range.start);
+            if let Some(lang) = lang {
+                if start < range.end {
+                    regions.push(Region::Code {
+                        lang,
+                        range: start..range.end,
+                    });
+                }
+            }
+            pos = pos.max(range.end);
+        }
+        push_text(buffer, pos..buffer.len(), &mut regions);
+        regions
+    }
+}
+
+/// Add the prose in `range`, leaving out bare URLs.
+fn push_text(buffer: &[u8], range: Range<usize>, regions: &mut Vec<Region<'_>>) {
+    let mut start = range.start;
+    let mut pos = range.start;
+    while pos < range.end {
+        let at_boundary = pos == range.start || !buffer[pos - 1].is_ascii_alphanumeric();
+        match url_len(&buffer[pos..range.end]) {
+            Some(len) if at_boundary => {
+                if start < pos {
+                    regions.push(Region::Text(start..pos));
+                }
+                pos += len;
+                start = pos;
+            }
+            _ => pos += 1,
+        }
+    }
+    if start < range.end {
+        regions.push(Region::Text(start..range.end));
+    }
+}
+
+/// The length of the URL at the start of `text`, if any.
+fn url_len(text: &[u8]) -> Option<usize> {
+    let scheme = text
+        .iter()
+        .position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'+' | b'.' | b'-')))
+        .unwrap_or(text.len());
+    let rest = &text[scheme..];
+    let is_url = text.starts_with(b"www.")
+        || (0 < scheme && text[0].is_ascii_alphabetic() && rest.starts_with(b"://"))
+        || (text[..scheme].eq_ignore_ascii_case(b"mailto") && rest.starts_with(b":"));
+    if !is_url {
+        return None;
+    }
+
+    let mut len = text
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || matches!(b, b'<' | b'>' | b'"' | b'`'))
+        .unwrap_or(text.len());
+    // Leave trailing punctuation to the sentence around the URL
+    loop {
+        match text[len - 1] {
+            b'.' | b',' | b';' | b':' | b'!' | b'?' | b'\'' | b'*' | b'_' => len -= 1,
+            b')' if count(&text[..len], b'(') < count(&text[..len], b')') => len -= 1,
+            _ => break,
+        }
+    }
+    Some(len)
+}
+
+fn count(text: &[u8], byte: u8) -> usize {
+    text.iter().filter(|b| **b == byte).count()
+}
+
+/// The lines of `buffer`, without their line endings.
+fn lines(buffer: &[u8]) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < buffer.len() {
+        let newline = buffer[start..].iter().position(|b| *b == b'\n');
+        let next = newline.map(|i| start + i + 1).unwrap_or(buffer.len());
+        let mut end = newline.map(|i| start + i).unwrap_or(buffer.len());
+        if start < end && buffer[end - 1] == b'\r' {
+            end -= 1;
+        }
+        lines.push(start..end);
+        start = next;
+    }
+    lines
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|b| b.is_ascii_whitespace())
+}
+
+/// The number of bytes of leading whitespace.
+fn leading(line: &[u8]) -> usize {
+    line.iter()
+        .position(|b| !matches!(b, b' ' | b'\t'))
+        .unwrap_or(line.len())
+}
+
+/// The column of the first non-whitespace character, with tabs stopping every 4 columns.
+fn indent(line: &[u8]) -> usize {
+    line[..leading(line)].iter().fold(0, |column, b| match b {
+        b'\t' => column + 4 - column % 4,
+        _ => column + 1,
+    })
+}
+
+fn find(buffer: &[u8], range: Range<usize>, needle: &[u8]) -> Option<usize> {
+    buffer[range.clone()]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| range.start + i)
+}
+
+/// The first word of an info string or directive argument, like `rust` in `rust,ignore` or
+/// `{.rust}`.
+fn lang(buffer: &[u8], range: Range<usize>) -> &str {
+    let text = std::str::from_utf8(&buffer[range]).unwrap_or_default();
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .find(|s| !s.is_empty())
+        .unwrap_or_default()
+        .trim_matches(|c| matches!(c, '{' | '}' | '.'))
+}
+
+/// Skip a character reference like `&amp;`, returning where it ends.
+fn entity(buffer: &[u8], pos: usize, end: usize, spans: &mut Spans<'_>) -> Option<usize> {
+    let name = pos + 1 + usize::from(buffer.get(pos + 1) == Some(&b'#'));
+    let limit = end.min(name + 32);
+    let semicolon = (name..limit).find(|i| !buffer[*i].is_ascii_alphanumeric())?;
+    if name == semicolon || buffer[semicolon] != b';' {
+        return None;
+    }
+    spans.skip(pos..semicolon + 1);
+    Some(semicolon + 1)
+}
+
+/// An HTML tag, found by `html_tag`.
+struct Tag<'b> {
+    /// Just after the `>`
+    end: usize,
+    name: &'b [u8],
+    closing: bool,
+    /// From a `class` like `language-rust`
+    lang: Option<&'b str>,
+}
+
+/// Attributes holding prose, whose values are still checked.
+const PROSE_ATTRIBUTES: &[&[u8]] = &[b"alt", b"title", b"aria-label", b"placeholder"];
+
+/// Skip the HTML tag, comment, or declaration starting at `pos`, except the values of attributes
+/// holding prose.
+fn html_tag<'b>(
+    buffer: &'b [u8],
+    pos: usize,
+    end: usize,
+    spans: &mut Spans<'b>,
+) -> Option<Tag<'b>> {
+    let text = &buffer[pos..end];
+    if text.starts_with(b"<!--") {
+        let close = find(buffer, pos + 4..end, b"-->")? + 3;
+        spans.skip(pos..close);
+        return Some(Tag {
+            end: close,
+            name: b"",
+            closing: true,
+            lang: None,
+        });
+    }
+    if text.starts_with(b"<!") || text.starts_with(b"<?") {
+        let close = find(buffer, pos + 2..end, b">")? + 1;
+        spans.skip(pos..close);
+        return Some(Tag {
+            end: close,
+            name: b"",
+            closing: true,
+            lang: None,
+        });
+    }
+
+    let closing = text.get(1) == Some(&b'/');
+    let name_start = pos + 1 + usize::from(closing);
+    if !buffer.get(name_start)?.is_ascii_alphabetic() {
+        return None;
+    }
+    let mut p = name_start;
+    while p < end && (buffer[p].is_ascii_alphanumeric() || matches!(buffer[p], b'-' | b':')) {
+        p += 1;
+    }
+    let name = &buffer[name_start..p];
+
+    let mut kept = Vec::new();
+    let mut lang = None;
+    loop {
+        while p < end && buffer[p].is_ascii_whitespace() {
+            p += 1;
+        }
+        if end <= p || buffer[p] == b'<' {
+            return None;
+        }
+        if buffer[p] == b'>' {
+            p += 1;
+            break;
+        }
+        let attr_start = p;
+        while p < end
+            && !buffer[p].is_ascii_whitespace()
+            && !matches!(buffer[p], b'=' | b'>' | b'<')
+        {
+            p += 1;
+        }
+        if p == attr_start {
+            p += 1;
+            continue;
+        }
+        let attr = &buffer[attr_start..p];
+        while p < end && buffer[p].is_ascii_whitespace() {
+            p += 1;
+        }
+        if p < end && buffer[p] == b'=' {
+            p += 1;
+            while p < end && buffer[p].is_ascii_whitespace() {
+                p += 1;
+            }
+            let value = match buffer.get(p) {
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let close = find(buffer, p + 1..end, &[quote])?;
+                    let value = p + 1..close;
+                    p = close + 1;
+                    value
+                }
+                _ => {
+                    let start = p;
+                    while p < end && !buffer[p].is_ascii_whitespace() && buffer[p] != b'>' {
+                        p += 1;
+                    }
+                    start..p
+                }
+            };
+            if PROSE_ATTRIBUTES
+                .iter()
+                .any(|a| attr.eq_ignore_ascii_case(a))
+            {
+                kept.push(value);
+            } else if attr.eq_ignore_ascii_case(b"class") {
+                let classes = std::str::from_utf8(&buffer[value]).unwrap_or_default();
+                lang = classes.split_whitespace().find_map(|class| {
+                    class
+                        .strip_prefix("language-")
+                        .or_else(|| class.strip_prefix("lang-"))
+                });
+            }
+        }
+    }
+
+    let mut skip_from = pos;
+    for value in kept {
+        spans.skip(skip_from..value.start);
+        skip_from = value.end;
+    }
+    spans.skip(skip_from..p);
+    Some(Tag {
+        end: p,
+        name,
+        closing,
+        lang,
+    })
+}
+
+fn html<'b>(buffer: &'b [u8], spans: &mut Spans<'b>) {
+    let end = buffer.len();
+    let mut pos = 0;
+    while pos < end {
+        pos = match buffer[pos] {
+            b'<' => match html_tag(buffer, pos, end, spans) {
+                Some(tag) if !tag.closing => html_content(buffer, &tag, spans),
+                Some(tag) => tag.end,
+                None => pos + 1,
+            },
+            b'&' => entity(buffer, pos, end, spans).unwrap_or(pos + 1),
+            _ => pos + 1,
+        };
+    }
+}
+
+/// Skip the content of elements that aren't prose, returning where to continue.
+fn html_content<'b>(buffer: &'b [u8], tag: &Tag<'b>, spans: &mut Spans<'b>) -> usize {
+    let is = |name: &[u8]| tag.name.eq_ignore_ascii_case(name);
+    if !(is(b"script") || is(b"style") || is(b"code") || is(b"pre")) {
+        return tag.end;
+    }
+    let after = &buffer[tag.end..];
+    if is(b"pre") && after[leading(after)..].starts_with(b"<code") {
+        // Let the `code` say what language it is in
+        return tag.end;
+    }
+
+    let mut close_tag = b"</".to_vec();
+    close_tag.extend_from_slice(tag.name);
+    let lower = buffer[tag.end..].to_ascii_lowercase();
+    let close_tag = close_tag.to_ascii_lowercase();
+    let close = find(&lower, 0..lower.len(), &close_tag)
+        .map(|i| tag.end + i)
+        .unwrap_or(buffer.len());
+    match tag.lang {
+        Some(lang) if is(b"code") => spans.code(lang, tag.end..close),
+        _ => spans.skip(tag.end..close),
+    }
+    close
+}
+
+fn markdown<'b>(buffer: &'b [u8], spans: &mut Spans<'b>) {
+    let lines = lines(buffer);
+    let next_start = |i: usize| lines.get(i + 1).map(|l| l.start).unwrap_or(buffer.len());
+
+    let mut i = front_matter(buffer, &lines, spans);
+    let mut prose = None;
+    let mut prev_blank = true;
+    let mut in_list = false;
+    while i < lines.len() {
+        let line = lines[i].clone();
+        let text = &buffer[line.clone()];
+
+        if let Some(fence) = Fence::open(text) {
+            if let Some(start) = prose.take() {
+                markdown_inline(buffer, start..line.start, spans);
+            }
+            let close = (i + 1..lines.len()).find(|j| fence.closes(&buffer[lines[*j].clone()]));
+            let content = next_start(i)..close.map(|j| lines[j].start).unwrap_or(buffer.len());
+            spans.skip(line.start..content.start);
+            match lang(buffer, line.start + fence.info..line.end) {
+                "" => spans.skip(content),
+                lang => spans.code(lang, content),
+            }
+            if let Some(close) = close {
+                spans.skip(lines[close].clone());
+            }
+            i = close.map(|j| j + 1).unwrap_or(lines.len());
+            prev_blank = false;
+            continue;
+        }
+
+        let blank = is_blank(text);
+        if !blank && prev_blank && !in_list && 4 <= indent(text) {
+            if let Some(start) = prose.take() {
+                markdown_inline(buffer, start..line.start, spans);
+            }
+            let mut last = i;
+            let mut j = i;
+            while j < lines.len() {
+                let text = &buffer[lines[j].clone()];
+                if !is_blank(text) {
+                    if indent(text) < 4 {
+                        break;
+                    }
+                    last = j;
+                }
+                j += 1;
+            }
+            spans.skip(line.start..lines[last].end);
+            i = last + 1;
+            continue;
+        }
+
+        if is_link_definition(text) {
+            if let Some(start) = prose.take() {
+                markdown_inline(buffer, start..line.start, spans);
+            }
+            spans.skip(line);
+            prev_blank = false;
+            i += 1;
+            continue;
+        }
+
+        if !blank {
+            if is_list_item(&text[leading(text)..]) {
+                in_list = true;
+            } else if prev_blank && indent(text) == 0 {
+                in_list = false;
+            }
+            prose.get_or_insert(line.start);
+        }
+        prev_blank = blank;
+        i += 1;
+    }
+    if let Some(start) = prose {
+        markdown_inline(buffer, start..buffer.len(), spans);
+    }
+}
+
+/// Skip the keys of YAML or TOML front matter, returning the line after it.
+fn front_matter(buffer: &[u8], lines: &[Range<usize>], spans: &mut Spans<'_>) -> usize {
+    let delimiter: &[u8] = match lines.first().map(|l| &buffer[l.clone()]) {
+        Some(b"---") => b"---",
+        Some(b"+++") => b"+++",
+        _ => return 0,
+    };
+    let is_yaml = delimiter == b"---";
+    let close = (1..lines.len()).find(|i| {
+        let text = &buffer[lines[*i].clone()];
+        text == delimiter || (is_yaml && text == b"...")
+    });
+    let close = match close {
+        Some(close) => close,
+        None => return 0,
+    };
+
+    spans.skip(lines[0].clone());
+    let separator = if is_yaml { b':' } else { b'=' };
+    for line in &lines[1..close] {
+        let text = &buffer[line.clone()];
+        let start = text
+            .iter()
+            .position(|b| !matches!(b, b' ' | b'\t' | b'-'))
+            .unwrap_or(text.len());
+        if !is_yaml && text[start..].starts_with(b"[") {
+            spans.skip(line.clone());
+        } else if let Some(sep) = text.iter().position(|b| *b == separator) {
+            let key = &text[start..sep];
+            let key = &key[..key.len() - key.iter().rev().take_while(|b| **b == b' ').count()];
+            if !key.is_empty() && !key.iter().any(|b| b.is_ascii_whitespace()) {
+                spans.skip(line.start..line.start + sep + 1);
+            }
+        }
+    }
+    spans.skip(lines[close].clone());
+    close + 1
+}
+
+/// The opening line of a fenced code block.
+struct Fence {
+    byte: u8,
+    len: usize,
+    /// Offset of the info string within the line
+    info: usize,
+}
+
+impl Fence {
+    fn open(line: &[u8]) -> Option<Self> {
+        let start = leading(line);
+        if 3 < indent(line) {
+            return None;
+        }
+        let byte = *line.get(start).filter(|b| matches!(b, b'`' | b'~'))?;
+        let len = line[start..].iter().take_while(|b| **b == byte).count();
+        let info = start + len;
+        if len < 3 || (byte == b'`' && line[info..].contains(&b'`')) {
+            return None;
+        }
+        Some(Self { byte, len, info })
+    }
+
+    fn closes(&self, line: &[u8]) -> bool {
+        let start = leading(line);
+        let len = line[start..]
+            .iter()
+            .take_while(|b| **b == self.byte)
+            .count();
+        indent(line) <= 3 && self.len <= len && is_blank(&line[start + len..])
+    }
+}
+
+fn is_list_item(text: &[u8]) -> bool {
+    let digits = text.iter().take_while(|b| b.is_ascii_digit()).count();
+    let marker = match text.get(digits) {
+        Some(b'.') | Some(b')') if 0 < digits => digits + 1,
+        Some(b'-') | Some(b'*') | Some(b'+') if digits == 0 => 1,
+        _ => return false,
+    };
+    matches!(text.get(marker), None | Some(b' ') | Some(b'\t'))
+}
+
+/// A link reference definition, like `[label]: https://example.com "Title"`.
+fn is_link_definition(line: &[u8]) -> bool {
+    let start = leading(line);
+    if 3 < indent(line) || !line[start..].starts_with(b"[") {
+        return false;
+    }
+    find(line, start..line.len(), b"]:").is_some()
+}
+
+fn markdown_inline<'b>(buffer: &'b [u8], range: Range<usize>, spans: &mut Spans<'b>) {
+    let end = range.end;
+    let mut pos = range.start;
+    while pos < end {
+        pos = match buffer[pos] {
+            b'\\' => pos + 2,
+            b'`' => {
+                let len = buffer[pos..end].iter().take_while(|b| **b == b'`').count();
+                match code_span_end(buffer, pos + len..end, len) {
+                    Some(close) => {
+                        spans.skip(pos..close);
+                        close
+                    }
+                    None => pos + len,
+                }
+            }
+            b'<' => match autolink_end(buffer, pos, end) {
+                Some(close) => {
+                    spans.skip(pos..close);
+                    close
+                }
+                None => html_tag(buffer, pos, end, spans)
+                    .map(|tag| tag.end)
+                    .unwrap_or(pos + 1),
+            },
+            b']' if buffer.get(pos + 1) == Some(&b'(') => {
+                match link_destination_end(buffer, pos + 1, end) {
+                    Some(close) => {
+                        spans.skip(pos + 1..close);
+                        close
+                    }
+                    None => pos + 1,
+                }
+            }
+            b']' if buffer.get(pos + 1) == Some(&b'[') => match find(buffer, pos + 2..end, b"]") {
+                Some(close) => {
+                    spans.skip(pos + 1..close + 1);
+                    close + 1
+                }
+                None => pos + 1,
+            },
+            b'&' => entity(buffer, pos, end, spans).unwrap_or(pos + 1),
+            _ => pos + 1,
+        };
+    }
+}
+
+/// Where a code span opened by `len` backticks ends, after its closing backticks.
+fn code_span_end(buffer: &[u8], range: Range<usize>, len: usize) -> Option<usize> {
+    let mut pos = range.start;
+    while pos < range.end {
+        if buffer[pos] == b'`' {
+            let run = buffer[pos..range.end]
+                .iter()
+                .take_while(|b| **b == b'`')
+                .count();
+            if run == len {
+                return Some(pos + run);
+            }
+            pos += run;
+        } else {
+            pos += 1;
+        }
+    }
+    None
+}
+
+/// Where an autolink like `<https://example.com>` ends, after the `>`.
+fn autolink_end(buffer: &[u8], pos: usize, end: usize) -> Option<usize> {
+    let close =
+        (pos + 1..end).find(|i| matches!(buffer[*i], b'>' | b'<' | b' ' | b'\t' | b'\n'))?;
+    let content = &buffer[pos + 1..close];
+    let is_autolink = buffer[close] == b'>'
+        && (url_len(content).is_some() || (content.contains(&b'@') && content.contains(&b'.')));
+    if is_autolink {
+        Some(close + 1)
+    } else {
+        None
+    }
+}
+
+/// Where the link destination and title opened by the `(` at `pos` end, after the `)`.
+fn link_destination_end(buffer: &[u8], pos: usize, end: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = pos;
+    while i < end {
+        match buffer[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b'\n' if buffer.get(i + 1).map(|b| b.is_ascii_whitespace()) != Some(false) => {
+                return None;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Directives whose arguments and content are code or paths rather than prose.
+const RST_SKIPPED_DIRECTIVES: &[&[u8]] = &[
+    b"highlight",
+    b"image",
+    b"include",
+    b"literalinclude",
+    b"math",
+    b"raw",
+];
+
+/// Directives whose content is code in the language named by their argument.
+const RST_CODE_DIRECTIVES: &[&[u8]] = &[b"code", b"code-block", b"sourcecode"];
+
+fn rst<'b>(buffer: &'b [u8], spans: &mut Spans<'b>) {
+    let lines = lines(buffer);
+    let mut literal_parent = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].clone();
+        let text = &buffer[line.clone()];
+        if is_blank(text) {
+            i += 1;
+            continue;
+        }
+        let indent = indent(text);
+        let start = line.start + leading(text);
+
+        // A paragraph ending with `::` introduces an indented literal block
+        if let Some(parent) = literal_parent.take() {
+            if parent < indent {
+                let end = rst_block_end(buffer, &lines, i, parent);
+                spans.skip(line.start..lines[end - 1].end);
+                i = end;
+                continue;
+            }
+        }
+
+        let body = &buffer[start..line.end];
+        if body.starts_with(b".. ") {
+            i = rst_explicit(buffer, &lines, i, spans);
+            continue;
+        }
+
+        if let Some(name) = rst_field_name_end(buffer, start..line.end) {
+            spans.skip(start..name);
+            rst_inline(buffer, name..line.end, spans);
+        } else {
+            rst_inline(buffer, start..line.end, spans);
+        }
+        if body.ends_with(b"::") {
+            literal_parent = Some(indent);
+        }
+        i += 1;
+    }
+}
+
+/// Handle the explicit markup, like a directive or target, on line `i`, returning the line to
+/// continue with.
+fn rst_explicit<'b>(
+    buffer: &'b [u8],
+    lines: &[Range<usize>],
+    i: usize,
+    spans: &mut Spans<'b>,
+) -> usize {
+    let line = lines[i].clone();
+    let text = &buffer[line.clone()];
+    let indent = indent(text);
+    let marker = line.start + leading(text);
+    let rest = marker + 3;
+    let body = &buffer[rest..line.end];
+    if body.starts_with(b"_") || body.starts_with(b"|") {
+        // Hyperlink targets and substitution definitions are all names and URLs
+        let end = rst_block_end(buffer, lines, i + 1, indent);
+        spans.skip(line.start..lines[end - 1].end);
+        return end;
+    }
+    if body.starts_with(b"[") {
+        // Footnotes and citations are prose after their label
+        let label = find(buffer, rest..line.end, b"]")
+            .map(|i| i + 1)
+            .unwrap_or(rest);
+        spans.skip(line.start..label);
+        rst_inline(buffer, label..line.end, spans);
+        return i + 1;
+    }
+
+    let directive = find(buffer, rest..line.end, b"::").filter(|sep| {
+        let name = &buffer[rest..*sep];
+        !name.is_empty()
+            && name
+                .iter()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'+' | b'.'))
+    });
+    let (name, after) = match directive {
+        Some(sep) => (&buffer[rest..sep], sep + 2),
+        None => {
+            // A comment, which is still prose
+            rst_inline(buffer, rest..line.end, spans);
+            return i + 1;
+        }
+    };
+    let is = |names: &[&[u8]]| names.iter().any(|n| name.eq_ignore_ascii_case(n));
+
+    let end = rst_block_end(buffer, lines, i + 1, indent);
+    if is(RST_SKIPPED_DIRECTIVES) {
+        spans.skip(line.start..lines[end - 1].end);
+        return end;
+    }
+    if is(RST_CODE_DIRECTIVES) {
+        let lang = lang(buffer, after..line.end);
+        // Options come before the content
+        let mut content = i + 1;
+        while content < end {
+            let text = &buffer[lines[content].clone()];
+            if !text[leading(text)..].starts_with(b":") {
+                break;
+            }
+            content += 1;
+        }
+        let content_start = lines.get(content).map(|l| l.start).unwrap_or(buffer.len());
+        spans.skip(line.start..content_start);
+        if content < end {
+            let code = content_start..lines[end - 1].end;
+            match lang {
+                "" => spans.skip(code),
+                lang => spans.code(lang, code),
+            }
+        }
+        return end;
+    }
+
+    // Admonitions and the like hold prose, and their options are field lists
+    spans.skip(line.start..after);
+    rst_inline(buffer, after..line.end, spans);
+    i + 1
+}
+
+/// Where the lines indented more than `parent` starting at line `i` end, as a line index.
+///
+/// Returns `i` when there are none.
+fn rst_block_end(buffer: &[u8], lines: &[Range<usize>], i: usize, parent: usize) -> usize {
+    let mut end = i;
+    for (j, line) in lines.iter().enumerate().skip(i) {
+        let text = &buffer[line.clone()];
+        if is_blank(text) {
+            continue;
+        }
+        if indent(text) <= parent {
+            break;
+        }
+        end = j + 1;
+    }
+    end
+}
+
+/// Where the name of a field like `:param name:` ends, after its closing `:`.
+fn rst_field_name_end(buffer: &[u8], range: Range<usize>) -> Option<usize> {
+    if buffer.get(range.start) != Some(&b':') {
+        return None;
+    }
+    let close = find(buffer, range.start + 1..range.end, b":")?;
+    let name = &buffer[range.start + 1..close];
+    let is_field = !name.is_empty()
+        && !name.contains(&b'`')
+        && !name[0].is_ascii_whitespace()
+        && matches!(buffer.get(close + 1), None | Some(b' ') | Some(b'\t'))
+        && close < range.end;
+    if is_field {
+        Some(close + 1)
+    } else {
+        None
+    }
+}
+
+fn rst_inline<'b>(buffer: &'b [u8], range: Range<usize>, spans: &mut Spans<'b>) {
+    let end = range.end;
+    let mut pos = range.start;
+    let at_boundary = |pos: usize| pos == range.start || !buffer[pos - 1].is_ascii_alphanumeric();
+    while pos < end {
+        let text = &buffer[pos..end];
+        pos = if text.starts_with(b"``") {
+            match find(buffer, pos + 2..end, b"``") {
+                Some(close) => {
+                    spans.skip(pos..close + 2);
+                    close + 2
+                }
+                None => pos + 2,
+            }
+        } else if text[0] == b':' && at_boundary(pos) {
+            // A role like :ref:`target`
+            let name = text[1..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'+'))
+                .count();
+            let open = pos + 1 + name;
+            let close = if 0 < name && buffer[open..end].starts_with(b":`") {
+                find(buffer, open + 2..end, b"`")
+            } else {
+                None
+            };
+            match close {
+                Some(close) => {
+                    spans.skip(pos..close + 1);
+                    close + 1
+                }
+                None => pos + 1,
+            }
+        } else if text[0] == b'`' {
+            match find(buffer, pos + 1..end, b"`") {
+                Some(close) => {
+                    // Only the target of `text <url>`_ is skipped
+                    if buffer[close - 1] == b'>' {
+                        if let Some(open) = buffer[pos..close].iter().rposition(|b| *b == b'<') {
+                            spans.skip(pos + open..close);
+                        }
+                    }
+                    close + 1
+                }
+                None => pos + 1,
+            }
+        } else if text[0] == b'|' && text.get(1).map(|b| b.is_ascii_whitespace()) == Some(false) {
+            // A substitution reference like |name|
+            match find(buffer, pos + 1..end, b"|") {
+                Some(close) if !buffer[close - 1].is_ascii_whitespace() => {
+                    spans.skip(pos..close + 1);
+                    close + 1
+                }
+                _ => pos + 1,
+            }
+        } else if text[0] == b'[' {
+            // A footnote or citation reference like [#note]_
+            match find(buffer, pos + 1..end, b"]") {
+                Some(close) if buffer.get(close + 1) == Some(&b'_') => {
+                    spans.skip(pos..close + 2);
+                    close + 2
+                }
+                _ => pos + 1,
+            }
+        } else {
+            pos + 1
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The checked text, with code prefixed by its language
+    fn checked(markup: Markup, buffer: &str) -> Vec<String> {
+        regions(markup, buffer.as_bytes())
+            .into_iter()
+            .map(|region| match region {
+                Region::Text(range) => buffer[range].trim().to_owned(),
+                Region::Code { lang, range } => format!("{}: {}", lang, buffer[range].trim()),
+            })
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn plain_is_all_text() {
+        let buffer = "Hello `world` https://example.com";
+        assert_eq!(
+            regions(Markup::Plain, buffer.as_bytes()),
+            vec![Region::Text(0..buffer.len())]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_inline_code_and_links() {
+        let buffer = "Use `fn foo()` from [the docs](https://exmaple.com/docz \"Title\") and <https://x.y/zz>.\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["Use", "from [the docs]", "and", "."]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_bare_urls() {
+        let buffer = "See https://exmaple.com/a_(b). Or www.exmaple.com, ok?\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["See", ". Or", ", ok?"]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_html_but_alt_text() {
+        let buffer = "An <img srcset=\"foo.png\" alt=\"A picture\"> here &amp; there\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["An", "A picture", "here", "there"]
+        );
+    }
+
+    #[test]
+    fn markdown_fenced_code_has_lang() {
+        let buffer = "Text\n\n```rust,ignore\nlet x = 1;\n```\n\n~~~\nnot checked\n~~~\nMore\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["Text", "rust: let x = 1;", "More"]
+        );
+    }
+
+    #[test]
+    fn markdown_unclosed_fence() {
+        let buffer = "Text\n```toml\nkey = 1\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["Text", "toml: key = 1"]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_indented_code() {
+        let buffer = "Text\n\n    code\n\n    more code\nDone\n\n- item\n\n    continued\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["Text", "Done\n\n- item\n\n    continued"]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_front_matter_keys() {
+        let buffer = "---\ntitel: Hello\ntags:\n  - one\n---\nBody\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["Hello", "- one", "Body"]
+        );
+
+        let buffer = "+++\ntitel = \"Hello\"\n[extra]\nkee = 1\n+++\nBody\n";
+        assert_eq!(
+            checked(Markup::Markdown, buffer),
+            vec!["\"Hello\"", "1", "Body"]
+        );
+    }
+
+    #[test]
+    fn markdown_skips_link_definitions() {
+        let buffer = "A [link][refrence].\n\n[refrence]: https://example.com\n";
+        assert_eq!(checked(Markup::Markdown, buffer), vec!["A [link]", "."]);
+    }
+
+    #[test]
+    fn offsets_are_exact() {
+        let buffer = "`x` teh";
+        assert_eq!(
+            regions(Markup::Markdown, buffer.as_bytes()),
+            vec![Region::Text(3..7)]
+        );
+    }
+
+    #[test]
+    fn html_skips_tags_and_code() {
+        let buffer = "<!DOCTYPE html>\n<p class=\"intro\" data-fooo=\"bar\">Hello <b>world</b></p>\n<!-- <dvi> -->\n<script>var x;</script>\n<pre><code class=\"language-python\">import os</code></pre>\n<code>y</code>\n";
+        assert_eq!(
+            checked(Markup::Html, buffer),
+            vec!["Hello", "world", "python: import os"]
+        );
+    }
+
+    #[test]
+    fn html_keeps_prose_attributes() {
+        let buffer = "<img src='a.png' title='Some title'>";
+        assert_eq!(checked(Markup::Html, buffer), vec!["Some title"]);
+    }
+
+    #[test]
+    fn rst_skips_literals_and_targets() {
+        let buffer = "Use ``foo_bar`` and :func:`mod.fnuc` with `Link <https://exmaple.com>`_.\n\n.. _target: https://exmaple.com\n\nExample::\n\n    literal blcok\n\nAfter\n";
+        assert_eq!(
+            checked(Markup::Rst, buffer),
+            vec!["Use", "and", "with `Link", "`_.", "Example::", "After"]
+        );
+    }
+
+    #[test]
+    fn rst_code_directive_has_lang() {
+        let buffer = ".. code-block:: python\n   :linenos:\n\n   import os\n\n.. note:: Be careful\n\n   Indeed\n\n.. image:: pictur.png\n   :alt: no\n";
+        assert_eq!(
+            checked(Markup::Rst, buffer),
+            vec!["python: import os", "Be careful\n\n   Indeed"]
+        );
+    }
+
+    #[test]
+    fn rst_skips_field_names() {
+        let buffer = ":paramm x: The value\n";
+        assert_eq!(checked(Markup::Rst, buffer), vec!["The value"]);
+    }
+}
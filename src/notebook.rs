@@ -0,0 +1,268 @@
+//! Read the cells of Jupyter notebooks, keeping track of where their text is in the JSON so fixes
+//! can be written back without disturbing the rest of the file.
+
+use std::ops::Range;
+
+use serde_json::value::RawValue;
+
+/// A Jupyter notebook, in the nbformat 4 layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notebook {
+    /// The language of code cells, as a file extension when the notebook says what it is
+    pub language: Option<String>,
+    pub cells: Vec<Cell>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub kind: CellKind,
+    pub source: Text,
+    /// The text of stream outputs and of plain text results
+    pub outputs: Vec<Text>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    Markdown,
+    Code,
+    Raw,
+}
+
+/// Text stored as JSON strings, like a cell's source that is split into lines.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Text {
+    pub text: String,
+    pub pieces: Vec<Piece>,
+}
+
+/// One of the JSON strings making up a `Text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Piece {
+    /// Where the string, including its quotes, is in the notebook
+    pub raw: Range<usize>,
+    /// Where the decoded string is in `Text::text`
+    pub text: Range<usize>,
+}
+
+impl Notebook {
+    pub fn parse(buffer: &str) -> Result<Self, serde_json::Error> {
+        let notebook: RawNotebook<'_> = serde_json::from_str(buffer)?;
+
+        let language = notebook.metadata.language_info.as_ref().and_then(|info| {
+            info.file_extension
+                .as_deref()
+                .map(|ext| ext.trim_start_matches('.'))
+                .or(info.name.as_deref())
+        });
+        let language = language
+            .or_else(|| {
+                let kernel = notebook.metadata.kernelspec.as_ref()?;
+                kernel.language.as_deref()
+            })
+            .map(|language| language.to_owned());
+
+        let mut cells = Vec::with_capacity(notebook.cells.len());
+        for cell in notebook.cells {
+            let kind = match cell.cell_type.as_str() {
+                "markdown" => CellKind::Markdown,
+                "code" => CellKind::Code,
+                _ => CellKind::Raw,
+            };
+            let source = Text::from_raw(buffer, cell.source)?;
+            let mut outputs = Vec::new();
+            for output in cell.outputs {
+                let text = output
+                    .text
+                    .or_else(|| output.data.get("text/plain").copied());
+                if let Some(text) = text {
+                    outputs.push(Text::from_raw(buffer, text)?);
+                }
+            }
+            cells.push(Cell {
+                kind,
+                source,
+                outputs,
+            });
+        }
+
+        Ok(Self { language, cells })
+    }
+}
+
+impl Text {
+    /// Decode a string or list of strings borrowed from `buffer`.
+    fn from_raw(buffer: &str, value: &RawValue) -> Result<Self, serde_json::Error> {
+        let mut text = Self::default();
+        if value.get().starts_with('[') {
+            let lines: Vec<&RawValue> = serde_json::from_str(value.get())?;
+            for line in lines {
+                text.push(buffer, line)?;
+            }
+        } else {
+            text.push(buffer, value)?;
+        }
+        Ok(text)
+    }
+
+    fn push(&mut self, buffer: &str, value: &RawValue) -> Result<(), serde_json::Error> {
+        let raw = value.get();
+        let decoded: String = serde_json::from_str(raw)?;
+        let start = raw.as_ptr() as usize - buffer.as_ptr() as usize;
+        let text_start = self.text.len();
+        self.text.push_str(&decoded);
+        self.pieces.push(Piece {
+            raw: start..start + raw.len(),
+            text: text_start..self.text.len(),
+        });
+        Ok(())
+    }
+}
+
+/// Replace the JSON strings at each `raw` range with the encoding of its new text.
+pub fn splice(buffer: &[u8], mut edits: Vec<(Range<usize>, String)>) -> Vec<u8> {
+    edits.sort_by_key(|(raw, _)| raw.start);
+    let mut spliced = Vec::with_capacity(buffer.len());
+    let mut pos = 0;
+    for (raw, text) in edits {
+        spliced.extend_from_slice(&buffer[pos..raw.start]);
+        let encoded = serde_json::to_string(&text).expect("strings are always serializable");
+        spliced.extend_from_slice(encoded.as_bytes());
+        pos = raw.end;
+    }
+    spliced.extend_from_slice(&buffer[pos..]);
+    spliced
+}
+
+#[derive(serde::Deserialize)]
+struct RawNotebook<'b> {
+    #[serde(borrow)]
+    cells: Vec<RawCell<'b>>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct Metadata {
+    language_info: Option<LanguageInfo>,
+    kernelspec: Option<KernelSpec>,
+}
+
+#[derive(serde::Deserialize)]
+struct LanguageInfo {
+    name: Option<String>,
+    file_extension: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct KernelSpec {
+    language: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawCell<'b> {
+    cell_type: String,
+    #[serde(borrow)]
+    source: &'b RawValue,
+    #[serde(default, borrow)]
+    outputs: Vec<RawOutput<'b>>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawOutput<'b> {
+    #[serde(default, borrow)]
+    text: Option<&'b RawValue>,
+    #[serde(default, borrow)]
+    data: std::collections::BTreeMap<String, &'b RawValue>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Title\n",
+    "Some \"quoted\" text"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": ["hello\n"]
+    },
+    {
+     "data": {"text/plain": "42", "image/png": "iVBOR"},
+     "output_type": "execute_result"
+    }
+   ],
+   "source": "print('hello')"
+  }
+ ],
+ "metadata": {
+  "kernelspec": {"language": "python", "name": "python3"},
+  "language_info": {"name": "python", "file_extension": ".py"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn parse_cells() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.language.as_deref(), Some("py"));
+        assert_eq!(notebook.cells.len(), 2);
+
+        let markdown = &notebook.cells[0];
+        assert_eq!(markdown.kind, CellKind::Markdown);
+        assert_eq!(markdown.source.text, "# Title\nSome \"quoted\" text");
+        assert_eq!(markdown.source.pieces.len(), 2);
+        let piece = &markdown.source.pieces[1];
+        assert_eq!(&NOTEBOOK[piece.raw.clone()], r#""Some \"quoted\" text""#);
+        assert_eq!(
+            &markdown.source.text[piece.text.clone()],
+            "Some \"quoted\" text"
+        );
+
+        let code = &notebook.cells[1];
+        assert_eq!(code.kind, CellKind::Code);
+        assert_eq!(code.source.text, "print('hello')");
+        let outputs: Vec<_> = code.outputs.iter().map(|o| o.text.as_str()).collect();
+        assert_eq!(outputs, vec!["hello\n", "42"]);
+    }
+
+    #[test]
+    fn language_from_kernel() {
+        let notebook =
+            Notebook::parse(r#"{"cells": [], "metadata": {"kernelspec": {"language": "julia"}}}"#)
+                .unwrap();
+        assert_eq!(notebook.language.as_deref(), Some("julia"));
+    }
+
+    #[test]
+    fn splice_escapes() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        let piece = notebook.cells[0].source.pieces[1].clone();
+        let spliced = splice(
+            NOTEBOOK.as_bytes(),
+            vec![(piece.raw, "Some \"quoted\"\ttext".to_owned())],
+        );
+        let spliced = String::from_utf8(spliced).unwrap();
+        assert!(spliced.contains(r#""Some \"quoted\"\ttext""#));
+
+        let reparsed = Notebook::parse(&spliced).unwrap();
+        assert_eq!(
+            reparsed.cells[0].source.text,
+            "# Title\nSome \"quoted\"\ttext"
+        );
+        assert_eq!(reparsed.cells[1].source.text, notebook.cells[1].source.text);
+    }
+}
@@ -1,3 +1,5 @@
+pub use typos_config::Severities;
+
 pub struct ConfigStorage {
     arena: std::sync::Mutex<typed_arena::Arena<kstring::KString>>,
 }
@@ -42,6 +44,10 @@ pub struct ConfigEngine<'s> {
     walk: Intern<crate::config::Walk>,
     tokenizer: Intern<typos::tokens::Tokenizer>,
     dict: Intern<crate::dict::Override<'s, 's, crate::dict::BuiltIn>>,
+    segmenters: std::collections::HashMap<
+        (std::path::PathBuf, u8),
+        (std::sync::Arc<typos::tokens::Segmenter>, u64),
+    >,
 }
 
 impl<'s> ConfigEngine<'s> {
@@ -54,6 +60,7 @@ impl<'s> ConfigEngine<'s> {
             walk: Default::default(),
             tokenizer: Default::default(),
             dict: Default::default(),
+            segmenters: Default::default(),
         }
     }
 
@@ -89,12 +96,28 @@ impl<'s> ConfigEngine<'s> {
         debug_assert!(path.is_absolute(), "{} is not absolute", path.display());
         let dir = self.get_dir(path).expect("`walk()` should be called first");
         let file_config = dir.get_file_config(path);
+        self.get_policy(dir, &file_config)
+    }
+
+    fn get_policy<'e>(&'e self, dir: &'e DirConfig, file_config: &FileConfig) -> Policy<'e, 'e> {
         Policy {
             check_filenames: file_config.check_filenames,
             check_files: file_config.check_files,
             binary: file_config.binary,
-            tokenizer: self.get_tokenizer(&file_config),
-            dict: self.get_dict(&file_config),
+            fix_symlinks: file_config.fix_symlinks,
+            preserve_mtime: file_config.preserve_mtime,
+            legacy_encodings: file_config.legacy_encodings,
+            archives: file_config.archives,
+            documents: file_config.documents,
+            markup: file_config.markup,
+            comments: file_config.comments,
+            notebook_outputs: file_config.notebook_outputs,
+            repeated_words: file_config.repeated_words,
+            severities: file_config.severities,
+            code_types: Some(CodeTypes { engine: self, dir }),
+            tokenizer: self.get_tokenizer(file_config),
+            dict: self.get_dict(file_config),
+            fingerprint: Some(file_config.fingerprint),
         }
     }
 
@@ -179,6 +202,7 @@ impl<'s> ConfigEngine<'s> {
         let mut type_matcher = ignore::types::TypesBuilder::new();
         type_matcher.add_defaults();
         let mut types: std::collections::HashMap<_, _> = Default::default();
+        let mut reachable = Vec::new();
         for (type_name, type_engine) in type_.into_iter() {
             if type_engine.extend_glob.is_empty() {
                 if type_matcher
@@ -194,13 +218,34 @@ impl<'s> ConfigEngine<'s> {
                 }
             }
 
-            let type_config = self.init_file_config(type_engine.engine);
+            let type_config = self.init_file_config(cwd, type_engine.engine)?;
+            reachable.push((
+                type_name.clone(),
+                type_engine.extend_glob,
+                type_config.fingerprint,
+            ));
             types.insert(type_name, type_config);
         }
         default.update(&overrides);
-        let default = self.init_file_config(default);
+        let mut default = self.init_file_config(cwd, default)?;
+
+        // Code blocks and archive members are checked with the settings of their own types, so
+        // any file's findings can depend on every type
+        reachable.sort();
+        let reachable = hash(format!("{:?}", reachable).as_bytes());
+        default.fingerprint ^= reachable;
+        for type_config in types.values_mut() {
+            type_config.fingerprint ^= reachable;
+        }
 
         type_matcher.select("all");
+        // The last selected glob wins, so let configured types win over built-in ones sharing
+        // their globs, like `md` and `markdown`
+        let mut configured: Vec<_> = types.keys().collect();
+        configured.sort();
+        for type_name in configured {
+            type_matcher.select(type_name.as_str());
+        }
 
         let dir = DirConfig {
             walk,
@@ -213,24 +258,54 @@ impl<'s> ConfigEngine<'s> {
         Ok(())
     }
 
-    fn init_file_config(&mut self, engine: crate::config::EngineConfig) -> FileConfig {
+    fn init_file_config(
+        &mut self,
+        cwd: &std::path::Path,
+        engine: crate::config::EngineConfig,
+    ) -> Result<FileConfig, anyhow::Error> {
+        let mut fingerprint = fingerprint(&engine);
         let binary = engine.binary();
         let check_filename = engine.check_filename();
         let check_file = engine.check_file();
+        let fix_symlinks = engine.fix_symlinks();
+        let preserve_mtime = engine.preserve_mtime();
+        let legacy_encodings = engine.legacy_encodings();
+        let archives = engine.archives();
+        let documents = engine.documents();
+        let markup = engine.markup();
+        let notebook_outputs = engine.notebook_outputs();
+        let repeated_words = engine.repeated_words();
         let crate::config::EngineConfig {
-            tokenizer, dict, ..
+            severity,
+            tokenizer,
+            dict,
+            ..
         } = engine;
+        let severity_config = severity.unwrap_or_else(crate::config::SeverityConfig::from_defaults);
         let tokenizer_config =
             tokenizer.unwrap_or_else(crate::config::TokenizerConfig::from_defaults);
         let dict_config = dict.unwrap_or_else(crate::config::DictConfig::from_defaults);
 
-        let tokenizer = typos::tokens::TokenizerBuilder::new()
+        let mut tokenizer = typos::tokens::TokenizerBuilder::new()
             .unicode(tokenizer_config.unicode())
             .ignore_hex(tokenizer_config.ignore_hex())
             .leading_digits(tokenizer_config.identifier_leading_digits())
+            .escapes(tokenizer_config.escapes())
+            .dashes(tokenizer_config.identifier_dashes())
+            .dots(tokenizer_config.identifier_dots())
             .build();
+        if let Some(path) = tokenizer_config.compound_word_list() {
+            // Config files already resolved this relative to themselves
+            let (segmenter, list_fingerprint) =
+                self.init_segmenter(&cwd.join(path), tokenizer_config.compound_word_confidence())?;
+            tokenizer.segmenter(Some(segmenter));
+            // The list's content matters as much as its path to the cache
+            fingerprint ^= list_fingerprint;
+        }
 
-        let dict = crate::dict::BuiltIn::new(dict_config.locale());
+        let mut dict = crate::dict::BuiltIn::new(dict_config.locale());
+        dict.locale_identifiers(dict_config.locale_identifiers());
+        dict.phrases(dict_config.phrases());
         let mut dict = crate::dict::Override::new(dict);
         dict.identifiers(
             dict_config
@@ -242,20 +317,90 @@ impl<'s> ConfigEngine<'s> {
                 .extend_words()
                 .map(|(k, v)| (self.storage.get(k), self.storage.get(v))),
         );
+        dict.phrases(
+            dict_config
+                .extend_phrases()
+                .map(|(k, v)| (self.storage.get(k), self.storage.get(v))),
+        );
 
         let dict = self.dict.intern(dict);
         let tokenizer = self.tokenizer.intern(tokenizer);
 
-        FileConfig {
+        Ok(FileConfig {
             check_filenames: check_filename,
             check_files: check_file,
             binary,
+            fix_symlinks,
+            preserve_mtime,
+            legacy_encodings,
+            archives,
+            documents,
+            markup,
+            comments: None,
+            notebook_outputs,
+            repeated_words,
+            severities: Severities::from_config(&severity_config),
             tokenizer,
             dict,
+            fingerprint,
+        })
+    }
+
+    fn init_segmenter(
+        &mut self,
+        path: &std::path::Path,
+        confidence: u8,
+    ) -> Result<(std::sync::Arc<typos::tokens::Segmenter>, u64), anyhow::Error> {
+        let key = (path.to_owned(), confidence);
+        if let Some(loaded) = self.segmenters.get(&key) {
+            return Ok(loaded.clone());
         }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| anyhow::format_err!("Could not read {}: {}", path.display(), err))?;
+        let mut segmenter = typos::tokens::Segmenter::new();
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // `word` or `word count`, like most word-frequency lists
+            let mut fields = line.split(|c: char| c.is_whitespace() || c == ',');
+            let word = fields.next().unwrap_or_default();
+            let count = match fields.find(|f| !f.is_empty()) {
+                Some(count) => count.parse().map_err(|_| {
+                    anyhow::format_err!(
+                        "{}:{}: invalid count `{}`",
+                        path.display(),
+                        line_num + 1,
+                        count
+                    )
+                })?,
+                None => 1,
+            };
+            segmenter.insert(word, count);
+        }
+        segmenter.min_confidence(f64::from(confidence) / 100.0);
+
+        let loaded = (std::sync::Arc::new(segmenter), hash(content.as_bytes()));
+        self.segmenters.insert(key, loaded.clone());
+        Ok(loaded)
     }
 }
 
+fn fingerprint(engine: &crate::config::EngineConfig) -> u64 {
+    // Going through `Value` to sort the `HashMap`s
+    let value = serde_json::to_value(engine).expect("config is always serializable");
+    hash(value.to_string().as_bytes())
+}
+
+fn hash(data: &[u8]) -> u64 {
+    let hash = blake3::hash(data);
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash.as_bytes()[0..8]);
+    u64::from_le_bytes(bytes)
+}
+
 struct Intern<T> {
     data: Vec<T>,
 }
@@ -300,11 +445,76 @@ impl DirConfig {
             .and_then(|g| g.file_type_def())
             .map(|f| f.name());
 
-        name.and_then(|name| self.types.get(name).copied())
+        name.map(|name| self.get_type_config(name))
             .unwrap_or(self.default)
     }
+
+    fn get_lang_config(&self, lang: &str) -> Option<FileConfig> {
+        let lang = LANG_ALIASES
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(lang))
+            .map(|(_, name)| *name)
+            .unwrap_or(lang);
+        let known = self
+            .type_matcher
+            .definitions()
+            .iter()
+            .any(|def| def.name() == lang);
+        if known {
+            return Some(self.get_type_config(lang));
+        }
+
+        let path = std::path::PathBuf::from(format!("code.{}", lang));
+        let match_ = self.type_matcher.matched(&path, false);
+        let name = match_.inner().and_then(|g| g.file_type_def())?.name();
+        Some(self.get_type_config(name))
+    }
+
+    fn get_type_config(&self, name: &str) -> FileConfig {
+        let mut file_config = self.types.get(name).copied().unwrap_or(self.default);
+        file_config.comments = crate::comments::syntax(name);
+        file_config
+    }
 }
 
+/// Looks up the settings for content embedded in other files, like code blocks in markup by
+/// their language or archive members by their name.
+#[derive(Copy, Clone)]
+pub struct CodeTypes<'e> {
+    engine: &'e ConfigEngine<'e>,
+    dir: &'e DirConfig,
+}
+
+impl<'e> CodeTypes<'e> {
+    /// The policy for code in `lang`, a file type name or extension, `None` when it is unknown.
+    ///
+    /// Check `Policy::check_files` for whether code in `lang` is checked at all.
+    pub fn policy(&self, lang: &str) -> Option<Policy<'e, 'e>> {
+        let file_config = self.dir.get_lang_config(lang)?;
+        Some(self.engine.get_policy(self.dir, &file_config))
+    }
+
+    /// The policy for an archive member, by its `name` within the archive.
+    pub fn member_policy(&self, name: &std::path::Path) -> Policy<'e, 'e> {
+        let file_config = self.dir.get_file_config(name);
+        self.engine.get_policy(self.dir, &file_config)
+    }
+}
+
+/// Language names, like those in Markdown info strings and notebook kernels, that are neither a
+/// type name nor an extension.
+const LANG_ALIASES: &[(&str, &str)] = &[
+    ("bash", "sh"),
+    ("c++", "cpp"),
+    ("golang", "go"),
+    ("javascript", "js"),
+    ("python", "py"),
+    ("python3", "py"),
+    ("shell", "sh"),
+    ("typescript", "ts"),
+    ("zsh", "sh"),
+];
+
 #[derive(Copy, Clone, Debug)]
 struct FileConfig {
     tokenizer: usize,
@@ -312,6 +522,17 @@ struct FileConfig {
     check_filenames: bool,
     check_files: bool,
     binary: bool,
+    fix_symlinks: bool,
+    preserve_mtime: bool,
+    legacy_encodings: bool,
+    archives: bool,
+    documents: bool,
+    markup: crate::config::Markup,
+    comments: Option<&'static crate::comments::Syntax>,
+    notebook_outputs: bool,
+    repeated_words: bool,
+    severities: Severities,
+    fingerprint: u64,
 }
 
 #[non_exhaustive]
@@ -320,14 +541,159 @@ pub struct Policy<'t, 'd> {
     pub check_filenames: bool,
     pub check_files: bool,
     pub binary: bool,
+    /// Write fixes to the target of a symlink, rather than refusing.
+    pub fix_symlinks: bool,
+    /// Keep the modification time of fixed files, rather than updating it.
+    pub preserve_mtime: bool,
+    /// Decode text that isn't UTF-8 as Shift-JIS, Windows-1252, or Latin-1.
+    pub legacy_encodings: bool,
+    /// Check the content of compressed files and the members of archives.
+    pub archives: bool,
+    /// Check the text of PDF, DOCX, ODT, and EPUB documents.
+    pub documents: bool,
+    /// Only check the prose of files written in this markup language.
+    pub markup: crate::config::Markup,
+    /// How comments are written in the file's language, `None` to check it all as prose.
+    ///
+    /// Outside of comments, every word is checked as part of an identifier.
+    pub comments: Option<&'static crate::comments::Syntax>,
+    /// Check the outputs of Jupyter notebook cells, not just their source.
+    pub notebook_outputs: bool,
+    /// Flag words repeated with only whitespace between them, like `the the`.
+    pub repeated_words: bool,
+    pub severities: Severities,
+    /// Settings for code blocks embedded in markup and archive members, `None` to skip code
+    /// blocks and check members like the archive.
+    pub code_types: Option<CodeTypes<'t>>,
     pub tokenizer: &'t typos::tokens::Tokenizer,
     pub dict: &'d dyn typos::Dictionary,
+    /// Identifies the settings this was resolved from, `None` when unknown.
+    pub fingerprint: Option<u64>,
 }
 
 impl<'t, 'd> Policy<'t, 'd> {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Find the typos in `buffer`, including repeated words when enabled, in order.
+    pub fn check_bytes<'b>(
+        &self,
+        buffer: &'b [u8],
+    ) -> Vec<(typos::Typo<'b>, crate::report::Severity)>
+    where
+        't: 'b,
+        'd: 'b,
+    {
+        let mut found = Vec::new();
+        for (range, is_code) in self.code_regions(buffer) {
+            let offset = range.start;
+            let region = &buffer[range];
+            let typos: Vec<_> = if is_code {
+                typos::check_code_bytes(region, self.tokenizer, self.dict).collect()
+            } else {
+                typos::check_bytes(region, self.tokenizer, self.dict).collect()
+            };
+            found.extend(typos.into_iter().map(|mut typo| {
+                typo.byte_offset += offset;
+                self.with_severity(typo)
+            }));
+            if self.repeated_words {
+                found.extend(
+                    typos::check_repeated_bytes(region, self.tokenizer, self.dict).map(
+                        |mut typo| {
+                            typo.byte_offset += offset;
+                            (typo, self.severities.repeated_words)
+                        },
+                    ),
+                );
+            }
+        }
+        if self.repeated_words {
+            merge_overlapping(&mut found);
+        }
+        found
+    }
+
+    /// Find the typos in `buffer`, including repeated words when enabled, in order.
+    pub fn check_str<'b>(&self, buffer: &'b str) -> Vec<(typos::Typo<'b>, crate::report::Severity)>
+    where
+        't: 'b,
+        'd: 'b,
+    {
+        let mut found = Vec::new();
+        // Comments start and end at ASCII markers or line endings, so at `char` boundaries
+        for (range, is_code) in self.code_regions(buffer.as_bytes()) {
+            let offset = range.start;
+            let region = &buffer[range];
+            let typos: Vec<_> = if is_code {
+                typos::check_code_str(region, self.tokenizer, self.dict).collect()
+            } else {
+                typos::check_str(region, self.tokenizer, self.dict).collect()
+            };
+            found.extend(typos.into_iter().map(|mut typo| {
+                typo.byte_offset += offset;
+                self.with_severity(typo)
+            }));
+            if self.repeated_words {
+                found.extend(
+                    typos::check_repeated_str(region, self.tokenizer, self.dict).map(|mut typo| {
+                        typo.byte_offset += offset;
+                        (typo, self.severities.repeated_words)
+                    }),
+                );
+            }
+        }
+        if self.repeated_words {
+            merge_overlapping(&mut found);
+        }
+        found
+    }
+
+    /// Split `buffer` into its comments and the code around them, flagging the code.
+    ///
+    /// Without `comments`, all of `buffer` is prose.
+    fn code_regions(&self, buffer: &[u8]) -> Vec<(std::ops::Range<usize>, bool)> {
+        let syntax = match self.comments {
+            Some(syntax) => syntax,
+            None => return vec![(0..buffer.len(), false)],
+        };
+        let mut regions = Vec::new();
+        let mut pos = 0;
+        for comment in crate::comments::comments(syntax, buffer) {
+            if pos < comment.start {
+                regions.push((pos..comment.start, true));
+            }
+            pos = comment.end;
+            regions.push((comment, false));
+        }
+        if pos < buffer.len() {
+            regions.push((pos..buffer.len(), true));
+        }
+        regions
+    }
+
+    fn with_severity<'b>(
+        &self,
+        typo: typos::Typo<'b>,
+    ) -> (typos::Typo<'b>, crate::report::Severity) {
+        let severity = self.severities.of(self.dict.source(&typo.typo));
+        (typo, severity)
+    }
+}
+
+/// Sort `typos`, dropping those that overlap an earlier one so they can all be fixed.
+fn merge_overlapping(typos: &mut Vec<(typos::Typo<'_>, crate::report::Severity)>) {
+    // Stable, so dictionary typos win over repeated words at the same offset
+    typos.sort_by_key(|(t, _)| t.byte_offset);
+    let mut end = 0;
+    typos.retain(|(t, _)| {
+        let keep = end <= t.byte_offset;
+        if keep {
+            end = t.byte_offset + t.typo.len();
+        }
+        keep
+    });
 }
 
 static DEFAULT_TOKENIZER: once_cell::sync::Lazy<typos::tokens::Tokenizer> =
@@ -340,8 +706,20 @@ impl<'t, 'd> Default for Policy<'t, 'd> {
             check_filenames: true,
             check_files: true,
             binary: false,
+            fix_symlinks: true,
+            preserve_mtime: false,
+            legacy_encodings: false,
+            archives: false,
+            documents: false,
+            markup: crate::config::Markup::Plain,
+            comments: None,
+            notebook_outputs: false,
+            repeated_words: false,
+            severities: Default::default(),
+            code_types: None,
             tokenizer: &DEFAULT_TOKENIZER,
             dict: &DEFAULT_DICT,
+            fingerprint: None,
         }
     }
 }
@@ -471,6 +849,72 @@ mod test {
         assert!(policy.binary);
     }
 
+    #[test]
+    fn test_policy_fingerprint() {
+        let storage = ConfigStorage::new();
+        let mut engine = ConfigEngine::new(&storage);
+        engine.set_isolated(true);
+
+        let type_name = kstring::KString::from_static(NEVER_EXIST_TYPE);
+
+        let config = crate::config::Config {
+            type_: maplit::hashmap! {
+                type_name.clone() => crate::config::TypeEngineConfig {
+                    extend_glob: vec![type_name],
+                    engine: crate::config::EngineConfig {
+                        binary: Some(true),
+                        ..Default::default()
+                    },
+                },
+            },
+            ..Default::default()
+        };
+        engine.set_overrides(config);
+
+        let cwd = std::path::Path::new(".").canonicalize().unwrap();
+        engine.init_dir(&cwd).unwrap();
+        let default = engine.policy(&cwd.join("Cargo.toml")).fingerprint;
+        let other = engine.policy(&cwd.join("README.md")).fingerprint;
+        let specific = engine.policy(&cwd.join(NEVER_EXIST_TYPE)).fingerprint;
+        assert!(default.is_some());
+        assert_eq!(default, other);
+        assert_ne!(default, specific);
+    }
+
+    #[test]
+    fn test_policy_fingerprint_covers_types() {
+        let cwd = std::path::Path::new(".").canonicalize().unwrap();
+        let fingerprint = |locale| {
+            let storage = ConfigStorage::new();
+            let mut engine = ConfigEngine::new(&storage);
+            engine.set_isolated(true);
+            let config = crate::config::Config {
+                type_: maplit::hashmap! {
+                    kstring::KString::from_static("rust") => crate::config::TypeEngineConfig {
+                        engine: crate::config::EngineConfig {
+                            dict: Some(crate::config::DictConfig {
+                                locale: Some(locale),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            };
+            engine.set_overrides(config);
+            engine.init_dir(&cwd).unwrap();
+            engine.policy(&cwd.join("README.md")).fingerprint
+        };
+
+        // Fenced Rust code in the README is checked with the `rust` settings
+        assert_ne!(
+            fingerprint(crate::config::Locale::EnUs),
+            fingerprint(crate::config::Locale::EnGb)
+        );
+    }
+
     #[test]
     fn test_policy_type_specific() {
         let storage = ConfigStorage::new();
@@ -504,4 +948,80 @@ mod test {
         let policy = engine.policy(&cwd.join(NEVER_EXIST_TYPE));
         assert!(!policy.binary);
     }
+
+    #[test]
+    fn test_policy_type_shares_globs() {
+        let storage = ConfigStorage::new();
+        let mut engine = ConfigEngine::new(&storage);
+        engine.set_isolated(true);
+
+        let config = crate::config::Config {
+            type_: maplit::hashmap! {
+                kstring::KString::from_static("md") => crate::config::TypeEngineConfig {
+                    engine: crate::config::EngineConfig {
+                        markup: Some(crate::config::Markup::Markdown),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        engine.set_overrides(config);
+
+        let cwd = std::path::Path::new(".").canonicalize().unwrap();
+        engine.init_dir(&cwd).unwrap();
+        let policy = engine.policy(&cwd.join("guide.md"));
+        assert_eq!(policy.markup, crate::config::Markup::Markdown);
+        let policy = engine.policy(&cwd.join("Cargo.toml"));
+        assert_eq!(policy.markup, crate::config::Markup::Plain);
+    }
+
+    #[test]
+    fn test_code_types() {
+        let storage = ConfigStorage::new();
+        let mut engine = ConfigEngine::new(&storage);
+        engine.set_isolated(true);
+
+        let config = crate::config::Config {
+            type_: maplit::hashmap! {
+                kstring::KString::from_static("py") => crate::config::TypeEngineConfig {
+                    engine: crate::config::EngineConfig {
+                        check_file: Some(false),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                kstring::KString::from_static("rust") => crate::config::TypeEngineConfig {
+                    engine: crate::config::EngineConfig {
+                        binary: Some(true),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        engine.set_overrides(config);
+
+        let cwd = std::path::Path::new(".").canonicalize().unwrap();
+        engine.init_dir(&cwd).unwrap();
+        let code_types = engine.policy(&cwd.join("guide.md")).code_types.unwrap();
+        assert!(code_types.policy("rust").unwrap().binary);
+        assert!(code_types.policy("rs").unwrap().binary);
+        assert!(!code_types.policy("python").unwrap().check_files);
+        assert!(!code_types.policy("toml").unwrap().binary);
+        assert!(!code_types.policy("py").unwrap().check_files);
+        assert!(code_types.policy(NEVER_EXIST_TYPE).is_none());
+        assert!(
+            code_types
+                .member_policy(std::path::Path::new("src/lib.rs"))
+                .binary
+        );
+        assert!(
+            !code_types
+                .member_policy(std::path::Path::new("setup.py"))
+                .check_files
+        );
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::borrow::Cow;
 
+pub use typos_config::Severity;
+
 pub trait Report: Send + Sync {
     fn report(&self, msg: Message) -> Result<(), std::io::Error>;
 }
@@ -39,6 +41,17 @@ impl<'m> Message<'m> {
         }
     }
 
+    /// How severe the finding is, `None` for messages that aren't findings.
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            Message::BinaryFile(_) => None,
+            Message::Typo(c) => Some(c.severity),
+            Message::File(_) => None,
+            Message::Parse(_) => None,
+            Message::Error(_) => None,
+        }
+    }
+
     pub fn context(self, context: Option<Context<'m>>) -> Self {
         match self {
             Message::Typo(typo) => {
@@ -75,6 +88,7 @@ pub struct Typo<'m> {
     pub byte_offset: usize,
     pub typo: &'m str,
     pub corrections: typos::Status<'m>,
+    pub severity: Severity,
 }
 
 impl<'m> Default for Typo<'m> {
@@ -85,6 +99,7 @@ impl<'m> Default for Typo<'m> {
             byte_offset: 0,
             typo: "",
             corrections: typos::Status::Invalid,
+            severity: Severity::Error,
         }
     }
 }
@@ -95,6 +110,11 @@ impl<'m> Default for Typo<'m> {
 pub enum Context<'m> {
     File(FileContext<'m>),
     Path(PathContext<'m>),
+    Archive(ArchiveContext<'m>),
+    Document(DocumentContext<'m>),
+    Notebook(NotebookContext<'m>),
+    Commit(CommitContext<'m>),
+    Branch(BranchContext<'m>),
 }
 
 impl<'m> std::fmt::Display for Context<'m> {
@@ -102,6 +122,32 @@ impl<'m> std::fmt::Display for Context<'m> {
         match self {
             Context::File(c) => write!(f, "{}:{}", c.path.display(), c.line_num),
             Context::Path(c) => write!(f, "{}", c.path.display()),
+            Context::Archive(c) => write!(
+                f,
+                "{}!{}:{}",
+                c.path.display(),
+                c.member.display(),
+                c.line_num
+            ),
+            Context::Document(c) => {
+                write!(f, "{}", c.path.display())?;
+                if let Some(part) = c.part {
+                    write!(f, "!{}", part)?;
+                }
+                if let Some(page) = c.page {
+                    write!(f, ":page {}", page)?;
+                }
+                write!(f, ":paragraph {}", c.paragraph)
+            }
+            Context::Notebook(c) => {
+                write!(f, "{}:cell {}", c.path.display(), c.cell)?;
+                if let Some(output) = c.output {
+                    write!(f, ":output {}", output)?;
+                }
+                write!(f, ":{}", c.line_num)
+            }
+            Context::Commit(c) => write!(f, "{}:{}", c.commit, c.line_num),
+            Context::Branch(c) => write!(f, "{}", c.branch),
         }
     }
 }
@@ -136,6 +182,108 @@ impl<'m> Default for PathContext<'m> {
     }
 }
 
+/// A line of a file within an archive
+#[derive(Clone, Debug, serde::Serialize, derive_setters::Setters)]
+#[non_exhaustive]
+pub struct ArchiveContext<'m> {
+    /// The archive
+    pub path: &'m std::path::Path,
+    /// The file within the archive
+    pub member: &'m std::path::Path,
+    pub line_num: usize,
+}
+
+impl<'m> Default for ArchiveContext<'m> {
+    fn default() -> Self {
+        Self {
+            path: std::path::Path::new("-"),
+            member: std::path::Path::new("-"),
+            line_num: 0,
+        }
+    }
+}
+
+/// A paragraph of text extracted from a document
+#[derive(Clone, Debug, serde::Serialize, derive_setters::Setters)]
+#[non_exhaustive]
+pub struct DocumentContext<'m> {
+    pub path: &'m std::path::Path,
+    /// The file within the document, for formats made of several like EPUB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<&'m str>,
+    /// The page, for formats with fixed pagination like PDF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    /// Counted from 1, within the page or part when there is one
+    pub paragraph: usize,
+}
+
+impl<'m> Default for DocumentContext<'m> {
+    fn default() -> Self {
+        Self {
+            path: std::path::Path::new("-"),
+            part: None,
+            page: None,
+            paragraph: 0,
+        }
+    }
+}
+
+/// A line of a Jupyter notebook cell
+#[derive(Clone, Debug, serde::Serialize, derive_setters::Setters)]
+#[non_exhaustive]
+pub struct NotebookContext<'m> {
+    pub path: &'m std::path::Path,
+    /// Counted from 1
+    pub cell: usize,
+    /// Counted from 1 within the cell, when the line is from one of its outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<usize>,
+    /// Counted from 1 within the cell's source or output
+    pub line_num: usize,
+}
+
+impl<'m> Default for NotebookContext<'m> {
+    fn default() -> Self {
+        Self {
+            path: std::path::Path::new("-"),
+            cell: 0,
+            output: None,
+            line_num: 0,
+        }
+    }
+}
+
+/// A line of a commit message
+#[derive(Clone, Debug, serde::Serialize, derive_setters::Setters)]
+#[non_exhaustive]
+pub struct CommitContext<'m> {
+    pub commit: &'m str,
+    pub line_num: usize,
+}
+
+impl<'m> Default for CommitContext<'m> {
+    fn default() -> Self {
+        Self {
+            commit: "HEAD",
+            line_num: 0,
+        }
+    }
+}
+
+/// The name of a git branch
+#[derive(Clone, Debug, serde::Serialize, derive_setters::Setters)]
+#[non_exhaustive]
+pub struct BranchContext<'m> {
+    pub branch: &'m str,
+}
+
+impl<'m> Default for BranchContext<'m> {
+    fn default() -> Self {
+        Self { branch: "HEAD" }
+    }
+}
+
 #[derive(Copy, Clone, Debug, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 #[non_exhaustive]
@@ -22,3 +22,1051 @@ fn test_stdin_correct() {
         .write_stdin("Apropriate world");
     cmd.assert().success().stdout("Appropriate world");
 }
+
+#[test]
+fn test_stdin_correct_utf16() {
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("-")
+        .arg("--write-changes")
+        .write_stdin(&b"\xFF\xFEt\x00e\x00h\x00\r\x00\n\x00"[..]);
+    cmd.assert()
+        .success()
+        .stdout(&b"\xFF\xFEt\x00h\x00e\x00\r\x00\n\x00"[..]);
+}
+
+#[test]
+fn test_stdin_correct_utf32() {
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("-")
+        .arg("--format=brief")
+        .write_stdin(&b"\xFF\xFE\x00\x00t\x00\x00\x00e\x00\x00\x00h\x00\x00\x00"[..]);
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("-:1:0: `teh` -> `the`"));
+}
+
+#[test]
+fn test_stdin_streaming_line_num() {
+    let mut input = "Hello world\n".repeat(20_000);
+    input.push_str("Apropriate world\n");
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("-").arg("--format=json").write_stdin(input);
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(r#""line_num":20001"#));
+}
+
+#[test]
+fn test_cache_replays_findings() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let cache_home = temp.child("cache");
+    let sample = temp.child("sample.txt");
+    sample.write_str("Apropriate world").unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.env("XDG_CACHE_HOME", cache_home.path())
+        .arg(sample.path());
+    cmd.assert().code(2);
+    cache_home
+        .child("typos")
+        .assert(predicates::path::missing());
+
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("typos").unwrap();
+        cmd.env("XDG_CACHE_HOME", cache_home.path())
+            .arg("--cache")
+            .arg("--format=brief")
+            .arg(sample.path());
+        cmd.assert()
+            .code(2)
+            .stdout(predicates::str::contains("`Apropriate` -> `Appropriate`"));
+    }
+    cache_home.child("typos").assert(predicates::path::is_dir());
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.env("XDG_CACHE_HOME", cache_home.path())
+        .arg("--clear-cache");
+    cmd.assert().success();
+    cache_home
+        .child("typos")
+        .assert(predicates::path::missing());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_write_renames_tree() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("teh_dir/apropriate.md")
+        .write_str("Hello")
+        .unwrap();
+    temp.child("teh_dir/sub/apropriate.txt")
+        .write_str("Hello")
+        .unwrap();
+    temp.child("teh_dir/sub/appropriate.txt")
+        .write_str("World")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--write-changes")
+        .arg("--isolated")
+        .arg(temp.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("it already exists"));
+
+    temp.child("teh_dir").assert(predicates::path::missing());
+    temp.child("the_dir/appropriate.md").assert("Hello");
+    temp.child("the_dir/sub/apropriate.txt").assert("Hello");
+    temp.child("the_dir/sub/appropriate.txt").assert("World");
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_write_update_references() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    // References are updated even where the old name is otherwise allowed
+    temp.child("_typos.toml")
+        .write_str("[type.txt.extend-words]\napropriate = \"apropriate\"\n")
+        .unwrap();
+    temp.child("apropriate.md").write_str("Hello").unwrap();
+    temp.child("notes.txt")
+        .write_str("See ./apropriate.md\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--write-changes")
+        .arg("--update-references")
+        .arg(temp.path());
+    cmd.assert().success();
+
+    temp.child("appropriate.md").assert("Hello");
+    temp.child("notes.txt").assert("See ./appropriate.md\n");
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_write_update_references_skips_words() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[type.md.extend-words]\nteh = \"teh\"\n[type.txt]\ncheck-file = false\n")
+        .unwrap();
+    temp.child("teh/guide.md").write_str("Hello").unwrap();
+    temp.child("notes.md")
+        .write_str("Read teh/guide.md, not teh notes.\n")
+        .unwrap();
+    temp.child("skipped.txt")
+        .write_str("Read teh/guide.md\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--write-changes")
+        .arg("--update-references")
+        .arg(temp.path());
+    cmd.assert().success();
+
+    temp.child("the/guide.md").assert("Hello");
+    temp.child("notes.md")
+        .assert("Read the/guide.md, not teh notes.\n");
+    // Files whose content isn't checked aren't rewritten
+    temp.child("skipped.txt").assert("Read teh/guide.md\n");
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_diff_renames() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("apropriate.md").write_str("Hello").unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--diff")
+        .arg("--isolated")
+        .arg(temp.child("apropriate.md").path());
+    cmd.assert().stdout(predicates::str::contains(format!(
+        "rename from {}\nrename to {}\n",
+        temp.child("apropriate.md").path().display(),
+        temp.child("appropriate.md").path().display(),
+    )));
+
+    temp.close().unwrap();
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&[
+            "-c",
+            "user.name=typos",
+            "-c",
+            "user.email=typos@example.com",
+        ])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "`git {}` failed", args.join(" "));
+}
+
+#[test]
+fn test_git_tracked() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("tracked.txt").write_str("Apropriate").unwrap();
+    temp.child("untracked.txt").write_str("Hellp").unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(temp.path(), &["add", "tracked.txt"]);
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--git-tracked")
+        .arg("--format=brief")
+        .arg(temp.path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("`Apropriate` -> `Appropriate`"))
+        .stdout(predicates::str::contains("Hellp").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_git_tracked_skips_deleted_and_submodules() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("tracked.txt").write_str("Apropriate").unwrap();
+    temp.child("deleted.txt").write_str("Hellp").unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(temp.path(), &["add", "tracked.txt", "deleted.txt"]);
+    git(
+        temp.path(),
+        &[
+            "update-index",
+            "--add",
+            "--cacheinfo",
+            "160000,0123456789012345678901234567890123456789,submodule",
+        ],
+    );
+    git(temp.path(), &["commit", "-q", "-m", "Initial"]);
+    std::fs::remove_file(temp.child("deleted.txt").path()).unwrap();
+    temp.child("submodule").create_dir_all().unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--git-tracked")
+        .arg("--format=brief")
+        .arg(temp.path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("`Apropriate` -> `Appropriate`"))
+        .stderr(predicates::str::is_empty());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_git_staged_content() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("committed.txt").write_str("Apropriate").unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(temp.path(), &["add", "committed.txt"]);
+    git(temp.path(), &["commit", "-q", "-m", "Initial"]);
+    let staged = temp.child("staged.txt");
+    staged.write_str("Hello adn goodbye").unwrap();
+    git(temp.path(), &["add", "staged.txt"]);
+    staged.write_str("Hello and goodbye").unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--git-staged")
+        .arg("--format=brief")
+        .arg(temp.path());
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--git-staged")
+        .arg("--staged-content")
+        .arg("--format=brief")
+        .arg(temp.path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("`adn` -> `and`"))
+        .stdout(predicates::str::contains("Apropriate").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_commits() {
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(
+        temp.path(),
+        &[
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Add apropriate checks",
+            "-m",
+            "Signed-off-by: Hellp Adn <typos@example.com>",
+        ],
+    );
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--commits=HEAD")
+        .arg("--format=json");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(r#""commit":"#))
+        .stdout(predicates::str::contains(r#""typo":"apropriate""#))
+        .stdout(predicates::str::contains("Hellp").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_commit_msg_file() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let msg = temp.child("COMMIT_EDITMSG");
+    msg.write_str("Fix the parser\n\n# Hellp comment\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--commit-msg").arg(msg.path());
+    cmd.assert().success();
+
+    msg.write_str("Fix teh parser\n").unwrap();
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.arg("--commit-msg")
+        .arg(msg.path())
+        .arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("`teh` -> `the`"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_restage() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let clean = temp.child("clean.txt");
+    let dirty = temp.child("dirty.txt");
+    clean.write_str("Hello\n").unwrap();
+    dirty.write_str("Hello\n").unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-q", "-m", "Initial"]);
+    clean.write_str("Hello adn goodbye\n").unwrap();
+    git(temp.path(), &["add", "clean.txt"]);
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--git-staged")
+        .arg("--write-changes")
+        .arg("--restage");
+    cmd.assert().code(3);
+    clean.assert("Hello and goodbye\n");
+    git(temp.path(), &["diff", "--quiet", "--", "clean.txt"]);
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--git-staged")
+        .arg("--write-changes")
+        .arg("--restage");
+    cmd.assert().success();
+
+    dirty.write_str("Hello adn goodbye\n").unwrap();
+    git(temp.path(), &["add", "dirty.txt"]);
+    dirty.write_str("Hello adn goodbye\nUnstaged\n").unwrap();
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--git-staged")
+        .arg("--write-changes")
+        .arg("--restage")
+        .arg("--format=brief");
+    cmd.assert()
+        .code(1)
+        .stdout(predicates::str::contains("`adn` -> `and`"))
+        .stderr(predicates::str::contains("has unstaged changes"));
+    dirty.assert("Hello adn goodbye\nUnstaged\n");
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_locale_identifiers() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\nlocale = \"en-gb\"\nlocale-identifiers = false\n")
+        .unwrap();
+    temp.child("main.rs")
+        .write_str("// Pick a color\nlet c = set_color(style.color);\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "main.rs:1:10: `color` -> `colour`",
+        ))
+        .stdout(predicates::str::contains("main.rs:2:").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_archives() {
+    use assert_fs::prelude::*;
+    use std::io::Write;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\narchives = true\n")
+        .unwrap();
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(b"Hello\nadn goodbye\n").unwrap();
+    temp.child("notes.txt.gz")
+        .write_binary(&encoder.finish().unwrap())
+        .unwrap();
+
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::fast(),
+    ));
+    let content = b"Apropriate docs\n";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "docs/readme.md", &content[..])
+        .unwrap();
+    let archive = builder.into_inner().unwrap().finish().unwrap();
+    temp.child("docs.tar.gz").write_binary(&archive).unwrap();
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file("guide.txt", zip::write::FileOptions::default())
+        .unwrap();
+    writer.write_all(b"Fine\nFine teh end\n").unwrap();
+    let archive = writer.finish().unwrap().into_inner();
+    temp.child("guide.zip").write_binary(&archive).unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "notes.txt.gz:2:0: `adn` -> `and`",
+        ))
+        .stdout(predicates::str::contains(
+            "docs.tar.gz!docs/readme.md:1:0: `Apropriate` -> `Appropriate`",
+        ))
+        .stdout(predicates::str::contains(
+            "guide.zip!guide.txt:2:5: `teh` -> `the`",
+        ));
+
+    temp.child("_typos.toml").write_str("").unwrap();
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path());
+    cmd.assert().success().stdout(predicates::str::is_empty());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_archive_members() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\narchives = true\n[type.py]\ncheck-file = false\n")
+        .unwrap();
+
+    let mut builder = tar::Builder::new(Vec::new());
+    for &(name, content) in &[
+        ("notes.txt", &b"Fine teh end\n"[..]),
+        ("setup.py", &b"# teh setup\n"[..]),
+        ("data.bin", &b"\0\0teh\0"[..]),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, name, content).unwrap();
+    }
+    let archive = builder.into_inner().unwrap();
+    temp.child("bundle.tar").write_binary(&archive).unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=json");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(r#""member":"notes.txt""#))
+        .stdout(predicates::str::contains(
+            r#"{"type":"binary_file","path":"./bundle.tar!data.bin"}"#,
+        ))
+        .stdout(predicates::str::contains("setup.py").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_documents() {
+    use assert_fs::prelude::*;
+    use std::io::Write;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\ndocuments = true\n")
+        .unwrap();
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file("[Content_Types].xml", zip::write::FileOptions::default())
+        .unwrap();
+    writer.write_all(b"<Types/>").unwrap();
+    writer
+        .start_file("word/document.xml", zip::write::FileOptions::default())
+        .unwrap();
+    writer
+        .write_all(
+            br#"<w:document xmlns:w="w"><w:body>
+<w:p><w:r><w:t>Hello</w:t></w:r></w:p>
+<w:p><w:r><w:t>Hello adn </w:t></w:r><w:r><w:t>goodbye</w:t></w:r></w:p>
+</w:body></w:document>"#,
+        )
+        .unwrap();
+    let docx = writer.finish().unwrap().into_inner();
+    temp.child("report.docx").write_binary(&docx).unwrap();
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file(
+            "mimetype",
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+        )
+        .unwrap();
+    writer
+        .write_all(b"application/vnd.oasis.opendocument.text")
+        .unwrap();
+    writer
+        .start_file("content.xml", zip::write::FileOptions::default())
+        .unwrap();
+    writer
+        .write_all(
+            br#"<office:document-content xmlns:office="o" xmlns:text="t"><office:body><office:text>
+<text:h>Apropriate title</text:h>
+</office:text></office:body></office:document-content>"#,
+        )
+        .unwrap();
+    let odt = writer.finish().unwrap().into_inner();
+    temp.child("notes.odt").write_binary(&odt).unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "report.docx:paragraph 2:6: `adn` -> `and`",
+        ))
+        .stdout(predicates::str::contains(
+            "notes.odt:paragraph 1:0: `Apropriate` -> `Appropriate`",
+        ));
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--write-changes")
+        .arg("--format=brief")
+        .arg(temp.child("report.docx").path());
+    cmd.assert()
+        .code(1)
+        .stdout(predicates::str::contains("`adn` -> `and`"))
+        .stderr(predicates::str::contains("DOCX documents can't be fixed"));
+    assert_eq!(
+        std::fs::read(temp.child("report.docx").path()).unwrap(),
+        docx
+    );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_markup() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[type.md]\nmarkup = \"markdown\"\n\n[type.py]\ncheck-file = false\n")
+        .unwrap();
+    temp.child("guide.md")
+        .write_str(
+            "Use `teh_value` from [docs](https://exmaple.com/teh).
+
+```rust
+let x = \"adn\";
+```
+
+```py
+adn = 1
+```
+
+Fix teh typo.
+",
+        )
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("guide.md:4:9: `adn` -> `and`"))
+        .stdout(predicates::str::contains("guide.md:11:4: `teh` -> `the`"))
+        .stdout(predicates::str::contains("exmaple").not())
+        .stdout(predicates::str::contains(":1:").not())
+        .stdout(predicates::str::contains(":8:").not());
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--write-changes");
+    cmd.assert().code(0);
+    temp.child("guide.md").assert(
+        "Use `teh_value` from [docs](https://exmaple.com/teh).
+
+```rust
+let x = \"and\";
+```
+
+```py
+adn = 1
+```
+
+Fix the typo.
+",
+    );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_notebook() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let notebook = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Title\n",
+    "See `teh_value`, \"adn\" teh rest"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": ["Apropriate\n"]
+    }
+   ],
+   "source": [
+    "x = 1\n",
+    "print(\"adn\\n\")"
+   ]
+  }
+ ],
+ "metadata": {
+  "language_info": {"name": "python", "file_extension": ".py"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("analysis.ipynb").write_str(notebook).unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "analysis.ipynb:cell 1:2:18: `adn` -> `and`",
+        ))
+        .stdout(predicates::str::contains(
+            "analysis.ipynb:cell 1:2:23: `teh` -> `the`",
+        ))
+        .stdout(predicates::str::contains(
+            "analysis.ipynb:cell 2:2:7: `adn` -> `and`",
+        ))
+        .stdout(predicates::str::contains("teh_value").not())
+        .stdout(predicates::str::contains("Apropriate").not());
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=brief")
+        .arg("--config")
+        .arg(temp.child("outputs.toml").path());
+    temp.child("outputs.toml")
+        .write_str("[default]\nnotebook-outputs = true\n")
+        .unwrap();
+    cmd.assert().code(2).stdout(predicates::str::contains(
+        "analysis.ipynb:cell 2:output 1:1:0: `Apropriate` -> `Appropriate`",
+    ));
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--write-changes");
+    cmd.assert().code(0);
+    temp.child("analysis.ipynb").assert(
+        notebook
+            .replace(r#"\"adn\" teh rest"#, r#"\"and\" the rest"#)
+            .replace(r#"print(\"adn\\n\")"#, r#"print(\"and\\n\")"#),
+    );
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_notebook_phrase_across_lines() {
+    use assert_fs::prelude::*;
+
+    let notebook = r#"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "You can\n",
+    "not see teh end"
+   ]
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"#;
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\nphrases = true\n")
+        .unwrap();
+    temp.child("notes.ipynb").write_str(notebook).unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--write-changes")
+        .arg("--format=brief");
+    cmd.assert().code(2).stdout(predicates::str::contains(
+        "notes.ipynb:cell 1:1:4: `can\nnot` -> `cannot`",
+    ));
+    // The phrase spans two JSON strings and is left alone, while the typo within one is fixed
+    temp.child("notes.ipynb")
+        .assert(notebook.replace("not see teh end", "not see the end"));
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_notebook_kernel_language() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let notebook = |language: &str| {
+        format!(
+            r#"{{
+ "cells": [
+  {{"cell_type": "code", "metadata": {{}}, "outputs": [], "source": ["println(\"adn\")"]}}
+ ],
+ "metadata": {{"kernelspec": {{"language": "{}", "name": "{}"}}}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}}
+"#,
+            language, language
+        )
+    };
+    let temp = assert_fs::TempDir::new().unwrap();
+    // Languages without a type are checked like the notebook
+    temp.child("julia.ipynb")
+        .write_str(&notebook("julia"))
+        .unwrap();
+    temp.child("python.ipynb")
+        .write_str(&notebook("python"))
+        .unwrap();
+    temp.child("_typos.toml")
+        .write_str("[type.py]\ncheck-file = false\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "julia.ipynb:cell 1:1:9: `adn` -> `and`",
+        ))
+        .stdout(predicates::str::contains("python.ipynb").not());
+
+    temp.close().unwrap();
+}
+
+#[test]
+fn test_escapes() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\nescapes = true\n")
+        .unwrap();
+    temp.child("main.c")
+        .write_str("printf(\"%sapropriate\\n\\tteh &amp;adn\", name);\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--format=brief");
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "main.c:1:10: `apropriate` -> `appropriate`",
+        ))
+        .stdout(predicates::str::contains("main.c:1:24: `teh` -> `the`"))
+        .stdout(predicates::str::contains("main.c:1:33: `adn` -> `and`"));
+}
+
+#[test]
+fn test_identifier_dashes() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str(
+            "[type.css]\nidentifier-dashes = true\nextend-identifiers = { \"teh-box\" = \"teh-box\" }\n",
+        )
+        .unwrap();
+    temp.child("style.css")
+        .write_str(".teh-box { color: red; }\n.teh-list { color: blue; }\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=brief")
+        .arg(temp.child("style.css").path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains("style.css:2:1: `teh` -> `the`"))
+        .stdout(predicates::str::contains("style.css:1:").not());
+}
+
+#[test]
+fn test_compound_word_list() {
+    use assert_fs::prelude::*;
+    use predicates::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\ncompound-word-list = \"words.txt\"\n")
+        .unwrap();
+    temp.child("words.txt")
+        .write_str("# word count\ncopy 800\nsign 500\ndata 1000\nreceived 300\n")
+        .unwrap();
+    temp.child("main.c")
+        .write_str("copysign(recieveddata);\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=brief")
+        .arg(temp.child("main.c").path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(
+            "main.c:1:9: `recieved` -> `received`",
+        ))
+        .stdout(predicates::str::contains("copy").not());
+}
+
+#[test]
+fn test_compound_word_list_subdir() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\ncompound-word-list = \"words.txt\"\n")
+        .unwrap();
+    temp.child("words.txt")
+        .write_str("copy 800\nsign 500\ndata 1000\nreceived 300\n")
+        .unwrap();
+    temp.child("src/main.c")
+        .write_str("copysign(recieveddata);\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=brief")
+        .arg("src/");
+    cmd.assert().code(2).stdout(predicates::str::contains(
+        "main.c:1:9: `recieved` -> `received`",
+    ));
+
+    temp.child("_typos.toml")
+        .write_str(
+            "[default]\ncompound-word-list = \"words.txt\"\ncompound-word-confidence = 100\n",
+        )
+        .unwrap();
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.child("src").path())
+        .arg("--format=brief")
+        .arg(".");
+    cmd.assert().success().stdout(predicates::str::is_empty());
+}
+
+#[test]
+fn test_extend_phrases() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str(
+            "[default]\nphrases = true\n[default.extend-phrases]\n\"log in in\" = \"log in\"\n",
+        )
+        .unwrap();
+    temp.child("README.md")
+        .write_str("You can not\nlog in in the app.\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--write-changes")
+        .arg(temp.child("README.md").path());
+    cmd.assert().success();
+
+    temp.child("README.md")
+        .assert("You cannot\nlog in the app.\n");
+}
+
+#[test]
+fn test_repeated_words() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[type.md]\nrepeated-words = true\n")
+        .unwrap();
+    temp.child("README.md")
+        .write_str("Read the the\nmanual, that that is fine.\n")
+        .unwrap();
+    temp.child("main.rs")
+        .write_str("let foo foo = 1;\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--write-changes")
+        .arg(temp.child("README.md").path())
+        .arg(temp.child("main.rs").path());
+    cmd.assert().success();
+
+    temp.child("README.md")
+        .assert("Read the\nmanual, that that is fine.\n");
+    temp.child("main.rs").assert("let foo foo = 1;\n");
+}
+
+#[test]
+fn test_severity() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default.severity]\ntypo = \"warning\"\n")
+        .unwrap();
+    temp.child("README.md")
+        .write_str("Hello, apropriate\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=brief")
+        .arg(temp.child("README.md").path());
+    cmd.assert().success().stdout(predicates::str::contains(
+        ":1:7: warning: `apropriate` -> `appropriate`",
+    ));
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--format=json")
+        .arg("--deny-warnings")
+        .arg(temp.child("README.md").path());
+    cmd.assert()
+        .code(2)
+        .stdout(predicates::str::contains(r#""severity":"warning""#));
+}
+
+#[test]
+fn test_phrases_off_by_default() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("README.md")
+        .write_str("You can not park alot of cars.\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path())
+        .arg("--isolated")
+        .arg(temp.child("README.md").path());
+    cmd.assert().success();
+}
+
+#[test]
+fn test_write_phrases_skip_identifiers() {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("_typos.toml")
+        .write_str("[default]\nphrases = true\n")
+        .unwrap();
+    temp.child("main.rs")
+        .write_str("// alot of checks\nlet has_alot = 1;\nfn infact_check() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("typos").unwrap();
+    cmd.current_dir(temp.path()).arg("--write-changes");
+    cmd.assert().success();
+
+    temp.child("main.rs")
+        .assert("// a lot of checks\nlet has_alot = 1;\nfn infact_check() {}\n");
+
+    temp.close().unwrap();
+}